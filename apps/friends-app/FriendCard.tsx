@@ -1,4 +1,3 @@
-import { useState, useEffect } from 'react';
 import { Friend } from './types';
 import { Heatmap } from '../../shared/components/Heatmap';
 import { useImageService } from '../../shared/contexts/ImageContext';
@@ -11,29 +10,7 @@ interface FriendCardProps {
 
 export const FriendCard = ({ friend, onEdit, onDelete }: FriendCardProps) => {
   const imageService = useImageService();
-  const [avatarUrl, setAvatarUrl] = useState<string | null>(null);
-
-  useEffect(() => {
-    const loadAvatar = async () => {
-      if (friend.avatarImage) {
-        try {
-          const data = await imageService.getImage(friend.avatarImage);
-          const blob = new Blob([data], { type: 'image/png' });
-          const url = URL.createObjectURL(blob);
-          setAvatarUrl(url);
-        } catch (e) {
-          console.error('Failed to load avatar:', e);
-        }
-      }
-    };
-    loadAvatar();
-
-    return () => {
-      if (avatarUrl) {
-        URL.revokeObjectURL(avatarUrl);
-      }
-    };
-  }, [friend.avatarImage, imageService]);
+  const avatarUrl = friend.avatarImage ? imageService.imageUrl(friend.avatarImage, 128) : null;
 
   const now = new Date();
 
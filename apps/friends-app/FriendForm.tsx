@@ -29,9 +29,8 @@ export const FriendForm = ({ friend, onSave, onCancel }: FriendFormProps) => {
     try {
       const arrayBuffer = await file.arrayBuffer();
       const data = new Uint8Array(arrayBuffer);
-      const imageName = `avatar-${Date.now()}-${file.name}`;
-      await imageService.saveImage(imageName, data);
-      setAvatarImage(imageName);
+      const imageId = await imageService.saveImage(data);
+      setAvatarImage(imageId);
     } catch (err) {
       console.error('Failed to upload avatar:', err);
     }
@@ -3,9 +3,11 @@ import { useStorage } from '../../shared/contexts/StorageContext';
 import { Friend } from './types';
 import { FriendCard } from './FriendCard';
 import { FriendForm } from './FriendForm';
+import { useImageService, replaceImageOwner } from '../../shared/contexts/ImageContext';
 
 export default function FriendsApp() {
   const storage = useStorage();
+  const imageService = useImageService();
   const [friends, setFriends] = useState<Friend[]>([]);
   const [isFormOpen, setIsFormOpen] = useState(false);
   const [editingFriend, setEditingFriend] = useState<Friend | null>(null);
@@ -38,17 +40,22 @@ export default function FriendsApp() {
       ...friend,
       id: Date.now().toString(),
     };
+    await replaceImageOwner(imageService, `friend:${newFriend.id}`, undefined, newFriend.avatarImage);
     await saveFriends([...friends, newFriend]);
     setIsFormOpen(false);
   };
 
   const handleEditFriend = async (friend: Friend) => {
+    const previous = friends.find((f) => f.id === friend.id);
+    await replaceImageOwner(imageService, `friend:${friend.id}`, previous?.avatarImage, friend.avatarImage);
     const updatedFriends = friends.map((f) => (f.id === friend.id ? friend : f));
     await saveFriends(updatedFriends);
     setEditingFriend(null);
   };
 
   const handleDeleteFriend = async (id: string) => {
+    const friend = friends.find((f) => f.id === id);
+    await replaceImageOwner(imageService, `friend:${id}`, friend?.avatarImage, undefined);
     const updatedFriends = friends.filter((f) => f.id !== id);
     await saveFriends(updatedFriends);
   };
@@ -6,7 +6,7 @@ export interface Meeting {
 export interface Friend {
   id: string;
   name: string;
-  avatarImage?: string; // Image name in image service
+  avatarImage?: string; // Image ID in image service; older records hold a file name
   tags: string[];
   notes: string;
   birthday?: string; // ISO 8601 date string
@@ -1,5 +1,6 @@
+import { useEffect } from "react";
 import { WebviewWindow } from "@tauri-apps/api/webviewWindow";
-import { commands } from "../../shared/api";
+import { commands, events } from "../../shared/api";
 import { useTheme } from "../../shared/contexts/ThemeContext";
 
 interface App {
@@ -45,6 +46,27 @@ const apps: App[] = [
 export default function TauriContainer() {
   const { theme, setTheme } = useTheme();
 
+  // The container stays open, so it shows desktop notifications for weather alerts
+  useEffect(() => {
+    const unlisten = events.weatherAlert.listen(async ({ payload: alert }) => {
+      if (!alert.notify || !("Notification" in window)) return;
+
+      if (Notification.permission === "default") {
+        await Notification.requestPermission();
+      }
+      if (Notification.permission === "granted") {
+        new Notification(`${alert.title} - ${alert.location}`, {
+          body: alert.description,
+          tag: alert.id,
+        });
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   const launchApp = async (app: App) => {
     // Create unique window ID by appending timestamp
     const windowId = `${app.id}-${Date.now()}`;
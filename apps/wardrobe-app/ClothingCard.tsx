@@ -1,7 +1,5 @@
-import { useState, useEffect } from 'react';
 import { ClothingPiece } from './types';
 import { useImageService } from '../../shared/contexts/ImageContext';
-import { convertFileSrc } from '@tauri-apps/api/core';
 
 interface ClothingCardProps {
   clothing: ClothingPiece;
@@ -12,20 +10,7 @@ interface ClothingCardProps {
 
 export const ClothingCard = ({ clothing, onEdit, onDelete, onMarkWorn }: ClothingCardProps) => {
   const imageService = useImageService();
-  const [imageUrl, setImageUrl] = useState<string | null>(null);
-
-  useEffect(() => {
-    const loadImage = async () => {
-      try {
-        const imagePath = await imageService.getImagePath(clothing.image);
-        const assetUrl = convertFileSrc(imagePath);
-        setImageUrl(assetUrl);
-      } catch (e) {
-        console.error('Failed to load image:', e);
-      }
-    };
-    loadImage();
-  }, [clothing.image, imageService]);
+  const imageUrl = clothing.image ? imageService.imageUrl(clothing.image, 512) : null;
 
   const lastWorn = clothing.wornAt.length > 0
     ? new Date(clothing.wornAt[clothing.wornAt.length - 1])
@@ -1,7 +1,9 @@
 import { useState, useRef } from "react";
 import { ClothingPiece, WeatherType, ClothingType } from "./types";
-import { useImageService } from "../../shared/contexts/ImageContext";
-import { commands } from "../../shared/api";
+import {
+  useImageService,
+  ImageServiceError,
+} from "../../shared/contexts/ImageContext";
 
 interface ClothingFormProps {
   clothing?: ClothingPiece;
@@ -51,24 +53,29 @@ export const ClothingForm = ({
     try {
       // Read file as array buffer
       const arrayBuffer = await file.arrayBuffer();
-      const imageData = Array.from(new Uint8Array(arrayBuffer));
+      const imageData = new Uint8Array(arrayBuffer);
 
       // Remove background using Rust backend
-      const result = await commands.removeBackground(imageData);
-      if (result.status === 'error') {
-        throw new Error(result.error);
-      }
-
-      const processedData = new Uint8Array(result.data);
+      const processedData = await imageService.removeBackground(imageData);
 
-      // Save to image service
-      const imageName = `clothing-${Date.now()}-${file.name.replace(
-        /\.[^/.]+$/,
-        ""
-      )}.png`;
-      await imageService.saveImage(imageName, processedData);
-      setImage(imageName);
+      // Save to image service; the piece claims it once saved
+      const imageId = await imageService.saveImage(processedData);
+      setImage(imageId);
     } catch (err) {
+      if (err instanceof ImageServiceError) {
+        if (err.error.kind === 'ModelUnavailable') {
+          alert("Background removal is unavailable: the model is missing.");
+          return;
+        }
+        if (err.error.kind === 'InvalidInput') {
+          alert("This file could not be read as an image.");
+          return;
+        }
+        if (err.error.kind === 'TooLarge') {
+          alert(`This image is too large: ${err.error.message}`);
+          return;
+        }
+      }
       console.error("Failed to process image:", err);
       alert("Failed to process image. Please try again.");
     } finally {
@@ -2,8 +2,7 @@ import { useRef, useState, useEffect, forwardRef, useImperativeHandle } from 're
 import { Stage, Layer, Image as KonvaImage, Transformer } from 'react-konva';
 import Konva from 'konva';
 import { ClothingPiece, FitClothingPosition } from './types';
-import { invoke } from '@tauri-apps/api/core';
-import { convertFileSrc } from '@tauri-apps/api/core';
+import { useImageService } from '../../shared/contexts/ImageContext';
 
 interface FitCanvasProps {
   clothing: ClothingPiece[];
@@ -34,8 +33,10 @@ export const FitCanvas = forwardRef<FitCanvasRef, FitCanvasProps>(
     const imageNodesRef = useRef<Map<string, Konva.Image>>(new Map());
     const transformerRef = useRef<Konva.Transformer>(null);
     const stageRef = useRef<Konva.Stage>(null);
+    const imageService = useImageService();
 
-  // Load images for selected clothing using asset protocol
+  // Load images for selected clothing over the image scheme. It allows
+  // cross-origin reads, so the canvas can still be exported.
   useEffect(() => {
     const loadImages = async () => {
       const newImages = new Map<string, HTMLImageElement>();
@@ -43,11 +44,9 @@ export const FitCanvas = forwardRef<FitCanvasRef, FitCanvasProps>(
       for (const item of selectedClothing) {
         if (!images.has(item.id)) {
           try {
-            const imagePath = await invoke<string>('get_image_path', { name: item.image });
-            const assetUrl = convertFileSrc(imagePath);
-
             const img = new window.Image();
-            img.src = assetUrl;
+            img.crossOrigin = 'anonymous';
+            img.src = imageService.imageUrl(item.image);
             await new Promise((resolve, reject) => {
               img.onload = resolve;
               img.onerror = reject;
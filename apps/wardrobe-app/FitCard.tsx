@@ -1,7 +1,6 @@
 import { useState, useEffect } from 'react';
 import { Fit, ClothingPiece } from './types';
-import { invoke } from '@tauri-apps/api/core';
-import { convertFileSrc } from '@tauri-apps/api/core';
+import { useImageService } from '../../shared/contexts/ImageContext';
 
 interface FitCardProps {
   fit: Fit;
@@ -12,8 +11,9 @@ interface FitCardProps {
 }
 
 export function FitCard({ fit, clothing, onEdit, onDelete, onMarkWorn }: FitCardProps) {
-  const [previewUrl, setPreviewUrl] = useState<string | null>(null);
+  const imageService = useImageService();
   const [imageError, setImageError] = useState(false);
+  const previewUrl = imageService.imageUrl(fit.previewImage, 512);
 
   const fitClothing = fit.clothingPositions
     .map(pos => clothing.find(c => c.id === pos.clothingId))
@@ -23,20 +23,8 @@ export function FitCard({ fit, clothing, onEdit, onDelete, onMarkWorn }: FitCard
     ? new Date(fit.wornAt[fit.wornAt.length - 1]).toLocaleDateString()
     : 'Never';
 
-  // Load preview image using asset protocol
   useEffect(() => {
-    const loadPreview = async () => {
-      try {
-        const imagePath = await invoke<string>('get_image_path', { name: fit.previewImage });
-        const assetUrl = convertFileSrc(imagePath);
-        setPreviewUrl(assetUrl);
-      } catch (e) {
-        console.error(`Failed to load fit preview for ${fit.name}:`, e);
-        setImageError(true);
-      }
-    };
-
-    loadPreview();
+    setImageError(false);
   }, [fit.previewImage]);
 
   return (
@@ -55,6 +43,10 @@ export function FitCard({ fit, clothing, onEdit, onDelete, onMarkWorn }: FitCard
             src={previewUrl}
             alt={fit.name}
             className="w-full h-full object-cover"
+            onError={() => {
+              console.error(`Failed to load fit preview for ${fit.name}`);
+              setImageError(true);
+            }}
           />
         ) : (
           <div className="absolute inset-0 flex items-center justify-center text-secondary">
@@ -1,7 +1,7 @@
 import { useState, useEffect, useRef } from 'react';
 import { ClothingPiece, Fit, FitClothingPosition } from './types';
 import { FitCanvas, FitCanvasRef } from './FitCanvas';
-import { invoke } from '@tauri-apps/api/core';
+import { useImageService } from '../../shared/contexts/ImageContext';
 
 interface FitFormProps {
   fit?: Fit;
@@ -20,6 +20,7 @@ export function FitForm({ fit, clothing, onSave, onCancel }: FitFormProps) {
   );
   const [isSaving, setIsSaving] = useState(false);
   const canvasRef = useRef<FitCanvasRef>(null);
+  const imageService = useImageService();
 
   const selectedClothing = clothing.filter(c => selectedClothingIds.has(c.id));
 
@@ -65,27 +66,20 @@ export function FitForm({ fit, clothing, onSave, onCancel }: FitFormProps) {
         bytes[i] = binaryString.charCodeAt(i);
       }
 
-      // Generate unique image name
-      const imageId = fit?.id || Date.now().toString();
-      const imageName = `fit-preview-${imageId}.png`;
-
-      // Save image to image service
-      await invoke('save_image', {
-        name: imageName,
-        data: Array.from(bytes),
-      });
+      // Save image to image service; the fit claims it once saved
+      const imageId = await imageService.saveImage(bytes);
 
       const fitData: Omit<Fit, 'id'> | Fit = fit
         ? {
             ...fit,
             name: name.trim(),
             clothingPositions: positions,
-            previewImage: imageName,
+            previewImage: imageId,
           }
         : {
             name: name.trim(),
             clothingPositions: positions,
-            previewImage: imageName,
+            previewImage: imageId,
             wornAt: [],
             createdAt: new Date().toISOString(),
           };
@@ -5,12 +5,13 @@ import { ClothingCard } from './ClothingCard';
 import { ClothingForm } from './ClothingForm';
 import { FitCard } from './FitCard';
 import { FitForm } from './FitForm';
-import { invoke } from '@tauri-apps/api/core';
+import { useImageService, replaceImageOwner } from '../../shared/contexts/ImageContext';
 
 type ViewMode = 'clothing' | 'fits';
 
 export default function WardrobeApp() {
   const storage = useStorage();
+  const imageService = useImageService();
   const [viewMode, setViewMode] = useState<ViewMode>('clothing');
   const [clothing, setClothing] = useState<ClothingPiece[]>([]);
   const [fits, setFits] = useState<Fit[]>([]);
@@ -69,17 +70,22 @@ export default function WardrobeApp() {
       ...item,
       id: Date.now().toString(),
     };
+    await replaceImageOwner(imageService, `clothing:${newItem.id}`, undefined, newItem.image);
     await saveClothing([...clothing, newItem]);
     setIsFormOpen(false);
   };
 
   const handleEditClothing = async (item: ClothingPiece) => {
+    const previous = clothing.find((c) => c.id === item.id);
+    await replaceImageOwner(imageService, `clothing:${item.id}`, previous?.image, item.image);
     const updatedClothing = clothing.map((c) => (c.id === item.id ? item : c));
     await saveClothing(updatedClothing);
     setEditingClothing(null);
   };
 
   const handleDeleteClothing = async (id: string) => {
+    const item = clothing.find((c) => c.id === id);
+    await replaceImageOwner(imageService, `clothing:${id}`, item?.image, undefined);
     const updatedClothing = clothing.filter((c) => c.id !== id);
     await saveClothing(updatedClothing);
   };
@@ -108,11 +114,14 @@ export default function WardrobeApp() {
       ...fit,
       id: Date.now().toString(),
     };
+    await replaceImageOwner(imageService, `fit:${newFit.id}`, undefined, newFit.previewImage);
     await saveFits([...fits, newFit]);
     setIsFitFormOpen(false);
   };
 
   const handleEditFit = async (fit: Fit) => {
+    const previous = fits.find((f) => f.id === fit.id);
+    await replaceImageOwner(imageService, `fit:${fit.id}`, previous?.previewImage, fit.previewImage);
     const updatedFits = fits.map((f) => (f.id === fit.id ? fit : f));
     await saveFits(updatedFits);
     setEditingFit(null);
@@ -121,14 +130,8 @@ export default function WardrobeApp() {
   const handleDeleteFit = async (id: string) => {
     const fit = fits.find(f => f.id === id);
 
-    // Delete the fit's preview image if it exists
-    if (fit?.previewImage) {
-      try {
-        await invoke('remove_image', { name: fit.previewImage });
-      } catch (e) {
-        console.error('Failed to delete fit preview image:', e);
-      }
-    }
+    // The preview image goes once nothing else uses it
+    await replaceImageOwner(imageService, `fit:${id}`, fit?.previewImage, undefined);
 
     const updatedFits = fits.filter((f) => f.id !== id);
     await saveFits(updatedFits);
@@ -1,11 +1,13 @@
-export type WeatherType = 'hot' | 'warm' | 'cool' | 'cold' | 'rainy' | 'snowy';
+import type { WeatherType } from '../../shared/types/bindings';
+
+export type { WeatherType };
 export type ClothingType = 'top' | 'bottom' | 'dress' | 'outerwear' | 'shoes' | 'accessory';
 
 export interface ClothingPiece {
   id: string;
   name: string;
   weather: WeatherType[];
-  image: string; // Image name in image service
+  image: string; // Image ID in image service; older records hold a file name
   wornAt: string[]; // Array of ISO 8601 date strings
   type: ClothingType;
 }
@@ -23,7 +25,7 @@ export interface Fit {
   id: string;
   name: string;
   clothingPositions: FitClothingPosition[];
-  previewImage: string; // Image name in image service
+  previewImage: string; // Image ID in image service; older records hold a file name
   wornAt: string[]; // Array of ISO 8601 date strings when the fit was worn
   createdAt: string;
 }
@@ -1,8 +1,14 @@
 import { useState, useEffect } from 'react';
 import { useEvents, useEventListener } from '../../shared/contexts/EventContext';
 import { useStorage } from '../../shared/contexts/StorageContext';
-import { commands } from '../../shared/api';
-import type { WeatherData } from '../../shared/types/bindings';
+import { commands, unwrap } from '../../shared/api';
+import type { UnitSystem, WeatherData } from '../../shared/types/bindings';
+
+const formatTime = (date: string) =>
+  new Date(date).toLocaleTimeString('en-US', { hour: '2-digit', minute: '2-digit' });
+
+const temperatureUnit: Record<UnitSystem, string> = { metric: '°C', imperial: '°F', standard: 'K' };
+const speedUnit: Record<UnitSystem, string> = { metric: 'm/s', imperial: 'mph', standard: 'm/s' };
 
 export default function WeatherApp() {
   const events = useEvents();
@@ -11,6 +17,8 @@ export default function WeatherApp() {
   const [selectedDate, setSelectedDate] = useState<string | null>(null);
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  // When set, the shown weather is cached data fetched at this time
+  const [staleSince, setStaleSince] = useState<string | null>(null);
   const [lat, setLat] = useState<number | null>(null);
   const [lon, setLon] = useState<number | null>(null);
   const [geoError, setGeoError] = useState<string | null>(null);
@@ -25,14 +33,11 @@ export default function WeatherApp() {
     setError(null);
 
     try {
-      const result = await commands.fetchWeatherForDate(lat, lon, date);
-
-      if (result.status === 'error') {
-        throw new Error(result.error);
-      }
-
-      const data = result.data;
+      const { data, stale, fetched_at } = unwrap(
+        await commands.fetchWeatherForDate({ type: "coordinates", lat, lon }, date)
+      );
       setWeatherData(data);
+      setStaleSince(stale ? fetched_at : null);
 
       // Emit weather data changed event
       await events.emit('weather-data-changed', {
@@ -94,18 +99,14 @@ export default function WeatherApp() {
     };
   }, [lat, lon, events, storage]);
 
-  // Get user's location from IP
+  // Get user's location from the configured sources
   useEffect(() => {
     const getLocation = async () => {
       try {
-        const result = await commands.getLocation();
-
-        if (result.status === 'error') {
-          throw new Error(result.error);
-        }
+        const location = unwrap(await commands.getLocation());
 
-        setLat(result.data.latitude);
-        setLon(result.data.longitude);
+        setLat(location.lat);
+        setLon(location.lon);
         setGeoError(null);
       } catch (err) {
         setGeoError(`Failed to get location: ${err}`);
@@ -128,14 +129,11 @@ export default function WeatherApp() {
     setError(null);
 
     try {
-      const result = await commands.fetchWeather(lat, lon);
-
-      if (result.status === 'error') {
-        throw new Error(result.error);
-      }
-
-      const data = result.data;
+      const { data, stale, fetched_at } = unwrap(
+        await commands.fetchWeather({ type: "coordinates", lat, lon })
+      );
       setWeatherData(data);
+      setStaleSince(stale ? fetched_at : null);
 
       // Emit weather data changed event
       await events.emit('weather-data-changed', {
@@ -187,7 +185,7 @@ export default function WeatherApp() {
               })}
             </p>
             <p className="text-secondary text-xs mt-2">
-              5-day forecast available. Select a date within the next 5 days.
+              Past dates show recorded weather. Forecasts reach up to 5 days ahead.
             </p>
           </div>
         )}
@@ -215,6 +213,14 @@ export default function WeatherApp() {
 
           {!loading && !error && weatherData && (
             <div>
+              {staleSince && (
+                <div className="bg-warning/20 border border-warning rounded-lg p-3 mb-4">
+                  <p className="text-warning text-sm">
+                    Showing cached weather from {new Date(staleSince).toLocaleString('en-US')}
+                  </p>
+                </div>
+              )}
+
               <div className="flex items-center justify-between mb-6">
                 <div>
                   <h2 className="text-3xl font-bold text-primary">
@@ -235,13 +241,13 @@ export default function WeatherApp() {
                 <div className="text-center p-4 bg-accent/10 rounded-lg border border-accent/20">
                   <p className="text-sm text-secondary mb-1">Temperature</p>
                   <p className="text-3xl font-bold text-accent">
-                    {Math.round(weatherData.temperature)}°C
+                    {Math.round(weatherData.temperature)}{temperatureUnit[weatherData.units]}
                   </p>
                 </div>
                 <div className="text-center p-4 bg-accent/10 rounded-lg border border-accent/20">
                   <p className="text-sm text-secondary mb-1">Feels Like</p>
                   <p className="text-3xl font-bold text-accent">
-                    {Math.round(weatherData.feels_like)}°C
+                    {Math.round(weatherData.feels_like)}{temperatureUnit[weatherData.units]}
                   </p>
                 </div>
                 <div className="text-center p-4 bg-accent/10 rounded-lg border border-accent/20">
@@ -252,6 +258,34 @@ export default function WeatherApp() {
                 </div>
               </div>
 
+              <div className="grid grid-cols-3 gap-4 mb-6">
+                <div className="text-center p-4 bg-accent/10 rounded-lg border border-accent/20">
+                  <p className="text-sm text-secondary mb-1">Wind</p>
+                  <p className="text-xl font-bold text-accent">
+                    {weatherData.wind_speed.toFixed(1)} {speedUnit[weatherData.units]}
+                  </p>
+                  {weatherData.wind_gust !== null && (
+                    <p className="text-xs text-secondary mt-1">
+                      Gusts {weatherData.wind_gust.toFixed(1)} {speedUnit[weatherData.units]}
+                    </p>
+                  )}
+                </div>
+                <div className="text-center p-4 bg-accent/10 rounded-lg border border-accent/20">
+                  <p className="text-sm text-secondary mb-1">Pressure</p>
+                  <p className="text-xl font-bold text-accent">
+                    {weatherData.pressure} hPa
+                  </p>
+                </div>
+                <div className="text-center p-4 bg-accent/10 rounded-lg border border-accent/20">
+                  <p className="text-sm text-secondary mb-1">Daylight</p>
+                  <p className="text-xl font-bold text-accent">
+                    {weatherData.sunrise && weatherData.sunset
+                      ? `${formatTime(weatherData.sunrise)} – ${formatTime(weatherData.sunset)}`
+                      : '—'}
+                  </p>
+                </div>
+              </div>
+
               <button
                 onClick={fetchWeather}
                 className="w-full px-4 py-3 bg-accent text-white rounded-lg hover:bg-accent/90 transition-colors font-semibold"
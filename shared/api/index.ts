@@ -1,2 +1,20 @@
+import type { AppError, Result } from '../types/bindings';
+
 // Re-export type-safe Tauri commands
-export { commands, type GreetResponse } from '../types/bindings';
+export { commands, events, type GreetResponse } from '../types/bindings';
+
+// Error returned by a command, carrying the backend's error kind so callers
+// can branch on it
+export class CommandError extends Error {
+  constructor(public readonly error: AppError) {
+    super(error.message);
+  }
+}
+
+// The data of a successful command, or its error thrown as a CommandError
+export const unwrap = <T>(result: Result<T, AppError>): T => {
+  if (result.status === 'error') {
+    throw new CommandError(result.error);
+  }
+  return result.data;
+};
@@ -1,18 +1,63 @@
 import { createContext, useContext, ReactNode, useState } from 'react';
-import { isTauri } from '@tauri-apps/api/core';
-import { commands } from '../api';
-import type { ImageInfo } from '../types/bindings';
+import { convertFileSrc, isTauri } from '@tauri-apps/api/core';
+import { commands, CommandError } from '../api';
+import type { AppError, ImageInfo, Result } from '../types/bindings';
 
+// Images are stored once per distinct content and addressed by ID. Records
+// using an image claim it as its owner (e.g. `clothing:<id>`); an image is
+// deleted once its last owner releases it, and images nobody claims are
+// cleaned up after a grace period.
+//
+// Image bytes travel over the `image://` scheme as raw request and response
+// bodies; `imageUrl` can be used directly as an <img> source and is cached by
+// the webview.
 interface ImageServiceInterface {
-  saveImage: (name: string, data: Uint8Array) => Promise<string>;
-  getImage: (name: string) => Promise<Uint8Array>;
-  getImagePath: (name: string) => Promise<string>;
-  removeImage: (name: string) => Promise<void>;
+  saveImage: (data: Uint8Array, owner?: string) => Promise<string>;
+  getImage: (id: string) => Promise<Uint8Array>;
+  imageUrl: (id: string, size?: number) => string;
+  removeBackground: (data: Uint8Array) => Promise<Uint8Array>;
+  getImagePath: (id: string) => Promise<string>;
+  getImageVariant: (id: string, size: number) => Promise<string>;
+  retainImage: (id: string, owner: string) => Promise<void>;
+  releaseImage: (id: string, owner: string) => Promise<boolean>;
   listImages: () => Promise<ImageInfo[]>;
 }
 
 const ImageContext = createContext<ImageServiceInterface | null>(null);
 
+// Error from the image scheme or commands, carrying the backend's error kind
+export class ImageServiceError extends CommandError {}
+
+const imageUrl = (id: string, size?: number) =>
+  convertFileSrc(id, 'image') + (size ? `?size=${size}` : '');
+
+const imageRequest = async (url: string, body?: Uint8Array) => {
+  const response = await fetch(url, body ? { method: 'POST', body } : undefined);
+  if (!response.ok) {
+    // Only errors raised by the backend carry an AppError body
+    const isJson = response.headers.get('content-type')?.startsWith('application/json');
+    throw new ImageServiceError(
+      isJson
+        ? await response.json()
+        : {
+            kind: 'Upstream',
+            status: response.status,
+            message: `Image request failed with status ${response.status}`,
+          }
+    );
+  }
+  return response;
+};
+
+// Command errors are thrown as ImageServiceError too, so callers handle the
+// scheme and commands alike
+const imageCommand = <T,>(result: Result<T, AppError>): T => {
+  if (result.status === 'error') {
+    throw new ImageServiceError(result.error);
+  }
+  return result.data;
+};
+
 // Detect if we're running in Tauri context
 const isTauriContext = () => {
   return isTauri();
@@ -20,58 +65,63 @@ const isTauriContext = () => {
 
 // Tauri image service implementation
 const createTauriImageService = (): ImageServiceInterface => ({
-  saveImage: async (name: string, data: Uint8Array) => {
-    const result = await commands.saveImage(name, Array.from(data));
-    if (result.status === 'error') {
-      throw new Error(result.error);
-    }
-    return result.data;
+  saveImage: async (data: Uint8Array, owner?: string) => {
+    const query = owner ? `?owner=${encodeURIComponent(owner)}` : '';
+    const response = await imageRequest(imageUrl('') + query, data);
+    return response.text();
   },
-  getImage: async (name: string) => {
-    const result = await commands.getImage(name);
-    if (result.status === 'error') {
-      throw new Error(result.error);
-    }
-    return new Uint8Array(result.data);
+  getImage: async (id: string) => {
+    const response = await imageRequest(imageUrl(id));
+    return new Uint8Array(await response.arrayBuffer());
   },
-  getImagePath: async (name: string) => {
-    const result = await commands.getImagePath(name);
-    if (result.status === 'error') {
-      throw new Error(result.error);
-    }
-    return result.data;
+  imageUrl,
+  removeBackground: async (data: Uint8Array) => {
+    const response = await imageRequest(imageUrl('background-removal'), data);
+    return new Uint8Array(await response.arrayBuffer());
   },
-  removeImage: async (name: string) => {
-    const result = await commands.removeImage(name);
-    if (result.status === 'error') {
-      throw new Error(result.error);
-    }
-  },
-  listImages: async () => {
-    const result = await commands.listImages();
-    if (result.status === 'error') {
-      throw new Error(result.error);
-    }
-    return result.data;
+  getImagePath: async (id: string) => imageCommand(await commands.getImagePath(id)),
+  getImageVariant: async (id: string, size: number) =>
+    imageCommand(await commands.getImageVariant(id, size)),
+  retainImage: async (id: string, owner: string) => {
+    imageCommand(await commands.retainImage(id, owner));
   },
+  releaseImage: async (id: string, owner: string) =>
+    imageCommand(await commands.releaseImage(id, owner)),
+  listImages: async () => imageCommand(await commands.listImages()),
 });
 
 // Web stub implementation
 const createWebImageService = (): ImageServiceInterface => ({
-  saveImage: async (name: string, data: Uint8Array) => {
-    console.log('[WebImageService] saveImage not implemented:', name);
+  saveImage: async (data: Uint8Array, owner?: string) => {
+    console.log('[WebImageService] saveImage not implemented:', data.length, owner);
     return '';
   },
-  getImage: async (name: string) => {
-    console.log('[WebImageService] getImage not implemented:', name);
+  getImage: async (id: string) => {
+    console.log('[WebImageService] getImage not implemented:', id);
     return new Uint8Array();
   },
-  getImagePath: async (name: string) => {
-    console.log('[WebImageService] getImagePath not implemented:', name);
+  imageUrl: (id: string) => {
+    console.log('[WebImageService] imageUrl not implemented:', id);
     return '';
   },
-  removeImage: async (name: string) => {
-    console.log('[WebImageService] removeImage not implemented:', name);
+  removeBackground: async (data: Uint8Array) => {
+    console.log('[WebImageService] removeBackground not implemented:', data.length);
+    return data;
+  },
+  getImagePath: async (id: string) => {
+    console.log('[WebImageService] getImagePath not implemented:', id);
+    return '';
+  },
+  getImageVariant: async (id: string, size: number) => {
+    console.log('[WebImageService] getImageVariant not implemented:', id, size);
+    return '';
+  },
+  retainImage: async (id: string, owner: string) => {
+    console.log('[WebImageService] retainImage not implemented:', id, owner);
+  },
+  releaseImage: async (id: string, owner: string) => {
+    console.log('[WebImageService] releaseImage not implemented:', id, owner);
+    return false;
   },
   listImages: async () => {
     console.log('[WebImageService] listImages not implemented');
@@ -95,6 +145,26 @@ export const ImageProvider = ({ children }: ImageProviderProps) => {
   );
 };
 
+/**
+ * Move `owner`'s claim from its previous image to its next one, when a record
+ * is added (no previous), edited, or deleted (no next). Failures are logged so
+ * they never block saving the record itself.
+ */
+export const replaceImageOwner = async (
+  imageService: ImageServiceInterface,
+  owner: string,
+  previous: string | undefined,
+  next: string | undefined
+) => {
+  if (previous === next) return;
+  try {
+    if (next) await imageService.retainImage(next, owner);
+    if (previous) await imageService.releaseImage(previous, owner);
+  } catch (e) {
+    console.error(`Failed to update images of ${owner}:`, e);
+  }
+};
+
 export const useImageService = (): ImageServiceInterface => {
   const context = useContext(ImageContext);
   if (!context) {
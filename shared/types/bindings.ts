@@ -8,23 +8,235 @@ export const commands = {
 async greet(name: string) : Promise<GreetResponse> {
     return await TAURI_INVOKE("greet", { name });
 },
-async fetchWeather(lat: number, lon: number) : Promise<Result<WeatherData, string>> {
+async fetchWeather(location: LocationQuery) : Promise<Result<WeatherResponse<WeatherData>, AppError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("fetch_weather", { lat, lon }) };
+    return { status: "ok", data: await TAURI_INVOKE("fetch_weather", { location }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async fetchWeatherForDate(lat: number, lon: number, date: string) : Promise<Result<WeatherData, string>> {
+async fetchWeatherForDate(location: LocationQuery, date: string) : Promise<Result<WeatherResponse<WeatherData>, AppError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("fetch_weather_for_date", { lat, lon, date }) };
+    return { status: "ok", data: await TAURI_INVOKE("fetch_weather_for_date", { location, date }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getLocation() : Promise<Result<Coordinates, string>> {
+async fetchForecast(location: LocationQuery) : Promise<Result<WeatherResponse<ForecastPoint[]>, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("fetch_forecast", { location }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async fetchDailyForecast(location: LocationQuery) : Promise<Result<WeatherResponse<DailyForecast[]>, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("fetch_daily_forecast", { location }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getWeatherProvider() : Promise<WeatherProviderKind> {
+    return await TAURI_INVOKE("get_weather_provider");
+},
+async setWeatherProvider(provider: WeatherProviderKind) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_weather_provider", { provider }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getWeatherPreferences() : Promise<WeatherPreferences> {
+    return await TAURI_INVOKE("get_weather_preferences");
+},
+/**
+ * Change units or condition language. Returns the preferences as stored,
+ * with the language code normalized.
+ */
+async setWeatherPreferences(preferences: WeatherPreferences) : Promise<Result<WeatherPreferences, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_weather_preferences", { preferences }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Clothing weather tags for a reading, e.g. `["cool", "rainy"]`
+ */
+async classifyWeather(weather: WeatherData) : Promise<WeatherType[]> {
+    return await TAURI_INVOKE("classify_weather", { weather });
+},
+async getClassifierThresholds() : Promise<ClassifierThresholds> {
+    return await TAURI_INVOKE("get_classifier_thresholds");
+},
+/**
+ * Change the classifier thresholds. Returns them as stored.
+ */
+async setClassifierThresholds(thresholds: ClassifierThresholds) : Promise<Result<ClassifierThresholds, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_classifier_thresholds", { thresholds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Cached weather responses with their age, newest first
+ */
+async listWeatherCache() : Promise<WeatherCacheEntry[]> {
+    return await TAURI_INVOKE("list_weather_cache");
+},
+/**
+ * Drop cached weather of one kind, for one location, or both.
+ * Returns the number of entries removed.
+ */
+async invalidateWeatherCache(kind: WeatherCacheKind | null, lat: number | null, lon: number | null) : Promise<Result<number, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("invalidate_weather_cache", { kind, lat, lon }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async clearWeatherCache() : Promise<null> {
+    return await TAURI_INVOKE("clear_weather_cache");
+},
+async getWeatherCacheStats() : Promise<WeatherCacheStats> {
+    return await TAURI_INVOKE("get_weather_cache_stats");
+},
+async getWeatherCacheConfig() : Promise<WeatherCacheConfig> {
+    return await TAURI_INVOKE("get_weather_cache_config");
+},
+/**
+ * Change the cache settings. Returns them as stored.
+ */
+async setWeatherCacheConfig(config: WeatherCacheConfig) : Promise<Result<WeatherCacheConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_weather_cache_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAlertConfig() : Promise<AlertConfig> {
+    return await TAURI_INVOKE("get_alert_config");
+},
+async setAlertConfig(config: AlertConfig) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_alert_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Severe weather alerts in effect for the watched locations, or for one
+ * saved location
+ */
+async getActiveAlerts(locationId: string | null) : Promise<WeatherAlert[]> {
+    return await TAURI_INVOKE("get_active_alerts", { locationId });
+},
+/**
+ * Check the watched locations now instead of waiting for the next poll
+ */
+async checkWeatherAlerts() : Promise<Result<WeatherAlert[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("check_weather_alerts") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listSavedLocations() : Promise<SavedLocation[]> {
+    return await TAURI_INVOKE("list_saved_locations");
+},
+async addSavedLocation(location: LocationInput) : Promise<Result<SavedLocation, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_saved_location", { location }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateSavedLocation(id: string, location: LocationInput) : Promise<Result<SavedLocation, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_saved_location", { id, location }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeSavedLocation(id: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_saved_location", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDefaultLocation() : Promise<SavedLocation | null> {
+    return await TAURI_INVOKE("get_default_location");
+},
+/**
+ * Location made active on launch; `null` clears it
+ */
+async setDefaultLocation(id: string | null) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_default_location", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getActiveLocation() : Promise<SavedLocation | null> {
+    return await TAURI_INVOKE("get_active_location");
+},
+async setActiveLocation(id: string) : Promise<Result<SavedLocation, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_active_location", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Whether weather and location services were reachable on the last request
+ */
+async getConnectivity() : Promise<Connectivity> {
+    return await TAURI_INVOKE("get_connectivity");
+},
+/**
+ * Places matching a name such as "Kraków" or "Portland, Oregon"
+ */
+async geocode(query: string) : Promise<Result<Place[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("geocode", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Place at or nearest to the coordinates
+ */
+async reverseGeocode(lat: number, lon: number) : Promise<Result<Place, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reverse_geocode", { lat, lon }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Current device location and the source that provided it
+ */
+async getLocation() : Promise<Result<Geolocation, AppError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("get_location") };
 } catch (e) {
@@ -32,39 +244,108 @@ async getLocation() : Promise<Result<Coordinates, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async saveImage(name: string, data: number[]) : Promise<Result<string, string>> {
+async getGeolocationConfig() : Promise<GeolocationConfig> {
+    return await TAURI_INVOKE("get_geolocation_config");
+},
+/**
+ * Change the location sources; turning off `network_lookup` stops IP lookups
+ */
+async setGeolocationConfig(config: GeolocationConfig) : Promise<Result<null, AppError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("save_image", { name, data }) };
+    return { status: "ok", data: await TAURI_INVOKE("set_geolocation_config", { config }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getImage(name: string) : Promise<Result<number[], string>> {
+async getImagePath(id: string) : Promise<Result<string, AppError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_image", { name }) };
+    return { status: "ok", data: await TAURI_INVOKE("get_image_path", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getImagePath(name: string) : Promise<Result<string, string>> {
+/**
+ * Path of a copy of an image downscaled to the configured size closest to
+ * `size` pixels on its longest side
+ */
+async getImageVariant(id: string, size: number) : Promise<Result<string, AppError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_image_path", { name }) };
+    return { status: "ok", data: await TAURI_INVOKE("get_image_variant", { id, size }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async removeImage(name: string) : Promise<Result<null, string>> {
+async getImageVariantConfig() : Promise<Result<VariantConfig, AppError>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("remove_image", { name }) };
+    return { status: "ok", data: await TAURI_INVOKE("get_image_variant_config") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async listImages() : Promise<Result<ImageInfo[], string>> {
+async setImageVariantConfig(config: VariantConfig) : Promise<Result<VariantConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_image_variant_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getUploadLimits() : Promise<Result<UploadLimits, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_upload_limits") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setUploadLimits(limits: UploadLimits) : Promise<Result<UploadLimits, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_upload_limits", { limits }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getImageSanitizeOptions() : Promise<Result<SanitizeOptions, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_image_sanitize_options") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setImageSanitizeOptions(options: SanitizeOptions) : Promise<Result<SanitizeOptions, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_image_sanitize_options", { options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async retainImage(id: string, owner: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("retain_image", { id, owner }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Returns whether the image was deleted because nothing uses it any more
+ */
+async releaseImage(id: string, owner: string) : Promise<Result<boolean, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("release_image", { id, owner }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listImages() : Promise<Result<ImageInfo[], AppError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("list_images") };
 } catch (e) {
@@ -72,7 +353,64 @@ async listImages() : Promise<Result<ImageInfo[], string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async removeBackground(imageData: number[]) : Promise<Result<number[], string>> {
+/**
+ * Delete images no record has claimed within the grace period. Returns the
+ * number of images and stray files removed.
+ */
+async collectImageGarbage() : Promise<Result<number, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("collect_image_garbage") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Store an image sent as a JSON array of bytes
+ * 
+ * @deprecated POST the bytes to `image://localhost/` instead
+ */
+async saveImage(data: number[], owner: string | null) : Promise<Result<string, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("save_image", { data, owner }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Read an image as a JSON array of bytes
+ * 
+ * @deprecated GET `image://localhost/<id>` instead
+ */
+async getImage(id: string) : Promise<Result<number[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_image", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Images are deleted once their last owner releases them, and unclaimed
+ * ones after the grace period, so this only checks that the image exists
+ * 
+ * @deprecated Use `release_image` with the owner that claimed the image
+ */
+async removeImage(name: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_image", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Cut out an image sent as a JSON array of bytes, answering with a PNG
+ * 
+ * @deprecated POST the bytes to `image://localhost/background-removal` instead
+ */
+async removeBackground(imageData: number[]) : Promise<Result<number[], AppError>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("remove_background", { imageData }) };
 } catch (e) {
@@ -85,6 +423,15 @@ async removeBackground(imageData: number[]) : Promise<Result<number[], string>>
 /** user-defined events **/
 
 
+export const events = __makeEvents__<{
+activeLocationChanged: ActiveLocationChanged,
+connectivityChanged: ConnectivityChanged,
+weatherAlert: WeatherAlert
+}>({
+activeLocationChanged: "active-location-changed",
+connectivityChanged: "connectivity-changed",
+weatherAlert: "weather-alert"
+})
 
 /** user-defined constants **/
 
@@ -92,10 +439,141 @@ async removeBackground(imageData: number[]) : Promise<Result<number[], string>>
 
 /** user-defined types **/
 
+/**
+ * Emitted when the active location is switched, edited or removed
+ */
+export type ActiveLocationChanged = { location: SavedLocation | null }
+/**
+ * Background polling settings, saved in the app data dir
+ */
+export type AlertConfig = { enabled: boolean; poll_interval_secs: number; desktop_notifications: boolean; rules: AlertRules; location_ids: string[] | null }
+/**
+ * Conditions that raise an alert when forecast within the lookahead window
+ */
+export type AlertRules = { heavy_rain_mm_per_hour: number; freezing_temperature: number; high_wind_speed: number; lookahead_hours: number; provider_alerts: boolean }
+/**
+ * Error returned by every command, tagged by `kind` so the frontend can
+ * branch on it and show `message` to the user
+ */
+export type AppError = { kind: "NotFound"; message: string } | { kind: "InvalidInput"; message: string } | { kind: "InvalidPath"; message: string } | { kind: "TooLarge"; message: string } | { kind: "Network"; message: string } | { kind: "Upstream"; status: number; message: string } | { kind: "ModelUnavailable"; message: string } | { kind: "Io"; message: string } | { kind: "RateLimited"; retry_after_secs: number | null; message: string } | { kind: "Internal"; message: string }
+/**
+ * Where one temperature band ends and the next begins, by feels-like
+ * temperature, and how much precipitation makes a day wet
+ */
+export type ClassifierThresholds = { hot_feels_like: number; warm_feels_like: number; cool_feels_like: number; rainy_mm: number; snowy_mm: number }
+/**
+ * Whether external services could be reached on the last attempt
+ */
+export type Connectivity = { online: boolean; checked_at: string | null; last_online_at: string | null }
+/**
+ * Emitted when requests start failing to reach their service, or recover
+ */
+export type ConnectivityChanged = { online: boolean; changed_at: string }
 export type Coordinates = { latitude: number; longitude: number }
+/**
+ * Forecast summary for one local calendar day
+ */
+export type DailyForecast = { date: string; temp_min: number; temp_max: number; humidity: number; precipitation: number; pop: number; description: string; icon: string; location: string; units: UnitSystem }
+/**
+ * One step of a multi-day forecast
+ */
+export type ForecastPoint = { date: string; temperature: number; feels_like: number; humidity: number; wind_speed: number; wind_deg: number; wind_gust: number | null; pressure: number; clouds: number; visibility: number | null; pop: number; rain: number; snow: number; description: string; icon: string; units: UnitSystem }
+/**
+ * Current location of the device
+ */
+export type Geolocation = { lat: number; lon: number; name: string | null; source: LocationSource; accuracy_km: number; located_at: string }
+/**
+ * Where the current location comes from, tried in field order
+ */
+export type GeolocationConfig = { manual_override: Coordinates | null; use_saved_default: boolean; cache_ttl_secs: number; network_lookup: boolean; ip_providers: IpProviderKind[] }
 export type GreetResponse = { message: string }
-export type ImageInfo = { name: string; path: string }
-export type WeatherData = { temperature: number; feels_like: number; humidity: number; description: string; icon: string; location: string; date: string }
+export type ImageInfo = { id: string; path: string; size: number; owners: string[]; created_at: string; metadata: ImageMetadata | null }
+export type ImageMetadata = { width: number; height: number; format: string; orientation: number; captured_at: string | null; location: Coordinates | null }
+export type IpProviderKind = "ip_who_is" | "ipapi_co" | "ip_api"
+/**
+ * Fields of a location to create or replace
+ */
+export type LocationInput = { name: string; lat: number; lon: number }
+/**
+ * Where to fetch weather for: explicit coordinates, a saved location, or
+ * the active one
+ */
+export type LocationQuery = { type: "coordinates"; lat: number; lon: number } | { type: "saved"; id: string } | { type: "active" }
+/**
+ * Which source answered a location request
+ */
+export type LocationSource = { type: "manual" } | { type: "saved_default"; location_id: string } | { type: "cached"; provider: IpProviderKind; age_secs: number } | { type: "ip_lookup"; provider: IpProviderKind }
+/**
+ * A named place found by a geocoding lookup
+ */
+export type Place = { name: string; country: string; country_code: string | null; admin_area: string | null; timezone: string; lat: number; lon: number }
+/**
+ * What happens to an image's embedded metadata before it is stored, saved
+ * with the image store
+ */
+export type SanitizeOptions = { auto_rotate: boolean; strip_exif: boolean }
+/**
+ * Named place the user has saved
+ */
+export type SavedLocation = { id: string; name: string; lat: number; lon: number }
+/**
+ * Units weather values are reported in, named after OpenWeatherMap's
+ * `units` parameter. Only temperature and wind speed differ between them;
+ * pressure stays in hPa, visibility in metres and precipitation in mm.
+ */
+export type UnitSystem = "metric" | "imperial" | "standard"
+/**
+ * Format uploads can be re-encoded to
+ */
+export type UploadFormat = "png" | "jpeg" | "webp"
+/**
+ * What an upload may be before it is decoded. The pixel count guards
+ * against small files that decode to huge images.
+ */
+export type UploadLimits = { max_bytes: number; max_pixels: number; normalize_to: UploadFormat | null }
+/**
+ * Sizes downscaled copies of each image are made at, saved with the image
+ * store
+ */
+export type VariantConfig = { sizes: number[]; format: VariantFormat }
+/**
+ * Encoding of generated variants. WebP is written losslessly, so both keep
+ * the transparency of cut-out clothing.
+ */
+export type VariantFormat = "webp" | "png"
+/**
+ * Emitted when a severe condition is first detected for a watched location
+ */
+export type WeatherAlert = { id: string; kind: WeatherAlertKind; location_id: string; location: string; lat: number; lon: number; title: string; description: string; starts_at: string; ends_at: string | null; notify: boolean }
+export type WeatherAlertKind = "provider" | "heavy_rain" | "freezing" | "high_wind"
+/**
+ * Cache settings, adjustable while the app runs and saved in the app data dir
+ */
+export type WeatherCacheConfig = { current_ttl_secs: number; forecast_ttl_secs: number; current_capacity: number; forecast_capacity: number; series_capacity: number; stale_while_revalidate: boolean; grid_precision: number }
+/**
+ * A cached response, as listed for inspection
+ */
+export type WeatherCacheEntry = { kind: WeatherCacheKind; cell: string; hour: string | null; location: string; fetched_at: string; age_secs: number; stale: boolean }
+export type WeatherCacheKind = "current" | "forecast" | "series"
+/**
+ * Lookup counters since startup, and current entry counts
+ */
+export type WeatherCacheStats = { hits: number; stale_hits: number; misses: number; evictions: number; current_entries: number; forecast_entries: number; series_entries: number }
+export type WeatherData = { temperature: number; feels_like: number; humidity: number; description: string; icon: string; location: string; date: string; wind_speed: number; wind_deg: number; wind_gust: number | null; pressure: number; visibility: number | null; clouds: number; rain: number; snow: number; sunrise: string | null; sunset: string | null; timezone_offset: number; timezone: string | null; uv_index: number | null; units: UnitSystem }
+/**
+ * How weather is presented to the user
+ */
+export type WeatherPreferences = { units: UnitSystem; language: string }
+export type WeatherProviderKind = "open_weather_map" | "open_meteo" | "mock"
+/**
+ * Weather payload with the time it was fetched from the provider.
+ * `stale` is set once the data has outlived its cache TTL, e.g. while offline.
+ */
+export type WeatherResponse<T> = { data: T; stale: boolean; fetched_at: string }
+/**
+ * Weather a piece of clothing is suited for, as tagged in the wardrobe
+ */
+export type WeatherType = "hot" | "warm" | "cool" | "cold" | "rainy" | "snowy"
 
 /** tauri-specta globals **/
 
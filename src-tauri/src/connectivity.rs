@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::{Mutex, OnceLock};
+use tauri::AppHandle;
+use tauri_specta::Event;
+
+static CONNECTIVITY: OnceLock<Mutex<Connectivity>> = OnceLock::new();
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// Whether external services could be reached on the last attempt
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct Connectivity {
+    pub online: bool,
+    pub checked_at: Option<String>, // RFC 3339, unset until the first request
+    pub last_online_at: Option<String>, // RFC 3339
+}
+
+/// Emitted when requests start failing to reach their service, or recover
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct ConnectivityChanged {
+    pub online: bool,
+    pub changed_at: String, // RFC 3339
+}
+
+fn state() -> &'static Mutex<Connectivity> {
+    CONNECTIVITY.get_or_init(|| {
+        // Assume online until a request says otherwise
+        Mutex::new(Connectivity {
+            online: true,
+            checked_at: None,
+            last_online_at: None,
+        })
+    })
+}
+
+/// Route connectivity change events to the running app
+pub fn attach(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+pub fn current() -> Connectivity {
+    state().lock().expect("Connectivity lock poisoned").clone()
+}
+
+/// Record the outcome of a request. Any HTTP response counts as online;
+/// only failures to connect or to receive a response count as offline.
+pub fn report(online: bool) {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let changed = {
+        let mut connectivity = state().lock().expect("Connectivity lock poisoned");
+        let changed = connectivity.online != online;
+
+        connectivity.online = online;
+        connectivity.checked_at = Some(now.clone());
+        if online {
+            connectivity.last_online_at = Some(now.clone());
+        }
+
+        changed
+    };
+
+    if changed {
+        if let Some(app) = APP_HANDLE.get() {
+            let event = ConnectivityChanged {
+                online,
+                changed_at: now,
+            };
+            if let Err(e) = event.emit(app) {
+                eprintln!("Warning: Failed to emit connectivity change: {}", e);
+            }
+        }
+    }
+}
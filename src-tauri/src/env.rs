@@ -1,10 +1,32 @@
+use crate::weather_provider::WeatherProviderKind;
 use std::sync::OnceLock;
 
 static ENV_CONFIG: OnceLock<EnvConfig> = OnceLock::new();
 
+const DEFAULT_OPEN_WEATHER_BASE_URL: &str = "https://api.openweathermap.org/data/2.5";
+const DEFAULT_OPEN_WEATHER_ONE_CALL_BASE_URL: &str = "https://api.openweathermap.org/data/3.0";
+const DEFAULT_OPEN_METEO_BASE_URL: &str = "https://api.open-meteo.com/v1";
+const DEFAULT_OPEN_METEO_ARCHIVE_BASE_URL: &str = "https://archive-api.open-meteo.com/v1";
+const DEFAULT_OPEN_METEO_GEOCODING_BASE_URL: &str = "https://geocoding-api.open-meteo.com/v1";
+const DEFAULT_IP_API_BASE_URL: &str = "http://ip-api.com";
+const DEFAULT_IPWHO_IS_BASE_URL: &str = "https://ipwho.is";
+const DEFAULT_IPAPI_CO_BASE_URL: &str = "https://ipapi.co";
+
 #[derive(Debug, Clone)]
 pub struct EnvConfig {
-    pub open_weather_api_key: String,
+    pub open_weather_api_key: Option<String>,
+    pub weather_provider: Option<WeatherProviderKind>,
+    pub weather_mock_file: Option<String>,
+    pub weather_stale_while_revalidate: bool,
+    pub weather_cache_grid_precision: Option<u32>,
+    pub open_weather_base_url: String,
+    pub open_weather_one_call_base_url: String, // Serves weather alerts
+    pub open_meteo_base_url: String,
+    pub open_meteo_archive_base_url: String,
+    pub open_meteo_geocoding_base_url: String,
+    pub ip_api_base_url: String,
+    pub ipwho_is_base_url: String,
+    pub ipapi_co_base_url: String,
 }
 
 impl EnvConfig {
@@ -12,11 +34,58 @@ impl EnvConfig {
         // Load .env file if it exists (for development)
         let _ = dotenvy::dotenv();
 
-        let open_weather_api_key = std::env::var("OPEN_WEATHER_API_KEY")
-            .map_err(|_| "OPEN_WEATHER_API_KEY environment variable not set".to_string())?;
+        let open_weather_api_key = std::env::var("OPEN_WEATHER_API_KEY").ok();
+
+        let weather_provider = std::env::var("WEATHER_PROVIDER")
+            .ok()
+            .map(|value| value.parse())
+            .transpose()?;
+
+        let weather_mock_file = std::env::var("WEATHER_MOCK_FILE").ok();
+
+        // Serve expired cache entries while refreshing, unless switched off
+        let weather_stale_while_revalidate = std::env::var("WEATHER_STALE_WHILE_REVALIDATE")
+            .map(|value| {
+                !matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "0" | "false" | "off"
+                )
+            })
+            .unwrap_or(true);
+
+        let weather_cache_grid_precision = std::env::var("WEATHER_CACHE_GRID_PRECISION")
+            .ok()
+            .map(|value| {
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("Invalid WEATHER_CACHE_GRID_PRECISION: {}", e))
+            })
+            .transpose()?;
 
         Ok(Self {
             open_weather_api_key,
+            weather_provider,
+            weather_mock_file,
+            weather_stale_while_revalidate,
+            weather_cache_grid_precision,
+            open_weather_base_url: base_url("OPEN_WEATHER_BASE_URL", DEFAULT_OPEN_WEATHER_BASE_URL),
+            open_weather_one_call_base_url: base_url(
+                "OPEN_WEATHER_ONE_CALL_BASE_URL",
+                DEFAULT_OPEN_WEATHER_ONE_CALL_BASE_URL,
+            ),
+            open_meteo_base_url: base_url("OPEN_METEO_BASE_URL", DEFAULT_OPEN_METEO_BASE_URL),
+            open_meteo_archive_base_url: base_url(
+                "OPEN_METEO_ARCHIVE_BASE_URL",
+                DEFAULT_OPEN_METEO_ARCHIVE_BASE_URL,
+            ),
+            open_meteo_geocoding_base_url: base_url(
+                "OPEN_METEO_GEOCODING_BASE_URL",
+                DEFAULT_OPEN_METEO_GEOCODING_BASE_URL,
+            ),
+            ip_api_base_url: base_url("IP_API_BASE_URL", DEFAULT_IP_API_BASE_URL),
+            ipwho_is_base_url: base_url("IPWHO_IS_BASE_URL", DEFAULT_IPWHO_IS_BASE_URL),
+            ipapi_co_base_url: base_url("IPAPI_CO_BASE_URL", DEFAULT_IPAPI_CO_BASE_URL),
         })
     }
 
@@ -31,3 +100,11 @@ impl EnvConfig {
             .map_err(|_| "EnvConfig already initialized".to_string())
     }
 }
+
+/// Read a service base URL override, without a trailing slash
+fn base_url(var: &str, default: &str) -> String {
+    std::env::var(var)
+        .unwrap_or_else(|_| default.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use thiserror::Error;
+
+/// Error returned by every command, tagged by `kind` so the frontend can
+/// branch on it and show `message` to the user
+#[derive(Debug, Clone, PartialEq, Error, Serialize, Deserialize, Type)]
+#[serde(tag = "kind")]
+pub enum AppError {
+    #[error("{message}")]
+    NotFound { message: String },
+    #[error("{message}")]
+    InvalidInput { message: String },
+    #[error("{message}")]
+    InvalidPath { message: String }, // A name that could reach outside its directory
+    #[error("{message}")]
+    TooLarge { message: String }, // An upload over the configured size limits
+    #[error("{message}")]
+    Network { message: String }, // The service could not be reached
+    #[error("{message}")]
+    Upstream { status: u16, message: String }, // The service answered with an error
+    #[error("{message}")]
+    ModelUnavailable { message: String },
+    #[error("{message}")]
+    Io { message: String },
+    #[error("{message}")]
+    RateLimited {
+        retry_after_secs: Option<u32>,
+        message: String,
+    },
+    #[error("{message}")]
+    Internal { message: String },
+}
+
+impl AppError {
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound {
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::InvalidInput {
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_path(message: impl Into<String>) -> Self {
+        Self::InvalidPath {
+            message: message.into(),
+        }
+    }
+
+    pub fn too_large(message: impl Into<String>) -> Self {
+        Self::TooLarge {
+            message: message.into(),
+        }
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::Network {
+            message: message.into(),
+        }
+    }
+
+    pub fn upstream(status: u16, message: impl Into<String>) -> Self {
+        Self::Upstream {
+            status,
+            message: message.into(),
+        }
+    }
+
+    pub fn model_unavailable(message: impl Into<String>) -> Self {
+        Self::ModelUnavailable {
+            message: message.into(),
+        }
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::Io {
+            message: message.into(),
+        }
+    }
+
+    pub fn rate_limited(retry_after_secs: Option<u32>, message: impl Into<String>) -> Self {
+        Self::RateLimited {
+            retry_after_secs,
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::Internal {
+            message: message.into(),
+        }
+    }
+}
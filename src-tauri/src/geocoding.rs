@@ -0,0 +1,134 @@
+mod offline;
+mod open_meteo;
+
+use crate::env::EnvConfig;
+use crate::error::AppError;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+pub use offline::{City, OfflineGeocoder};
+pub use open_meteo::OpenMeteoGeocoder;
+
+static GEOCODER: OnceLock<Geocoder> = OnceLock::new();
+
+/// Most results a forward lookup returns
+pub const MAX_RESULTS: usize = 10;
+
+/// A named place found by a geocoding lookup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct Place {
+    pub name: String,
+    pub country: String,
+    pub country_code: Option<String>, // ISO 3166-1 alpha-2
+    pub admin_area: Option<String>,   // State, region or province
+    pub timezone: String,             // IANA name, e.g. "Europe/Warsaw"
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Source of place names and coordinates
+#[async_trait]
+pub trait GeocodingProvider: Send + Sync {
+    /// Places matching a free-text query, best match first
+    async fn geocode(&self, query: &str) -> Result<Vec<Place>, AppError>;
+
+    /// Place at or closest to the coordinates
+    async fn reverse(&self, _lat: f64, _lon: f64) -> Result<Place, AppError> {
+        Err(AppError::not_found(
+            "Reverse geocoding is not available from this provider",
+        ))
+    }
+}
+
+/// Online provider backed by the bundled city list when it cannot answer
+pub struct Geocoder {
+    online: Option<Arc<dyn GeocodingProvider>>,
+    offline: OfflineGeocoder,
+}
+
+impl Geocoder {
+    pub fn new(online: Option<Arc<dyn GeocodingProvider>>, offline: OfflineGeocoder) -> Self {
+        Self { online, offline }
+    }
+
+    /// Use the city list bundled at `cities_path` as the offline fallback
+    pub fn init(cities_path: &Path) -> Result<(), AppError> {
+        let offline = OfflineGeocoder::load(cities_path)?;
+        GEOCODER
+            .set(Self::new(Some(open_meteo(EnvConfig::get())), offline))
+            .map_err(|_| AppError::internal("Geocoder already initialized"))
+    }
+
+    pub fn global() -> &'static Geocoder {
+        GEOCODER.get_or_init(|| {
+            Self::new(
+                Some(open_meteo(EnvConfig::get())),
+                OfflineGeocoder::default(),
+            )
+        })
+    }
+
+    /// Search the online provider, falling back to the city list when it is
+    /// unreachable or failing
+    pub async fn geocode(&self, query: &str) -> Result<Vec<Place>, AppError> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(AppError::invalid_input("Search text must not be empty"));
+        }
+
+        if let Some(online) = &self.online {
+            match online.geocode(query).await {
+                Err(e) if is_unavailable(&e) => {
+                    eprintln!("Warning: {}, searching offline places", e);
+                }
+                result => return result,
+            }
+        }
+
+        self.offline.geocode(query).await
+    }
+
+    /// Ask the online provider first, then take the nearest bundled city
+    pub async fn reverse(&self, lat: f64, lon: f64) -> Result<Place, AppError> {
+        crate::locations::check_coordinates(lat, lon)?;
+
+        if let Some(online) = &self.online {
+            match online.reverse(lat, lon).await {
+                Err(AppError::NotFound { .. }) => {}
+                Err(e) if is_unavailable(&e) => {
+                    eprintln!("Warning: {}, searching offline places", e);
+                }
+                result => return result,
+            }
+        }
+
+        self.offline.reverse(lat, lon).await
+    }
+}
+
+/// IANA timezone of the nearest bundled city, without any network lookup.
+/// `None` before the city list is loaded or away from every known city.
+pub fn timezone_near(lat: f64, lon: f64) -> Option<String> {
+    let geocoder = GEOCODER.get()?;
+    geocoder
+        .offline
+        .nearest(lat, lon)
+        .map(|city| city.timezone.clone())
+}
+
+fn open_meteo(config: &EnvConfig) -> Arc<dyn GeocodingProvider> {
+    Arc::new(OpenMeteoGeocoder::new(
+        config.open_meteo_geocoding_base_url.clone(),
+    ))
+}
+
+/// Failures the offline list can stand in for, as opposed to a bad query
+fn is_unavailable(error: &AppError) -> bool {
+    matches!(
+        error,
+        AppError::Network { .. } | AppError::Upstream { .. } | AppError::RateLimited { .. }
+    )
+}
@@ -0,0 +1,242 @@
+use super::{GeocodingProvider, Place, MAX_RESULTS};
+use crate::error::AppError;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Reverse lookups further than this from every known city find nothing
+const MAX_REVERSE_DISTANCE_KM: f64 = 150.0;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Entry of the bundled city list
+#[derive(Debug, Clone, Deserialize)]
+pub struct City {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>, // Exonyms and spellings without diacritics
+    pub country: String,
+    pub country_code: String,
+    pub admin_area: Option<String>,
+    pub timezone: String,
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(default)]
+    pub population: u64, // Ranks otherwise equal matches
+}
+
+impl City {
+    fn place(&self) -> Place {
+        Place {
+            name: self.name.clone(),
+            country: self.country.clone(),
+            country_code: Some(self.country_code.clone()),
+            admin_area: self.admin_area.clone(),
+            timezone: self.timezone.clone(),
+            lat: self.lat,
+            lon: self.lon,
+        }
+    }
+}
+
+/// City with its names folded for matching
+#[derive(Debug)]
+struct Entry {
+    city: City,
+    names: Vec<String>,
+    regions: Vec<String>, // Country, country code and admin area
+}
+
+/// How closely a query matched a city name, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Match {
+    Exact,
+    Prefix,
+    Word,
+}
+
+/// Search over the major cities bundled with the app, used when the online
+/// provider cannot be reached
+#[derive(Debug, Default)]
+pub struct OfflineGeocoder {
+    entries: Vec<Entry>,
+}
+
+impl OfflineGeocoder {
+    pub fn new(cities: Vec<City>) -> Self {
+        let entries = cities
+            .into_iter()
+            .map(|city| {
+                let names = std::iter::once(&city.name)
+                    .chain(&city.aliases)
+                    .map(|name| fold(name))
+                    .collect();
+                let regions = [Some(&city.country), Some(&city.country_code)]
+                    .into_iter()
+                    .chain([city.admin_area.as_ref()])
+                    .flatten()
+                    .map(|region| fold(region))
+                    .collect();
+                Entry {
+                    city,
+                    names,
+                    regions,
+                }
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    /// Read the city list from a JSON file
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let body = fs::read_to_string(path)
+            .map_err(|e| AppError::io(format!("Failed to read city list: {}", e)))?;
+        let cities = serde_json::from_str(&body)
+            .map_err(|e| AppError::io(format!("Failed to parse city list: {}", e)))?;
+
+        Ok(Self::new(cities))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Closest city within 150 km
+    pub fn nearest(&self, lat: f64, lon: f64) -> Option<&City> {
+        self.entries
+            .iter()
+            .map(|entry| (distance_km(lat, lon, entry.city.lat, entry.city.lon), entry))
+            .filter(|(distance, _)| *distance <= MAX_REVERSE_DISTANCE_KM)
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, entry)| &entry.city)
+    }
+}
+
+#[async_trait]
+impl GeocodingProvider for OfflineGeocoder {
+    /// Matches names and aliases ignoring case and diacritics, so "krakow"
+    /// finds Kraków. Text after a comma narrows the search by country or
+    /// region, as in "Portland, Oregon".
+    async fn geocode(&self, query: &str) -> Result<Vec<Place>, AppError> {
+        let (name, region) = match query.split_once(',') {
+            Some((name, region)) => (fold(name), Some(fold(region))),
+            None => (fold(query), None),
+        };
+        if name.is_empty() {
+            return Err(AppError::invalid_input("Search text must not be empty"));
+        }
+
+        let mut matches: Vec<(Match, &Entry)> = self
+            .entries
+            .iter()
+            .filter(|entry| match &region {
+                Some(region) if !region.is_empty() => entry
+                    .regions
+                    .iter()
+                    .any(|candidate| candidate.starts_with(region.as_str())),
+                _ => true,
+            })
+            .filter_map(|entry| {
+                let best = entry
+                    .names
+                    .iter()
+                    .filter_map(|candidate| match_name(candidate, &name))
+                    .min()?;
+                Some((best, entry))
+            })
+            .collect();
+
+        matches.sort_by(|(a, a_entry), (b, b_entry)| {
+            a.cmp(b)
+                .then(b_entry.city.population.cmp(&a_entry.city.population))
+        });
+
+        Ok(matches
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, entry)| entry.city.place())
+            .collect())
+    }
+
+    /// Nearest city within 150 km
+    async fn reverse(&self, lat: f64, lon: f64) -> Result<Place, AppError> {
+        self.nearest(lat, lon)
+            .map(City::place)
+            .ok_or_else(|| AppError::not_found(format!("No known place near {}, {}", lat, lon)))
+    }
+}
+
+fn match_name(candidate: &str, query: &str) -> Option<Match> {
+    if candidate == query {
+        Some(Match::Exact)
+    } else if candidate.starts_with(query) {
+        Some(Match::Prefix)
+    } else if candidate
+        .match_indices(query)
+        .any(|(at, _)| candidate[..at].ends_with(' '))
+    {
+        Some(Match::Word)
+    } else {
+        None
+    }
+}
+
+/// Great-circle distance by the haversine formula
+fn distance_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// Lowercase, strip Latin diacritics and collapse punctuation into single
+/// spaces, so "Cluj-Napoca" and "cluj napoca" compare equal
+fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => folded.push('a'),
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => folded.push('c'),
+            'ď' | 'đ' | 'ð' => folded.push('d'),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => folded.push('e'),
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => folded.push('g'),
+            'ĥ' | 'ħ' => folded.push('h'),
+            'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => folded.push('i'),
+            'ĵ' => folded.push('j'),
+            'ķ' => folded.push('k'),
+            'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => folded.push('l'),
+            'ñ' | 'ń' | 'ņ' | 'ň' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => folded.push('o'),
+            'ŕ' | 'ŗ' | 'ř' => folded.push('r'),
+            'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => folded.push('s'),
+            'ţ' | 'ť' | 'ŧ' | 'ț' => folded.push('t'),
+            'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => folded.push('u'),
+            'ŵ' => folded.push('w'),
+            'ý' | 'ÿ' | 'ŷ' => folded.push('y'),
+            'ź' | 'ż' | 'ž' => folded.push('z'),
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'œ' => folded.push_str("oe"),
+            'þ' => folded.push_str("th"),
+            // Apostrophes join, as in "Xi'an"
+            '\'' | '’' => {}
+            c if c.is_alphanumeric() => folded.push(c),
+            _ => {
+                if !folded.is_empty() && !folded.ends_with(' ') {
+                    folded.push(' ');
+                }
+            }
+        }
+    }
+
+    folded.truncate(folded.trim_end().len());
+    folded
+}
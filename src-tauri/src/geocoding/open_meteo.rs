@@ -0,0 +1,73 @@
+use super::{GeocodingProvider, Place, MAX_RESULTS};
+use crate::error::AppError;
+use crate::http_client::HttpClient;
+use async_trait::async_trait;
+use serde::Deserialize;
+use tauri_plugin_http::reqwest::Url;
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    results: Vec<SearchResult>, // Left out when nothing matches
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    country: Option<String>,
+    country_code: Option<String>,
+    admin1: Option<String>,
+    timezone: Option<String>,
+}
+
+/// Open-Meteo place search, which needs no API key. It has no reverse
+/// lookup, so those go to the offline list.
+pub struct OpenMeteoGeocoder {
+    base_url: String,
+}
+
+impl OpenMeteoGeocoder {
+    pub fn new(base_url: String) -> Self {
+        Self { base_url }
+    }
+}
+
+#[async_trait]
+impl GeocodingProvider for OpenMeteoGeocoder {
+    async fn geocode(&self, query: &str) -> Result<Vec<Place>, AppError> {
+        let url = Url::parse_with_params(
+            &format!("{}/search", self.base_url),
+            &[
+                ("name", query),
+                ("count", &MAX_RESULTS.to_string()),
+                ("language", "en"),
+                ("format", "json"),
+            ],
+        )
+        .map_err(|e| AppError::internal(format!("Invalid geocoding URL: {}", e)))?;
+
+        let data: SearchResponse = HttpClient::global()
+            .get_json(url.as_str(), "geocoding")
+            .await?;
+
+        // Places without a country or timezone are features like seas and
+        // cannot be shown or used for weather
+        Ok(data
+            .results
+            .into_iter()
+            .filter_map(|result| {
+                Some(Place {
+                    name: result.name,
+                    country: result.country?,
+                    country_code: result.country_code,
+                    admin_area: result.admin1,
+                    timezone: result.timezone?,
+                    lat: result.latitude,
+                    lon: result.longitude,
+                })
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,39 @@
+/// Geohash alphabet, skipping a, i, l and o
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Longest supported geohash, finer than a metre
+pub const MAX_PRECISION: usize = 12;
+
+/// Geohash of the cell containing a point. Each extra character narrows the
+/// cell; 5 characters is about 5 km across and 6 about 1 km.
+pub fn encode(lat: f64, lon: f64, precision: usize) -> String {
+    let mut lat_range = (-90.0, 90.0);
+    let mut lon_range = (-180.0, 180.0);
+    let mut hash = String::with_capacity(precision);
+    let mut even_bit = true;
+
+    for _ in 0..precision.clamp(1, MAX_PRECISION) {
+        let mut index = 0;
+        for _ in 0..5 {
+            // Bits alternate between longitude and latitude, starting with longitude
+            let (range, value) = if even_bit {
+                (&mut lon_range, lon)
+            } else {
+                (&mut lat_range, lat)
+            };
+            let mid = (range.0 + range.1) / 2.0;
+
+            index <<= 1;
+            if value >= mid {
+                index |= 1;
+                range.0 = mid;
+            } else {
+                range.1 = mid;
+            }
+            even_bit = !even_bit;
+        }
+        hash.push(BASE32[index] as char);
+    }
+
+    hash
+}
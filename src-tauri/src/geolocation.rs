@@ -1,5 +1,25 @@
+mod ip_api;
+mod ipapi_co;
+mod ipwho_is;
+
+use crate::env::EnvConfig;
+use crate::error::AppError;
+use crate::locations::{check_coordinates, Locations};
+use crate::persist;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub use ip_api::IpApiProvider;
+pub use ipapi_co::IpapiCoProvider;
+pub use ipwho_is::IpWhoIsProvider;
+
+static GEOLOCATOR: OnceLock<Geolocator> = OnceLock::new();
+
+/// How far off an IP lookup may be; they resolve to about city level
+pub const IP_ACCURACY_KM: f64 = 25.0;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct Coordinates {
@@ -7,38 +27,299 @@ pub struct Coordinates {
     pub longitude: f64,
 }
 
-#[derive(Debug, Deserialize)]
-struct IpApiResponse {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum IpProviderKind {
+    IpWhoIs,
+    IpapiCo,
+    IpApi,
+}
+
+/// Where the current location comes from, tried in field order
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GeolocationConfig {
+    pub manual_override: Option<Coordinates>, // Always used when set
+    pub use_saved_default: bool,
+    pub cache_ttl_secs: u32,               // How long an IP lookup is reused
+    pub network_lookup: bool,              // Off keeps lookups on this device
+    pub ip_providers: Vec<IpProviderKind>, // Tried in order until one answers
+}
+
+impl Default for GeolocationConfig {
+    fn default() -> Self {
+        Self {
+            manual_override: None,
+            use_saved_default: true,
+            cache_ttl_secs: 6 * 60 * 60,
+            network_lookup: true,
+            // HTTPS providers first
+            ip_providers: vec![
+                IpProviderKind::IpWhoIs,
+                IpProviderKind::IpapiCo,
+                IpProviderKind::IpApi,
+            ],
+        }
+    }
+}
+
+impl GeolocationConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if let Some(coordinates) = &self.manual_override {
+            check_coordinates(coordinates.latitude, coordinates.longitude)?;
+        }
+        Ok(())
+    }
+}
+
+/// Which source answered a location request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LocationSource {
+    Manual,
+    SavedDefault {
+        location_id: String,
+    },
+    Cached {
+        provider: IpProviderKind,
+        age_secs: u32,
+    },
+    IpLookup {
+        provider: IpProviderKind,
+    },
+}
+
+/// Current location of the device
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct Geolocation {
+    pub lat: f64,
+    pub lon: f64,
+    pub name: Option<String>, // City or saved location name, when known
+    pub source: LocationSource,
+    pub accuracy_km: f64,   // 0 for a point the user picked
+    pub located_at: String, // RFC 3339
+}
+
+/// Position reported by an IP lookup service
+#[derive(Debug, Clone)]
+pub struct IpLocation {
+    pub lat: f64,
+    pub lon: f64,
+    pub city: Option<String>,
+}
+
+/// Service that places the device by its public IP address
+#[async_trait]
+pub trait IpLocationProvider: Send + Sync {
+    fn kind(&self) -> IpProviderKind;
+
+    async fn locate(&self) -> Result<IpLocation, AppError>;
+}
+
+/// Last successful IP lookup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LastKnown {
     lat: f64,
     lon: f64,
+    city: Option<String>,
+    provider: IpProviderKind,
+    located_at: i64,
+}
+
+impl LastKnown {
+    fn cached(&self, now: i64) -> Geolocation {
+        Geolocation {
+            lat: self.lat,
+            lon: self.lon,
+            name: self.city.clone(),
+            source: LocationSource::Cached {
+                provider: self.provider,
+                age_secs: (now - self.located_at).clamp(0, u32::MAX as i64) as u32,
+            },
+            accuracy_km: IP_ACCURACY_KM,
+            located_at: to_rfc3339(self.located_at),
+        }
+    }
 }
 
-/// Get approximate location based on IP address
-/// Uses ip-api.com which doesn't require an API key
-pub async fn get_location_from_ip() -> Result<Coordinates, String> {
-    let url = "http://ip-api.com/json/?fields=lat,lon";
+/// On-disk layout of the settings file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Stored {
+    config: GeolocationConfig,
+    last_known: Option<LastKnown>,
+}
+
+/// Resolves the device location through the configured chain of sources,
+/// persisting its settings and last IP lookup in the app data dir
+pub struct Geolocator {
+    path: Option<PathBuf>,
+    providers: Vec<Arc<dyn IpLocationProvider>>,
+    state: Mutex<Stored>,
+}
+
+impl Geolocator {
+    /// Load the settings file at `path`, starting from defaults if it does
+    /// not exist yet. Without a path nothing is persisted.
+    pub fn open(
+        path: Option<PathBuf>,
+        providers: Vec<Arc<dyn IpLocationProvider>>,
+    ) -> Result<Self, AppError> {
+        let stored = match &path {
+            Some(path) => persist::load_json::<Stored>(path, "geolocation")?,
+            None => None,
+        }
+        .unwrap_or_default();
+
+        Ok(Self {
+            path,
+            providers,
+            state: Mutex::new(stored),
+        })
+    }
+
+    pub fn init(path: PathBuf) -> Result<(), AppError> {
+        let geolocator = Self::open(Some(path), providers(EnvConfig::get()))?;
+        GEOLOCATOR
+            .set(geolocator)
+            .map_err(|_| AppError::internal("Geolocator already initialized"))
+    }
+
+    pub fn global() -> &'static Geolocator {
+        GEOLOCATOR.get_or_init(|| {
+            Self::open(None, providers(EnvConfig::get()))
+                .expect("In-memory geolocator cannot fail to open")
+        })
+    }
+
+    pub fn config(&self) -> GeolocationConfig {
+        self.lock().config.clone()
+    }
+
+    pub fn set_config(&self, config: GeolocationConfig) -> Result<(), AppError> {
+        config.validate()?;
+        let mut state = self.lock();
+        state.config = config;
+        self.save(&state)
+    }
 
-    let client = tauri_plugin_http::reqwest::Client::new();
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch location: {}", e))?;
+    /// Try the manual override, the saved default location, a fresh cached
+    /// lookup and then each IP provider in turn. When network lookups are
+    /// off or all fail, an expired cached lookup is still better than none.
+    pub async fn locate(&self) -> Result<Geolocation, AppError> {
+        let (config, last_known) = {
+            let state = self.lock();
+            (state.config.clone(), state.last_known.clone())
+        };
+        let now = chrono::Utc::now().timestamp();
 
-    if !response.status().is_success() {
-        return Err(format!("Location API error: {}", response.status()));
+        if let Some(coordinates) = &config.manual_override {
+            return Ok(Geolocation {
+                lat: coordinates.latitude,
+                lon: coordinates.longitude,
+                name: None,
+                source: LocationSource::Manual,
+                accuracy_km: 0.0,
+                located_at: to_rfc3339(now),
+            });
+        }
+
+        if config.use_saved_default {
+            if let Some(location) = Locations::global().default_location() {
+                return Ok(Geolocation {
+                    lat: location.lat,
+                    lon: location.lon,
+                    name: Some(location.name),
+                    source: LocationSource::SavedDefault {
+                        location_id: location.id,
+                    },
+                    accuracy_km: 0.0,
+                    located_at: to_rfc3339(now),
+                });
+            }
+        }
+
+        if let Some(last) = &last_known {
+            if now - last.located_at < config.cache_ttl_secs as i64 {
+                return Ok(last.cached(now));
+            }
+        }
+
+        let mut last_error = None;
+        if config.network_lookup {
+            for kind in &config.ip_providers {
+                let Some(provider) = self.providers.iter().find(|p| p.kind() == *kind) else {
+                    continue;
+                };
+                match provider.locate().await {
+                    Ok(found) => return Ok(self.remember(*kind, found, now)),
+                    Err(e) => {
+                        eprintln!("Warning: {:?} location lookup failed: {}", kind, e);
+                        last_error = Some(e);
+                    }
+                }
+            }
+        }
+
+        if let Some(last) = &last_known {
+            return Ok(last.cached(now));
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            if config.network_lookup {
+                AppError::not_found("No location providers are configured")
+            } else {
+                AppError::not_found("No location is set and network geolocation is disabled")
+            }
+        }))
+    }
+
+    /// Cache a successful lookup for later requests
+    fn remember(&self, provider: IpProviderKind, found: IpLocation, now: i64) -> Geolocation {
+        let mut state = self.lock();
+        state.last_known = Some(LastKnown {
+            lat: found.lat,
+            lon: found.lon,
+            city: found.city.clone(),
+            provider,
+            located_at: now,
+        });
+        if let Err(e) = self.save(&state) {
+            eprintln!("Warning: Failed to save last known location: {}", e);
+        }
+
+        Geolocation {
+            lat: found.lat,
+            lon: found.lon,
+            name: found.city,
+            source: LocationSource::IpLookup { provider },
+            accuracy_km: IP_ACCURACY_KM,
+            located_at: to_rfc3339(now),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Stored> {
+        self.state.lock().expect("Geolocation lock poisoned")
     }
 
-    let body = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+    fn save(&self, stored: &Stored) -> Result<(), AppError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
 
-    let data: IpApiResponse =
-        serde_json::from_str(&body).map_err(|e| format!("Failed to parse location data: {}", e))?;
+        persist::save_json(path, stored, "geolocation")
+    }
+}
+
+/// IP lookup services with their base URLs from the environment
+fn providers(config: &EnvConfig) -> Vec<Arc<dyn IpLocationProvider>> {
+    vec![
+        Arc::new(IpWhoIsProvider::new(config.ipwho_is_base_url.clone())),
+        Arc::new(IpapiCoProvider::new(config.ipapi_co_base_url.clone())),
+        Arc::new(IpApiProvider::new(config.ip_api_base_url.clone())),
+    ]
+}
 
-    Ok(Coordinates {
-        latitude: data.lat,
-        longitude: data.lon,
-    })
+fn to_rfc3339(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .to_rfc3339()
 }
@@ -0,0 +1,53 @@
+use super::{IpLocation, IpLocationProvider, IpProviderKind};
+use crate::error::AppError;
+use crate::http_client::HttpClient;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct IpApiResponse {
+    status: String,
+    message: Option<String>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    city: Option<String>,
+}
+
+/// ip-api.com. The free tier is served over plain HTTP only, so it is
+/// tried after the HTTPS providers.
+pub struct IpApiProvider {
+    base_url: String,
+}
+
+impl IpApiProvider {
+    pub fn new(base_url: String) -> Self {
+        Self { base_url }
+    }
+}
+
+#[async_trait]
+impl IpLocationProvider for IpApiProvider {
+    fn kind(&self) -> IpProviderKind {
+        IpProviderKind::IpApi
+    }
+
+    async fn locate(&self) -> Result<IpLocation, AppError> {
+        let url = format!("{}/json/?fields=status,message,lat,lon,city", self.base_url);
+        let data: IpApiResponse = HttpClient::global().get_json(&url, "location").await?;
+
+        match (data.status.as_str(), data.lat, data.lon) {
+            ("success", Some(lat), Some(lon)) => Ok(IpLocation {
+                lat,
+                lon,
+                city: data.city,
+            }),
+            _ => Err(AppError::upstream(
+                200,
+                format!(
+                    "Location lookup failed: {}",
+                    data.message.as_deref().unwrap_or("no coordinates returned")
+                ),
+            )),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use super::{IpLocation, IpLocationProvider, IpProviderKind};
+use crate::error::AppError;
+use crate::http_client::HttpClient;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct IpapiCoResponse {
+    #[serde(default)]
+    error: bool,
+    reason: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    city: Option<String>,
+}
+
+/// ipapi.co, keyless and served over HTTPS
+pub struct IpapiCoProvider {
+    base_url: String,
+}
+
+impl IpapiCoProvider {
+    pub fn new(base_url: String) -> Self {
+        Self { base_url }
+    }
+}
+
+#[async_trait]
+impl IpLocationProvider for IpapiCoProvider {
+    fn kind(&self) -> IpProviderKind {
+        IpProviderKind::IpapiCo
+    }
+
+    async fn locate(&self) -> Result<IpLocation, AppError> {
+        let url = format!("{}/json/", self.base_url);
+        let data: IpapiCoResponse = HttpClient::global().get_json(&url, "location").await?;
+
+        match (data.error, data.latitude, data.longitude) {
+            (false, Some(lat), Some(lon)) => Ok(IpLocation {
+                lat,
+                lon,
+                city: data.city,
+            }),
+            _ => Err(AppError::upstream(
+                200,
+                format!(
+                    "Location lookup failed: {}",
+                    data.reason.as_deref().unwrap_or("no coordinates returned")
+                ),
+            )),
+        }
+    }
+}
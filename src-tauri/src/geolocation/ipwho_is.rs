@@ -0,0 +1,55 @@
+use super::{IpLocation, IpLocationProvider, IpProviderKind};
+use crate::error::AppError;
+use crate::http_client::HttpClient;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct IpWhoIsResponse {
+    success: bool,
+    message: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    city: Option<String>,
+}
+
+/// ipwho.is, keyless and served over HTTPS
+pub struct IpWhoIsProvider {
+    base_url: String,
+}
+
+impl IpWhoIsProvider {
+    pub fn new(base_url: String) -> Self {
+        Self { base_url }
+    }
+}
+
+#[async_trait]
+impl IpLocationProvider for IpWhoIsProvider {
+    fn kind(&self) -> IpProviderKind {
+        IpProviderKind::IpWhoIs
+    }
+
+    async fn locate(&self) -> Result<IpLocation, AppError> {
+        let url = format!(
+            "{}/?fields=success,message,latitude,longitude,city",
+            self.base_url
+        );
+        let data: IpWhoIsResponse = HttpClient::global().get_json(&url, "location").await?;
+
+        match (data.success, data.latitude, data.longitude) {
+            (true, Some(lat), Some(lon)) => Ok(IpLocation {
+                lat,
+                lon,
+                city: data.city,
+            }),
+            _ => Err(AppError::upstream(
+                200,
+                format!(
+                    "Location lookup failed: {}",
+                    data.message.as_deref().unwrap_or("no coordinates returned")
+                ),
+            )),
+        }
+    }
+}
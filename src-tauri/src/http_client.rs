@@ -0,0 +1,222 @@
+use crate::connectivity;
+use crate::error::AppError;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri_plugin_http::reqwest::{header, Client, Error, Response, StatusCode, Url};
+use tokio::time::Instant;
+
+static HTTP_CLIENT: OnceLock<HttpClient> = OnceLock::new();
+
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub connect_timeout: Duration,
+    pub read_timeout: Duration,
+    pub max_retries: u32,       // Attempts after the first one
+    pub base_backoff: Duration, // Delay before the first retry, doubled for each one after
+    pub max_backoff: Duration,
+    pub max_retry_after: Duration, // Longer Retry-After waits give up instead
+    pub host_interval: Duration,   // Minimum spacing between requests to one host
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+            max_retries: 2,
+            base_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            max_retry_after: Duration::from_secs(30),
+            host_interval: Duration::from_millis(100),
+        }
+    }
+}
+
+/// HTTP client shared by the external service integrations. Reuses
+/// connections, retries transient failures with backoff and spaces out
+/// requests to each host.
+pub struct HttpClient {
+    client: Client,
+    config: HttpConfig,
+    next_slot: Mutex<HashMap<String, Instant>>, // Earliest time each host may be called again
+}
+
+impl HttpClient {
+    pub fn new(config: HttpConfig) -> Result<Self, AppError> {
+        let client = Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(config.connect_timeout)
+            .read_timeout(config.read_timeout)
+            .build()
+            .map_err(|e| AppError::internal(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            config,
+            next_slot: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn global() -> &'static HttpClient {
+        HTTP_CLIENT.get_or_init(|| {
+            HttpClient::new(HttpConfig::default()).expect("Failed to build HTTP client")
+        })
+    }
+
+    /// GET `url`, retrying timeouts, failed connections, dropped requests,
+    /// 5xx and 429 responses. The last response is returned once retries run
+    /// out, so callers still see the error status.
+    pub async fn get(&self, url: &str) -> Result<Response, Error> {
+        let host = host_of(url);
+        let mut attempt = 0;
+
+        loop {
+            self.wait_for_host(&host).await;
+
+            let delay = match self.client.get(url).send().await {
+                Ok(response) => {
+                    connectivity::report(true);
+                    if attempt >= self.config.max_retries || !is_transient(response.status()) {
+                        return Ok(response);
+                    }
+                    match retry_after(&response) {
+                        Some(wait) if wait > self.config.max_retry_after => return Ok(response),
+                        Some(wait) => wait,
+                        None => self.backoff(attempt),
+                    }
+                }
+                Err(e) => {
+                    if attempt >= self.config.max_retries || !is_transient_error(&e) {
+                        connectivity::report(false);
+                        return Err(e);
+                    }
+                    self.backoff(attempt)
+                }
+            };
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// GET `url` and deserialize the JSON body. `what` names the resource in
+    /// error messages.
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        what: &str,
+    ) -> Result<T, AppError> {
+        let response = self
+            .get(url)
+            .await
+            .map_err(|e| AppError::network(format!("Failed to fetch {}: {}", what, e)))?;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after_secs = retry_after(&response).map(|wait| wait.as_secs() as u32);
+            return Err(AppError::rate_limited(
+                retry_after_secs,
+                format!("{} API rate limit reached", capitalize(what)),
+            ));
+        }
+        if !status.is_success() {
+            return Err(AppError::upstream(
+                status.as_u16(),
+                format!("{} API error: {}", capitalize(what), status),
+            ));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AppError::network(format!("Failed to read {} response: {}", what, e)))?;
+
+        serde_json::from_str(&body).map_err(|e| {
+            AppError::upstream(
+                status.as_u16(),
+                format!("Failed to parse {} data: {}", what, e),
+            )
+        })
+    }
+
+    /// Exponential backoff with jitter, between half and all of the full delay
+    fn backoff(&self, attempt: u32) -> Duration {
+        let full = self
+            .config
+            .base_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.config.max_backoff);
+
+        full.mul_f64(0.5 + fastrand::f64() * 0.5)
+    }
+
+    /// Reserve the next free slot for `host` and sleep until it comes up
+    async fn wait_for_host(&self, host: &str) {
+        let slot = {
+            let mut next_slot = self
+                .next_slot
+                .lock()
+                .expect("HTTP rate limiter lock poisoned");
+            let now = Instant::now();
+            let slot = next_slot.get(host).map_or(now, |next| (*next).max(now));
+            next_slot.insert(host.to_string(), slot + self.config.host_interval);
+            slot
+        };
+
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Transport failures a later attempt may not hit: timeouts, refused or
+/// reset connections, and connections closed before a response arrived
+fn is_transient_error(error: &Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_request()
+}
+
+/// Host and port requests are rate limited by
+fn host_of(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            let host = url.host_str()?.to_string();
+            Some(match url.port_or_known_default() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host,
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Wait requested by a `Retry-After` header, given in seconds or as an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,201 @@
+use crate::error::AppError;
+use crate::geolocation::Coordinates;
+use exif::{Exif, In, Tag, Value};
+use image::codecs::jpeg::JpegEncoder;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use img_parts::{Bytes, DynImage, ImageEXIF};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::io::Cursor;
+
+/// Quality JPEG photos are re-encoded at when they have to be rotated
+const JPEG_QUALITY: u8 = 90;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ImageMetadata {
+    pub width: u32,                    // As displayed, after orientation
+    pub height: u32,                   // As displayed, after orientation
+    pub format: String,                // MIME type, e.g. "image/jpeg"
+    pub orientation: u8,               // EXIF orientation, 1 when upright
+    pub captured_at: Option<String>,   // Camera's local time, e.g. "2024-06-01T14:30:00"
+    pub location: Option<Coordinates>, // Where the photo was taken, from EXIF GPS tags
+}
+
+/// What happens to an image's embedded metadata before it is stored, saved
+/// with the image store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct SanitizeOptions {
+    pub auto_rotate: bool, // Turn photos upright instead of relying on their orientation flag
+    pub strip_exif: bool,  // Drop EXIF, including GPS; stripped photos are always turned upright
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            auto_rotate: true,
+            strip_exif: true,
+        }
+    }
+}
+
+/// Format of an image and a decoder for it, guessed from its contents
+fn decoder(data: &[u8]) -> Result<(ImageFormat, impl ImageDecoder + '_), AppError> {
+    let invalid =
+        |e: &dyn std::fmt::Display| AppError::invalid_input(format!("Failed to read image: {}", e));
+
+    let reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| invalid(&e))?;
+    let format = reader
+        .format()
+        .ok_or_else(|| AppError::invalid_input("Unrecognized image format"))?;
+    let decoder = reader.into_decoder().map_err(|e| invalid(&e))?;
+
+    Ok((format, decoder))
+}
+
+/// Read an image's dimensions, format and EXIF details without decoding it
+pub fn read(data: &[u8]) -> Result<ImageMetadata, AppError> {
+    let (format, mut decoder) = decoder(data)?;
+    let (width, height) = decoder.dimensions();
+    // Unreadable EXIF is treated as absent; the pixels are still usable
+    let exif = decoder
+        .exif_metadata()
+        .ok()
+        .flatten()
+        .and_then(|raw| exif::Reader::new().read_raw(raw).ok());
+
+    let orientation = exif
+        .as_ref()
+        .and_then(|exif| exif.get_field(Tag::Orientation, In::PRIMARY))
+        .and_then(|field| field.value.get_uint(0))
+        .and_then(|value| u8::try_from(value).ok())
+        .filter(|value| (1..=8).contains(value))
+        .unwrap_or(1);
+    // Orientations 5-8 turn the image a quarter, swapping its sides
+    let (width, height) = if orientation >= 5 {
+        (height, width)
+    } else {
+        (width, height)
+    };
+
+    Ok(ImageMetadata {
+        width,
+        height,
+        format: format.to_mime_type().to_string(),
+        orientation,
+        captured_at: exif.as_ref().and_then(captured_at),
+        location: exif.as_ref().and_then(location),
+    })
+}
+
+fn captured_at(exif: &Exif) -> Option<String> {
+    let field = [Tag::DateTimeOriginal, Tag::DateTime]
+        .into_iter()
+        .find_map(|tag| exif.get_field(tag, In::PRIMARY))?;
+    let Value::Ascii(ref values) = field.value else {
+        return None;
+    };
+    let time = exif::DateTime::from_ascii(values.first()?).ok()?;
+
+    chrono::NaiveDate::from_ymd_opt(time.year.into(), time.month.into(), time.day.into())?
+        .and_hms_opt(time.hour.into(), time.minute.into(), time.second.into())
+        .map(|time| time.format("%Y-%m-%dT%H:%M:%S").to_string())
+}
+
+fn location(exif: &Exif) -> Option<Coordinates> {
+    let degrees = |tag: Tag, reference: Tag, negative: u8| -> Option<f64> {
+        let Value::Rational(ref parts) = exif.get_field(tag, In::PRIMARY)?.value else {
+            return None;
+        };
+        let [d, m, s] = parts.get(..3)? else {
+            return None;
+        };
+        let value = d.to_f64() + m.to_f64() / 60.0 + s.to_f64() / 3600.0;
+
+        let sign = match exif.get_field(reference, In::PRIMARY)?.value {
+            Value::Ascii(ref values) if values.first()?.first() == Some(&negative) => -1.0,
+            _ => 1.0,
+        };
+        value.is_finite().then_some(sign * value)
+    };
+
+    let latitude = degrees(Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S')?;
+    let longitude = degrees(Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W')?;
+    ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)).then_some(
+        Coordinates {
+            latitude,
+            longitude,
+        },
+    )
+}
+
+/// Decode an image turned the way its EXIF orientation says it is displayed
+pub fn decode_upright(data: &[u8]) -> Result<DynamicImage, AppError> {
+    let (_, mut decoder) = decoder(data)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+
+    let mut image = DynamicImage::from_decoder(decoder)
+        .map_err(|e| AppError::invalid_input(format!("Failed to load image: {}", e)))?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Apply `options` to an image about to be stored. Images that need no
+/// change, and data that is not a recognized image, are returned as they are.
+pub fn sanitize(data: &[u8], options: &SanitizeOptions) -> Result<Vec<u8>, AppError> {
+    let Ok((format, mut decoder)) = decoder(data) else {
+        return Ok(data.to_vec());
+    };
+    let has_exif = decoder.exif_metadata().ok().flatten().is_some();
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    drop(decoder);
+
+    let rotate =
+        orientation != Orientation::NoTransforms && (options.auto_rotate || options.strip_exif);
+    if rotate {
+        // Re-encoding writes no EXIF, so this strips it too
+        return encode(&decode_upright(data)?, format);
+    }
+    if !(options.strip_exif && has_exif) {
+        return Ok(data.to_vec());
+    }
+
+    // JPEG, PNG and WebP lose their EXIF without being re-encoded
+    match DynImage::from_bytes(Bytes::copy_from_slice(data)) {
+        Ok(Some(mut image)) => {
+            image.set_exif(None);
+            Ok(image.encoder().bytes().to_vec())
+        }
+        _ => encode(&decode_upright(data)?, format),
+    }
+}
+
+/// Encode in `format`, or as PNG when there is no encoder for it. JPEG
+/// drops transparency.
+pub fn encode(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, AppError> {
+    let mut encoded = Cursor::new(Vec::new());
+    let result = match format {
+        ImageFormat::Jpeg => image
+            .to_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut encoded, JPEG_QUALITY)),
+        ImageFormat::WebP
+            if !matches!(
+                image,
+                DynamicImage::ImageRgb8(_) | DynamicImage::ImageRgba8(_)
+            ) =>
+        {
+            DynamicImage::ImageRgba8(image.to_rgba8()).write_to(&mut encoded, format)
+        }
+        _ => image.write_to(&mut encoded, format),
+    };
+
+    if result.is_err() {
+        encoded = Cursor::new(Vec::new());
+        image
+            .write_to(&mut encoded, ImageFormat::Png)
+            .map_err(|e| AppError::internal(format!("Failed to encode image: {}", e)))?;
+    }
+    Ok(encoded.into_inner())
+}
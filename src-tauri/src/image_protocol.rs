@@ -0,0 +1,204 @@
+use crate::background_removal;
+use crate::error::AppError;
+use crate::image_service;
+use crate::image_store::{ImageId, ImageStore};
+use percent_encoding::percent_decode_str;
+use std::fs;
+use std::path::Path;
+use tauri::http::{header, HeaderValue, Method, Request, Response, StatusCode};
+
+/// URI scheme image bytes travel over as raw bodies, instead of as JSON
+/// arrays of numbers through commands:
+///
+/// - `GET image://localhost/<id>` serves a stored image, and with
+///   `?size=<pixels>` a downscaled variant
+/// - `POST image://localhost/?owner=<owner>` stores the body and answers
+///   with the new image's ID
+/// - `POST image://localhost/background-removal` answers with the body's
+///   subject cut out, as a PNG
+///
+/// Errors answer with the `AppError` as JSON. Windows serves the scheme as
+/// `http://image.localhost/`. Only the app's own pages may read answers,
+/// upload, or remove backgrounds; other origins are refused before anything
+/// is stored or run. Requests without an origin are the app's too, as the
+/// scheme is only reachable from its webviews.
+pub const SCHEME: &str = "image";
+
+const BACKGROUND_REMOVAL: &str = "background-removal";
+
+/// Origins the app's pages load from: `tauri://localhost` on macOS and Linux,
+/// `http(s)://tauri.localhost` on Windows
+const APP_ORIGINS: [&str; 3] = [
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+];
+/// Vite dev server the pages load from in debug builds
+const DEV_ORIGIN: &str = "http://localhost:1420";
+
+/// Images are addressed by their content, so one served by ID never changes
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// Names from before content addressing are checked again on every use
+const REVALIDATE: &str = "no-cache";
+
+pub fn handle(request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let url = tauri::Url::parse(&request.uri().to_string()).ok();
+    let query = |key: &str| {
+        url.as_ref()?
+            .query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.into_owned())
+    };
+    let reference = request.uri().path().trim_start_matches('/');
+    let origin = request.headers().get(header::ORIGIN);
+    let foreign = origin.is_some_and(|origin| !is_app_origin(origin));
+    let origin = origin.filter(|origin| is_app_origin(origin)).cloned();
+
+    let result = match (request.method(), reference) {
+        (&Method::OPTIONS | &Method::POST, _) if foreign => Ok(forbidden()),
+        (&Method::OPTIONS, _) => Ok(preflight()),
+        (&Method::GET, _) => serve(&request, reference, query("size")),
+        (&Method::POST, "") => upload(request.body(), query("owner")),
+        (&Method::POST, BACKGROUND_REMOVAL) => {
+            background_removal::remove_background(request.body()).map(|png| {
+                respond(StatusCode::OK)
+                    .header(header::CONTENT_TYPE, "image/png")
+                    .body(png)
+            })
+        }
+        _ => Ok(respond(StatusCode::METHOD_NOT_ALLOWED).body(Vec::new())),
+    };
+
+    let mut response = result
+        .and_then(|response| {
+            response.map_err(|e| AppError::internal(format!("Failed to build response: {}", e)))
+        })
+        .unwrap_or_else(error_response);
+
+    // Pages load from another origin than the scheme's, so the app's own
+    // must be allowed to read the body
+    let headers = response.headers_mut();
+    if let Some(origin) = origin {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    }
+    headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+    response
+}
+
+type Built = Result<Response<Vec<u8>>, tauri::http::Error>;
+
+fn is_app_origin(origin: &HeaderValue) -> bool {
+    origin.to_str().is_ok_and(|origin| {
+        APP_ORIGINS.contains(&origin) || (cfg!(debug_assertions) && origin == DEV_ORIGIN)
+    })
+}
+
+/// Response headers every answer carries
+fn respond(status: StatusCode) -> tauri::http::response::Builder {
+    Response::builder()
+        .status(status)
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+}
+
+/// Answer to writes and preflights from pages that are not the app's
+fn forbidden() -> Built {
+    let error = AppError::invalid_input("Only the app may upload or process images");
+    respond(StatusCode::FORBIDDEN)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(&error).unwrap_or_default())
+}
+
+/// Uploads send a binary body, which browsers check with the scheme first
+fn preflight() -> Built {
+    respond(StatusCode::NO_CONTENT)
+        .header(header::ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, OPTIONS")
+        .header(header::ACCESS_CONTROL_ALLOW_HEADERS, "Content-Type")
+        .header(header::ACCESS_CONTROL_MAX_AGE, "86400")
+        .body(Vec::new())
+}
+
+fn serve(
+    request: &Request<Vec<u8>>,
+    reference: &str,
+    size: Option<String>,
+) -> Result<Built, AppError> {
+    let store = ImageStore::global()?;
+    // Names from before content addressing may contain escaped characters
+    let reference = percent_decode_str(reference).decode_utf8_lossy();
+
+    let (path, content_type) = match size {
+        Some(size) => {
+            let size = size
+                .parse()
+                .map_err(|_| AppError::invalid_input(format!("Invalid image size '{}'", size)))?;
+            let path = store.variant(&reference, size)?;
+            (path, store.variant_config().format.mime_type().to_string())
+        }
+        None => {
+            let info = store.info(&reference)?;
+            let content_type = info.metadata.map(|metadata| metadata.format);
+            (
+                info.path,
+                content_type.unwrap_or_else(|| "application/octet-stream".to_string()),
+            )
+        }
+    };
+
+    // Variants of one image differ by size and format, both part of their path
+    let path = Path::new(&path);
+    let version = [path.parent().and_then(Path::file_name), path.file_name()]
+        .into_iter()
+        .flatten()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let etag = format!("\"{}\"", version);
+    let cache_control = if ImageId::parse(&reference).is_some() {
+        IMMUTABLE
+    } else {
+        REVALIDATE
+    };
+
+    let unchanged = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+    if unchanged {
+        return Ok(respond(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, &etag)
+            .header(header::CACHE_CONTROL, cache_control)
+            .body(Vec::new()));
+    }
+
+    let body = fs::read(path).map_err(|e| AppError::io(format!("Failed to read image: {}", e)))?;
+    Ok(respond(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, cache_control)
+        .body(body))
+}
+
+fn upload(body: &[u8], owner: Option<String>) -> Result<Built, AppError> {
+    let id = image_service::save_upload(body, owner.as_deref())?;
+
+    Ok(respond(StatusCode::CREATED)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(id.to_string().into_bytes()))
+}
+
+fn error_response(error: AppError) -> Response<Vec<u8>> {
+    let status = match error {
+        AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+        AppError::InvalidInput { .. } | AppError::InvalidPath { .. } => StatusCode::BAD_REQUEST,
+        AppError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        AppError::ModelUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let body = serde_json::to_vec(&error).unwrap_or_default();
+
+    respond(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .unwrap_or_else(|_| Response::new(Vec::new()))
+}
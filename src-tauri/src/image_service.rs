@@ -1,122 +1,374 @@
+use crate::error::AppError;
+use crate::image_metadata::{self, ImageMetadata, SanitizeOptions};
+use crate::image_store::{ImageId, ImageInfo, ImageStore, UNCLAIMED_GRACE_SECS};
+use crate::image_variants::VariantConfig;
+use crate::persist;
+use image::ImageFormat;
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use std::fs;
-use std::path::PathBuf;
-use tauri::Manager;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Type)]
-pub struct ImageInfo {
-    pub name: String,
-    pub path: String,
+static UPLOADS: OnceLock<Uploads> = OnceLock::new();
+
+/// Longest file name most filesystems accept, in bytes
+const MAX_NAME_LEN: usize = 255;
+
+/// Formats the webview can display, as recognized from file contents
+const ACCEPTED_FORMATS: [ImageFormat; 5] = [
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::WebP,
+    ImageFormat::Gif,
+    ImageFormat::Bmp,
+];
+
+/// Device names Windows reserves in every directory, with any extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Image file name from before content addressing, checked so that it
+/// names a file directly inside the images directory on every platform
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageName(String);
+
+impl ImageName {
+    pub fn new(name: &str) -> Result<Self, AppError> {
+        let invalid = |reason: &str| {
+            AppError::invalid_path(format!("Invalid image name '{}': {}", name, reason))
+        };
+
+        if name.is_empty() {
+            return Err(invalid("name is empty"));
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(invalid("name is too long"));
+        }
+        if name.contains(['/', '\\']) {
+            return Err(invalid("path separators are not allowed"));
+        }
+        if name.chars().all(|c| c == '.') {
+            return Err(invalid("relative path components are not allowed"));
+        }
+        if name.contains(['<', '>', ':', '"', '|', '?', '*']) || name.chars().any(char::is_control)
+        {
+            return Err(invalid("name contains a reserved character"));
+        }
+        // Windows drops these, so "a.png." would open "a.png"
+        if name.ends_with(['.', ' ']) {
+            return Err(invalid("name must not end with a dot or space"));
+        }
+        let stem = name.split('.').next().unwrap_or_default().trim_end();
+        if RESERVED_NAMES
+            .iter()
+            .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+        {
+            return Err(invalid("name is reserved by the system"));
+        }
+
+        // Whatever the platform's path rules, the name must be one plain component
+        let mut components = Path::new(name).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(component)), None) if component == name => {
+                Ok(Self(name.to_string()))
+            }
+            _ => Err(invalid("name must be a single file name")),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
-fn get_images_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let media_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+impl fmt::Display for ImageName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
 
-    let images_dir = media_dir.join("images");
+/// Format uploads can be re-encoded to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadFormat {
+    Png,
+    Jpeg, // Drops transparency
+    Webp, // Lossless
+}
 
-    // Create directory if it doesn't exist
-    if !images_dir.exists() {
-        fs::create_dir_all(&images_dir)
-            .map_err(|e| format!("Failed to create images directory: {}", e))?;
+impl UploadFormat {
+    fn image_format(self) -> ImageFormat {
+        match self {
+            Self::Png => ImageFormat::Png,
+            Self::Jpeg => ImageFormat::Jpeg,
+            Self::Webp => ImageFormat::WebP,
+        }
     }
+}
 
-    Ok(images_dir)
+/// What an upload may be before it is decoded. The pixel count guards
+/// against small files that decode to huge images.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct UploadLimits {
+    pub max_bytes: u32,
+    pub max_pixels: u32,                    // Width times height
+    pub normalize_to: Option<UploadFormat>, // Re-encode every upload to this format
 }
 
-#[tauri::command]
-#[specta::specta]
-pub async fn save_image(
-    app: tauri::AppHandle,
-    name: String,
-    data: Vec<u8>,
-) -> Result<String, String> {
-    let images_dir = get_images_dir(&app)?;
-    let file_path = images_dir.join(&name);
+impl Default for UploadLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 25 * 1024 * 1024,
+            max_pixels: 50_000_000,
+            normalize_to: None,
+        }
+    }
+}
+
+impl UploadLimits {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.max_bytes == 0 || self.max_pixels == 0 {
+            return Err(AppError::invalid_input("Upload limits must be above zero"));
+        }
+        Ok(())
+    }
+}
+
+/// Checks image uploads against limits shared by every app, persisted in
+/// the app data dir
+pub struct Uploads {
+    path: Option<PathBuf>,
+    limits: RwLock<UploadLimits>,
+}
+
+impl Uploads {
+    /// Load the upload limits file at `path`, starting from defaults if it
+    /// does not exist yet. Without a path nothing is persisted.
+    pub fn open(path: Option<PathBuf>) -> Result<Self, AppError> {
+        let limits = match &path {
+            Some(path) => persist::load_json::<UploadLimits>(path, "upload limits")?,
+            None => None,
+        }
+        .unwrap_or_default();
+        limits.validate()?;
+
+        Ok(Self {
+            path,
+            limits: RwLock::new(limits),
+        })
+    }
+
+    pub fn init(path: PathBuf) -> Result<(), AppError> {
+        let uploads = Self::open(Some(path))?;
+        UPLOADS
+            .set(uploads)
+            .map_err(|_| AppError::internal("Upload limits already initialized"))
+    }
+
+    pub fn global() -> &'static Uploads {
+        UPLOADS
+            .get_or_init(|| Self::open(None).expect("In-memory upload limits cannot fail to open"))
+    }
+
+    pub fn limits(&self) -> UploadLimits {
+        self.limits
+            .read()
+            .expect("Upload limits lock poisoned")
+            .clone()
+    }
+
+    /// Replace the limits, returning them as stored
+    pub fn set_limits(&self, limits: UploadLimits) -> Result<UploadLimits, AppError> {
+        limits.validate()?;
+        let mut current = self.limits.write().expect("Upload limits lock poisoned");
+        if let Some(path) = &self.path {
+            persist::save_json(path, &limits, "upload limits")?;
+        }
+        *current = limits.clone();
+        Ok(limits)
+    }
+
+    /// Check that `data` is an image in an accepted format within the
+    /// limits, reading only its header so nothing large is decoded
+    pub fn check(&self, data: &[u8]) -> Result<ImageMetadata, AppError> {
+        let limits = self.limits();
+
+        if data.len() as u64 > limits.max_bytes as u64 {
+            return Err(AppError::too_large(format!(
+                "Image is {} bytes, over the limit of {}",
+                data.len(),
+                limits.max_bytes
+            )));
+        }
+        let accepted =
+            image::guess_format(data).is_ok_and(|format| ACCEPTED_FORMATS.contains(&format));
+        if !accepted {
+            return Err(AppError::invalid_input("Data is not a supported image"));
+        }
+        let metadata = image_metadata::read(data)?;
+        let pixels = metadata.width as u64 * metadata.height as u64;
+        if pixels > limits.max_pixels as u64 {
+            return Err(AppError::too_large(format!(
+                "Image is {}x{} pixels, over the limit of {} pixels",
+                metadata.width, metadata.height, limits.max_pixels
+            )));
+        }
 
-    fs::write(&file_path, data).map_err(|e| format!("Failed to write image: {}", e))?;
+        Ok(metadata)
+    }
+
+    /// Check an upload and decode it in full, so corrupt files are refused
+    /// before they are stored. Returns the bytes to store, re-encoded when
+    /// the limits ask for a normalized format.
+    pub fn prepare(&self, data: &[u8]) -> Result<Vec<u8>, AppError> {
+        self.check(data)?;
+        let image = image_metadata::decode_upright(data)?;
+
+        match self.limits().normalize_to {
+            Some(format) => image_metadata::encode(&image, format.image_format()),
+            None => Ok(data.to_vec()),
+        }
+    }
+}
+
+/// Check and store an upload for `owner`, and start generating its variants
+/// so thumbnails are ready by the time a card shows the image
+pub fn save_upload(data: &[u8], owner: Option<&str>) -> Result<ImageId, AppError> {
+    let data = Uploads::global().prepare(data)?;
+    let store = ImageStore::global()?;
+    let id = store.save(&data, owner)?;
 
-    file_path
-        .to_str()
-        .ok_or_else(|| "Invalid path".to_string())
-        .map(|s| s.to_string())
+    let reference = id.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = store.generate_variants(&reference) {
+            eprintln!(
+                "Warning: Failed to generate variants of image {}: {}",
+                reference, e
+            );
+        }
+    });
+
+    Ok(id)
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_image(app: tauri::AppHandle, name: String) -> Result<Vec<u8>, String> {
-    let images_dir = get_images_dir(&app)?;
-    let file_path = images_dir.join(&name);
+pub async fn get_image_path(id: String) -> Result<String, AppError> {
+    ImageStore::global()?.path(&id)
+}
 
-    if !file_path.exists() {
-        return Err(format!("Image '{}' not found", name));
-    }
+/// Path of a copy of an image downscaled to the configured size closest to
+/// `size` pixels on its longest side
+#[tauri::command]
+#[specta::specta]
+pub async fn get_image_variant(id: String, size: u32) -> Result<String, AppError> {
+    let store = ImageStore::global()?;
+    tauri::async_runtime::spawn_blocking(move || store.variant(&id, size))
+        .await
+        .map_err(|e| AppError::internal(format!("Image variant task failed: {}", e)))?
+}
 
-    fs::read(&file_path).map_err(|e| format!("Failed to read image: {}", e))
+#[tauri::command]
+#[specta::specta]
+pub async fn get_image_variant_config() -> Result<VariantConfig, AppError> {
+    Ok(ImageStore::global()?.variant_config())
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_image_path(app: tauri::AppHandle, name: String) -> Result<String, String> {
-    let images_dir = get_images_dir(&app)?;
-    let file_path = images_dir.join(&name);
+pub async fn set_image_variant_config(config: VariantConfig) -> Result<VariantConfig, AppError> {
+    ImageStore::global()?.set_variant_config(config)
+}
 
-    if !file_path.exists() {
-        return Err(format!("Image '{}' not found", name));
-    }
+#[tauri::command]
+#[specta::specta]
+pub async fn get_upload_limits() -> Result<UploadLimits, AppError> {
+    Ok(Uploads::global().limits())
+}
 
-    file_path
-        .to_str()
-        .ok_or_else(|| "Invalid path".to_string())
-        .map(|s| s.to_string())
+#[tauri::command]
+#[specta::specta]
+pub async fn set_upload_limits(limits: UploadLimits) -> Result<UploadLimits, AppError> {
+    Uploads::global().set_limits(limits)
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn remove_image(app: tauri::AppHandle, name: String) -> Result<(), String> {
-    let images_dir = get_images_dir(&app)?;
-    let file_path = images_dir.join(&name);
+pub async fn get_image_sanitize_options() -> Result<SanitizeOptions, AppError> {
+    Ok(ImageStore::global()?.sanitize_options())
+}
 
-    if !file_path.exists() {
-        return Err(format!("Image '{}' not found", name));
-    }
+#[tauri::command]
+#[specta::specta]
+pub async fn set_image_sanitize_options(
+    options: SanitizeOptions,
+) -> Result<SanitizeOptions, AppError> {
+    ImageStore::global()?.set_sanitize_options(options)
+}
 
-    fs::remove_file(&file_path).map_err(|e| format!("Failed to remove image: {}", e))
+#[tauri::command]
+#[specta::specta]
+pub async fn retain_image(id: String, owner: String) -> Result<(), AppError> {
+    ImageStore::global()?.retain(&id, &owner)
 }
 
+/// Returns whether the image was deleted because nothing uses it any more
 #[tauri::command]
 #[specta::specta]
-pub async fn list_images(app: tauri::AppHandle) -> Result<Vec<ImageInfo>, String> {
-    let images_dir = get_images_dir(&app)?;
+pub async fn release_image(id: String, owner: String) -> Result<bool, AppError> {
+    ImageStore::global()?.release(&id, &owner)
+}
 
-    if !images_dir.exists() {
-        return Ok(vec![]);
-    }
+#[tauri::command]
+#[specta::specta]
+pub async fn list_images() -> Result<Vec<ImageInfo>, AppError> {
+    Ok(ImageStore::global()?.list())
+}
+
+/// Delete images no record has claimed within the grace period. Returns the
+/// number of images and stray files removed.
+#[tauri::command]
+#[specta::specta]
+pub async fn collect_image_garbage() -> Result<u32, AppError> {
+    let cutoff = chrono::Utc::now().timestamp() - UNCLAIMED_GRACE_SECS;
+    ImageStore::global()?.collect_garbage(cutoff)
+}
 
-    let entries = fs::read_dir(&images_dir)
-        .map_err(|e| format!("Failed to read images directory: {}", e))?;
+// Commands from before image bytes moved to the `image://` scheme, kept for
+// one release so callers can move over
 
-    let mut images = Vec::new();
+/// Store an image sent as a JSON array of bytes
+#[deprecated(note = "POST the bytes to `image://localhost/` instead")]
+#[tauri::command]
+#[specta::specta]
+pub async fn save_image(data: Vec<u8>, owner: Option<String>) -> Result<String, AppError> {
+    save_upload(&data, owner.as_deref()).map(|id| id.to_string())
+}
 
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
+/// Read an image as a JSON array of bytes
+#[deprecated(note = "GET `image://localhost/<id>` instead")]
+#[tauri::command]
+#[specta::specta]
+pub async fn get_image(id: String) -> Result<Vec<u8>, AppError> {
+    ImageStore::global()?.read(&id)
+}
 
-        if path.is_file() {
-            if let (Some(name), Some(path_str)) = (
-                path.file_name().and_then(|n| n.to_str()),
-                path.to_str(),
-            ) {
-                images.push(ImageInfo {
-                    name: name.to_string(),
-                    path: path_str.to_string(),
-                });
-            }
-        }
-    }
+/// Images are deleted once their last owner releases them, and unclaimed
+/// ones after the grace period, so this only checks that the image exists
+#[deprecated(note = "Use `release_image` with the owner that claimed the image")]
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_image(name: String) -> Result<(), AppError> {
+    ImageStore::global()?.info(&name).map(|_| ())
+}
 
-    Ok(images)
+/// Cut out an image sent as a JSON array of bytes, answering with a PNG
+#[deprecated(note = "POST the bytes to `image://localhost/background-removal` instead")]
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_background(image_data: Vec<u8>) -> Result<Vec<u8>, AppError> {
+    crate::background_removal::remove_background(&image_data)
 }
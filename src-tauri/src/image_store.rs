@@ -0,0 +1,625 @@
+use crate::error::AppError;
+use crate::image_metadata::{self, ImageMetadata, SanitizeOptions};
+use crate::image_service::ImageName;
+use crate::image_variants::{self, VariantConfig};
+use crate::persist;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+static IMAGE_STORE: OnceLock<ImageStore> = OnceLock::new();
+
+/// How long an image saved without an owner is kept, so a form can upload
+/// a picture before the record that uses it is saved
+pub const UNCLAIMED_GRACE_SECS: i64 = 60 * 60;
+
+const MAX_OWNER_LEN: usize = 128;
+
+/// Files of the store itself in the images directory, never taken for
+/// images saved by name
+const RESERVED_FILES: [&str; 2] = ["index.json", "settings.json"];
+
+/// Owner given to images migrated from name-based storage, whose records
+/// are not known
+const LEGACY_OWNER_PREFIX: &str = "legacy:";
+
+/// BLAKE3 hash of an image's bytes, as 64 lowercase hex digits
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ImageId(String);
+
+impl ImageId {
+    pub fn of(data: &[u8]) -> Self {
+        Self(blake3::hash(data).to_hex().to_string())
+    }
+
+    pub fn parse(id: &str) -> Option<Self> {
+        let valid = id.len() == 64 && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+        valid.then(|| Self(id.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ImageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ImageInfo {
+    pub id: String,
+    pub path: String,
+    pub size: u32, // bytes
+    pub owners: Vec<String>,
+    pub created_at: String,              // RFC 3339
+    pub metadata: Option<ImageMetadata>, // None when the data is not a recognized image
+}
+
+/// Index entry of a stored blob
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Blob {
+    size: u64,
+    created_at: i64,          // Moved up when saved again while unclaimed
+    owners: BTreeSet<String>, // Records using the image, e.g. "clothing:1718000000000"
+    #[serde(default)]
+    metadata: Option<ImageMetadata>,
+}
+
+/// On-disk layout of `settings.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Settings {
+    #[serde(default)]
+    variants: VariantConfig,
+    #[serde(default)]
+    sanitize: SanitizeOptions,
+}
+
+/// On-disk layout of `index.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    blobs: BTreeMap<ImageId, Blob>,
+    #[serde(default)]
+    aliases: BTreeMap<String, ImageId>, // Names images had before content addressing
+}
+
+/// Images stored once per distinct content under their BLAKE3 hash, with an
+/// index of the records that use each one. Blobs live in
+/// `blobs/<first two hex digits>/<id>` beside `index.json`, and downscaled
+/// variants in `variants/<size>/<id>.<extension>`. Settings changed from the
+/// UI are kept in `settings.json`.
+pub struct ImageStore {
+    dir: PathBuf,
+    index: Mutex<Index>,
+    settings: RwLock<Settings>,
+}
+
+impl ImageStore {
+    /// Open the store in `dir`, creating it if needed. Files saved by name
+    /// before content addressing are moved into the store and stay
+    /// reachable by their old name.
+    pub fn open(dir: PathBuf) -> Result<Self, AppError> {
+        fs::create_dir_all(dir.join("blobs"))
+            .map_err(|e| AppError::io(format!("Failed to create images directory: {}", e)))?;
+
+        let index_path = dir.join("index.json");
+        let index = if index_path.exists() {
+            let body = fs::read_to_string(&index_path)
+                .map_err(|e| AppError::io(format!("Failed to read image index: {}", e)))?;
+            serde_json::from_str(&body)
+                .map_err(|e| AppError::io(format!("Failed to parse image index: {}", e)))?
+        } else {
+            Index::default()
+        };
+        let mut settings =
+            persist::load_json::<Settings>(&dir.join("settings.json"), "image settings")?
+                .unwrap_or_default();
+        // A hand-edited file with sizes out of range falls back to the defaults
+        settings.variants = settings.variants.normalize().unwrap_or_default();
+
+        let store = Self {
+            dir,
+            index: Mutex::new(index),
+            settings: RwLock::new(settings),
+        };
+        store.read_missing_metadata()?;
+        store.migrate_named_files()?;
+        Ok(store)
+    }
+
+    pub fn init(dir: PathBuf) -> Result<(), AppError> {
+        let store = Self::open(dir)?;
+        IMAGE_STORE
+            .set(store)
+            .map_err(|_| AppError::internal("Image store already initialized"))
+    }
+
+    pub fn global() -> Result<&'static ImageStore, AppError> {
+        IMAGE_STORE
+            .get()
+            .ok_or_else(|| AppError::internal("Image store not initialized"))
+    }
+
+    /// Store `data`, rotated and stripped of EXIF as the sanitize options
+    /// say, unless the same image is already stored, and record `owner` as
+    /// using it. Returns the image's ID.
+    pub fn save(&self, data: &[u8], owner: Option<&str>) -> Result<ImageId, AppError> {
+        if let Some(owner) = owner {
+            check_owner(owner)?;
+        }
+
+        let original = image_metadata::read(data).ok();
+        let data = &image_metadata::sanitize(data, &self.sanitize_options())?;
+        // The capture date survives stripping; the location does not
+        let metadata = image_metadata::read(data)
+            .ok()
+            .map(|metadata| ImageMetadata {
+                captured_at: original.and_then(|original| original.captured_at),
+                ..metadata
+            });
+
+        let id = ImageId::of(data);
+        let path = self.blob_path(&id);
+        let mut index = self.lock();
+
+        if !path.is_file() {
+            write_atomically(&path, data)?;
+        }
+        let now = chrono::Utc::now().timestamp();
+        let blob = index.blobs.entry(id.clone()).or_insert_with(|| Blob {
+            size: data.len() as u64,
+            created_at: now,
+            owners: BTreeSet::new(),
+            metadata,
+        });
+        // A retried upload starts a new grace period, so the image is not
+        // collected right after its ID was handed out again
+        if blob.owners.is_empty() {
+            blob.created_at = now;
+        }
+        if let Some(owner) = owner {
+            blob.owners.insert(owner.to_string());
+        }
+
+        self.save_index(&index)?;
+        Ok(id)
+    }
+
+    pub fn read(&self, reference: &str) -> Result<Vec<u8>, AppError> {
+        let path = self.path_of(reference)?;
+
+        fs::read(&path).map_err(|e| AppError::io(format!("Failed to read image: {}", e)))
+    }
+
+    /// File path of a stored image
+    pub fn path(&self, reference: &str) -> Result<String, AppError> {
+        let path = self.path_of(reference)?;
+
+        path.to_str()
+            .ok_or_else(|| AppError::internal("Invalid path"))
+            .map(|s| s.to_string())
+    }
+
+    /// Record `owner` as using an image
+    pub fn retain(&self, reference: &str, owner: &str) -> Result<(), AppError> {
+        check_owner(owner)?;
+        let mut index = self.lock();
+        let id = resolve(&index, reference)?;
+
+        if let Some(blob) = index.blobs.get_mut(&id) {
+            blob.owners.insert(owner.to_string());
+        }
+        self.save_index(&index)
+    }
+
+    /// Drop `owner`'s use of an image, deleting it once nothing uses it.
+    /// Releasing by a pre-migration name also drops the owner recorded for
+    /// that name, as removing it by name used to delete the file. Releasing
+    /// by an owner that never held the image changes nothing, so unclaimed
+    /// images are left to garbage collection. Returns whether the image was
+    /// deleted.
+    pub fn release(&self, reference: &str, owner: &str) -> Result<bool, AppError> {
+        let mut index = self.lock();
+        let id = resolve(&index, reference)?;
+
+        let legacy_owner = index
+            .aliases
+            .contains_key(reference)
+            .then(|| format!("{}{}", LEGACY_OWNER_PREFIX, reference));
+        let Some(blob) = index.blobs.get_mut(&id) else {
+            return Ok(false);
+        };
+        let mut released = blob.owners.remove(owner);
+        if let Some(legacy_owner) = legacy_owner {
+            released |= blob.owners.remove(&legacy_owner);
+        }
+        if !released {
+            return Ok(false);
+        }
+
+        let unused = blob.owners.is_empty();
+        if unused {
+            self.delete(&mut index, &id)?;
+        }
+        self.save_index(&index)?;
+        Ok(unused)
+    }
+
+    pub fn sanitize_options(&self) -> SanitizeOptions {
+        self.settings
+            .read()
+            .expect("Image settings lock poisoned")
+            .sanitize
+            .clone()
+    }
+
+    /// Change how images saved from now on are treated, returning the options
+    /// as stored; stored images are left as they are
+    pub fn set_sanitize_options(
+        &self,
+        options: SanitizeOptions,
+    ) -> Result<SanitizeOptions, AppError> {
+        self.update_settings(|settings| settings.sanitize = options.clone())?;
+        Ok(options)
+    }
+
+    pub fn variant_config(&self) -> VariantConfig {
+        self.settings
+            .read()
+            .expect("Image settings lock poisoned")
+            .variants
+            .clone()
+    }
+
+    /// Change the variant sizes or format, returning the config as stored.
+    /// Variants no longer configured are removed by the next garbage
+    /// collection.
+    pub fn set_variant_config(&self, config: VariantConfig) -> Result<VariantConfig, AppError> {
+        let config = config.normalize()?;
+        self.update_settings(|settings| settings.variants = config.clone())?;
+        Ok(config)
+    }
+
+    /// Path of a downscaled copy of an image at the configured size closest
+    /// to `size`, generating it on first request
+    pub fn variant(&self, reference: &str, size: u32) -> Result<String, AppError> {
+        let id = resolve(&self.lock(), reference)?;
+        let config = self.variant_config();
+        let path = self.render_variant(&id, config.size_for(size), &config)?;
+
+        path.to_str()
+            .ok_or_else(|| AppError::internal("Invalid path"))
+            .map(|s| s.to_string())
+    }
+
+    /// Generate every configured variant of an image not generated yet
+    pub fn generate_variants(&self, reference: &str) -> Result<(), AppError> {
+        let id = resolve(&self.lock(), reference)?;
+        let config = self.variant_config();
+
+        for size in &config.sizes {
+            self.render_variant(&id, *size, &config)?;
+        }
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<ImageInfo> {
+        let index = self.lock();
+
+        index
+            .blobs
+            .iter()
+            .map(|(id, blob)| self.info_of(id, blob))
+            .collect()
+    }
+
+    pub fn info(&self, reference: &str) -> Result<ImageInfo, AppError> {
+        let index = self.lock();
+        let id = resolve(&index, reference)?;
+
+        Ok(self.info_of(&id, &index.blobs[&id]))
+    }
+
+    /// Delete images nothing has claimed since before `unclaimed_before`
+    /// (seconds since the epoch), blob files the index does not know, and
+    /// index entries whose file has gone. Returns the number of images and
+    /// files removed.
+    pub fn collect_garbage(&self, unclaimed_before: i64) -> Result<u32, AppError> {
+        let mut index = self.lock();
+        let mut removed = 0;
+
+        let unclaimed: Vec<ImageId> = index
+            .blobs
+            .iter()
+            .filter(|(id, blob)| {
+                (blob.owners.is_empty() && blob.created_at < unclaimed_before)
+                    || !self.blob_path(id).is_file()
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &unclaimed {
+            self.delete(&mut index, id)?;
+            removed += 1;
+        }
+
+        for path in self.blob_files()? {
+            let known = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(ImageId::parse)
+                .is_some_and(|id| index.blobs.contains_key(&id));
+            if !known {
+                fs::remove_file(&path).map_err(|e| {
+                    AppError::io(format!("Failed to remove unused image file: {}", e))
+                })?;
+                removed += 1;
+            }
+        }
+        removed += self.collect_variants(&index, unclaimed_before)?;
+
+        self.save_index(&index)?;
+        Ok(removed)
+    }
+
+    fn info_of(&self, id: &ImageId, blob: &Blob) -> ImageInfo {
+        ImageInfo {
+            id: id.to_string(),
+            path: self.blob_path(id).to_string_lossy().into_owned(),
+            size: blob.size.min(u32::MAX as u64) as u32,
+            owners: blob.owners.iter().cloned().collect(),
+            created_at: chrono::DateTime::from_timestamp(blob.created_at, 0)
+                .unwrap_or_default()
+                .to_rfc3339(),
+            metadata: blob.metadata.clone(),
+        }
+    }
+
+    fn path_of(&self, reference: &str) -> Result<PathBuf, AppError> {
+        let id = resolve(&self.lock(), reference)?;
+        let path = self.blob_path(&id);
+
+        if !path.is_file() {
+            return Err(not_found(reference));
+        }
+        Ok(path)
+    }
+
+    fn blob_path(&self, id: &ImageId) -> PathBuf {
+        self.dir
+            .join("blobs")
+            .join(&id.as_str()[..2])
+            .join(id.as_str())
+    }
+
+    fn variant_path(&self, id: &ImageId, size: u32, config: &VariantConfig) -> PathBuf {
+        self.dir
+            .join("variants")
+            .join(size.to_string())
+            .join(format!("{}.{}", id, config.format.extension()))
+    }
+
+    fn render_variant(
+        &self,
+        id: &ImageId,
+        size: u32,
+        config: &VariantConfig,
+    ) -> Result<PathBuf, AppError> {
+        let path = self.variant_path(id, size, config);
+        if path.is_file() {
+            return Ok(path);
+        }
+
+        let data = fs::read(self.blob_path(id))
+            .map_err(|e| AppError::io(format!("Failed to read image: {}", e)))?;
+        let variant = image_variants::render(&data, size, config.format)?;
+        write_atomically(&path, &variant)?;
+        Ok(path)
+    }
+
+    /// Remove variants of deleted images and of sizes or formats no longer
+    /// configured. Half-written files count once older than
+    /// `unclaimed_before`, as a variant may still be being written.
+    fn collect_variants(&self, index: &Index, unclaimed_before: i64) -> Result<u32, AppError> {
+        let root = self.dir.join("variants");
+        if !root.is_dir() {
+            return Ok(0);
+        }
+        let config = self.variant_config();
+        let read_dir = |dir: &Path| {
+            fs::read_dir(dir)
+                .map_err(|e| AppError::io(format!("Failed to read variants directory: {}", e)))
+        };
+
+        let mut removed = 0;
+        for size_dir in read_dir(&root)?.flatten() {
+            let size = size_dir.file_name().to_str().and_then(|s| s.parse().ok());
+            let configured = size.is_some_and(|size| config.sizes.contains(&size));
+
+            if !size_dir.file_type().is_ok_and(|t| t.is_dir()) {
+                let _ = fs::remove_file(size_dir.path());
+                removed += 1;
+                continue;
+            }
+            for entry in read_dir(&size_dir.path())?.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let current = name
+                    .strip_suffix(&format!(".{}", config.format.extension()))
+                    .and_then(ImageId::parse)
+                    .is_some_and(|id| configured && index.blobs.contains_key(&id));
+                let abandoned = !name.ends_with(".tmp")
+                    || entry
+                        .metadata()
+                        .and_then(|metadata| metadata.modified())
+                        .is_ok_and(|modified| {
+                            chrono::DateTime::<chrono::Utc>::from(modified).timestamp()
+                                < unclaimed_before
+                        });
+                if !current && abandoned {
+                    fs::remove_file(entry.path()).map_err(|e| {
+                        AppError::io(format!("Failed to remove unused image variant: {}", e))
+                    })?;
+                    removed += 1;
+                }
+            }
+            if !configured {
+                let _ = fs::remove_dir(size_dir.path());
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Files under `blobs/`, including any left half-written
+    fn blob_files(&self) -> Result<Vec<PathBuf>, AppError> {
+        let read_dir = |dir: &Path| {
+            fs::read_dir(dir)
+                .map_err(|e| AppError::io(format!("Failed to read images directory: {}", e)))
+        };
+
+        let mut files = Vec::new();
+        for shard in read_dir(&self.dir.join("blobs"))?.flatten() {
+            if !shard.file_type().is_ok_and(|t| t.is_dir()) {
+                files.push(shard.path());
+                continue;
+            }
+            for entry in read_dir(&shard.path())?.flatten() {
+                if entry.file_type().is_ok_and(|t| t.is_file()) {
+                    files.push(entry.path());
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    /// Remove an image's file, variants and index entry, along with names
+    /// pointing to it
+    fn delete(&self, index: &mut Index, id: &ImageId) -> Result<(), AppError> {
+        let config = self.variant_config();
+        for size in &config.sizes {
+            remove_if_exists(&self.variant_path(id, *size, &config))?;
+        }
+        remove_if_exists(&self.blob_path(id))?;
+        index.blobs.remove(id);
+        index.aliases.retain(|_, target| target != id);
+        Ok(())
+    }
+
+    /// Fill in metadata of images stored before it was recorded
+    fn read_missing_metadata(&self) -> Result<(), AppError> {
+        let mut index = self.lock();
+        let mut changed = false;
+
+        for (id, blob) in index.blobs.iter_mut() {
+            if blob.metadata.is_none() {
+                let data = fs::read(self.blob_path(id)).unwrap_or_default();
+                blob.metadata = image_metadata::read(&data).ok();
+                changed |= blob.metadata.is_some();
+            }
+        }
+
+        if changed {
+            self.save_index(&index)?;
+        }
+        Ok(())
+    }
+
+    /// Move files saved by name directly in the images directory into the store
+    fn migrate_named_files(&self) -> Result<(), AppError> {
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| AppError::io(format!("Failed to read images directory: {}", e)))?;
+        let named: Vec<(String, PathBuf)> = entries
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+            .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.path())))
+            .filter(|(name, _)| !RESERVED_FILES.contains(&name.as_str()) && !name.ends_with(".tmp"))
+            .filter(|(name, _)| ImageName::new(name).is_ok())
+            .collect();
+
+        for (name, path) in named {
+            let data = fs::read(&path)
+                .map_err(|e| AppError::io(format!("Failed to read image '{}': {}", name, e)))?;
+            let id = self.save(&data, Some(&format!("{}{}", LEGACY_OWNER_PREFIX, name)))?;
+
+            let mut index = self.lock();
+            index.aliases.insert(name.clone(), id);
+            self.save_index(&index)?;
+            drop(index);
+
+            fs::remove_file(&path).map_err(|e| {
+                AppError::io(format!("Failed to remove migrated image '{}': {}", name, e))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Index> {
+        self.index.lock().expect("Image index lock poisoned")
+    }
+
+    /// Apply `change` to the settings and save them
+    fn update_settings(&self, change: impl FnOnce(&mut Settings)) -> Result<(), AppError> {
+        let mut settings = self.settings.write().expect("Image settings lock poisoned");
+        let mut updated = settings.clone();
+        change(&mut updated);
+
+        persist::save_json(&self.dir.join("settings.json"), &updated, "image settings")?;
+        *settings = updated;
+        Ok(())
+    }
+
+    fn save_index(&self, index: &Index) -> Result<(), AppError> {
+        persist::save_json(&self.dir.join("index.json"), index, "image index")
+    }
+}
+
+/// ID of an image given its ID or, for images saved before content
+/// addressing, its old name
+fn resolve(index: &Index, reference: &str) -> Result<ImageId, AppError> {
+    let id = match ImageId::parse(reference) {
+        Some(id) => id,
+        None => {
+            let name = ImageName::new(reference)?;
+            let alias = index.aliases.get(name.as_str());
+            alias.cloned().ok_or_else(|| not_found(reference))?
+        }
+    };
+
+    if !index.blobs.contains_key(&id) {
+        return Err(not_found(reference));
+    }
+    Ok(id)
+}
+
+fn not_found(reference: &str) -> AppError {
+    AppError::not_found(format!("Image '{}' not found", reference))
+}
+
+fn check_owner(owner: &str) -> Result<(), AppError> {
+    if owner.trim().is_empty() || owner.len() > MAX_OWNER_LEN {
+        return Err(AppError::invalid_input(format!(
+            "Image owner must be between 1 and {} characters",
+            MAX_OWNER_LEN
+        )));
+    }
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), AppError> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(AppError::io(format!("Failed to remove image: {}", e))),
+    }
+}
+
+/// Write an image or variant atomically. Two requests may generate the same
+/// variant at once; each writes its own temporary file.
+fn write_atomically(path: &Path, data: &[u8]) -> Result<(), AppError> {
+    persist::write_atomically(path, data)
+        .map_err(|e| AppError::io(format!("Failed to write image: {}", e)))
+}
@@ -0,0 +1,122 @@
+use crate::error::AppError;
+use crate::image_metadata;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::io::Cursor;
+
+/// Bounds of a variant's longest side, in pixels
+const MIN_VARIANT_SIZE: u32 = 16;
+const MAX_VARIANT_SIZE: u32 = 4096;
+const MAX_VARIANT_SIZES: usize = 8;
+
+/// Encoding of generated variants. WebP is written losslessly, so both keep
+/// the transparency of cut-out clothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum VariantFormat {
+    #[default]
+    Webp,
+    Png,
+}
+
+impl VariantFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Webp => "webp",
+            Self::Png => "png",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        self.image_format().to_mime_type()
+    }
+
+    fn image_format(self) -> ImageFormat {
+        match self {
+            Self::Webp => ImageFormat::WebP,
+            Self::Png => ImageFormat::Png,
+        }
+    }
+}
+
+/// Sizes downscaled copies of each image are made at, saved with the image
+/// store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct VariantConfig {
+    pub sizes: Vec<u32>, // Longest side in pixels
+    pub format: VariantFormat,
+}
+
+impl Default for VariantConfig {
+    fn default() -> Self {
+        Self {
+            sizes: vec![128, 512, 1024],
+            format: VariantFormat::default(),
+        }
+    }
+}
+
+impl VariantConfig {
+    /// Check the sizes, returning the config with them sorted and deduplicated
+    pub fn normalize(mut self) -> Result<Self, AppError> {
+        self.sizes.sort_unstable();
+        self.sizes.dedup();
+
+        if self.sizes.is_empty() || self.sizes.len() > MAX_VARIANT_SIZES {
+            return Err(AppError::invalid_input(format!(
+                "Between 1 and {} variant sizes are required",
+                MAX_VARIANT_SIZES
+            )));
+        }
+        if let Some(size) = self
+            .sizes
+            .iter()
+            .find(|size| !(MIN_VARIANT_SIZE..=MAX_VARIANT_SIZE).contains(*size))
+        {
+            return Err(AppError::invalid_input(format!(
+                "Variant size {} is outside {}-{} pixels",
+                size, MIN_VARIANT_SIZE, MAX_VARIANT_SIZE
+            )));
+        }
+        Ok(self)
+    }
+
+    /// Smallest configured size at least `requested`, or the largest there
+    /// is, so callers never get a variant smaller than they asked for unless
+    /// none is that big
+    pub fn size_for(&self, requested: u32) -> u32 {
+        self.sizes
+            .iter()
+            .copied()
+            .find(|size| *size >= requested)
+            .or_else(|| self.sizes.last().copied())
+            .unwrap_or(requested)
+    }
+}
+
+/// Downscale an image so its longest side is at most `size`, keeping its
+/// aspect ratio; smaller images are only re-encoded. Images still carrying an
+/// orientation flag are turned upright, as variants have no EXIF.
+pub fn render(data: &[u8], size: u32, format: VariantFormat) -> Result<Vec<u8>, AppError> {
+    let image = image_metadata::decode_upright(data)?;
+
+    let resized = if image.width().max(image.height()) > size {
+        image.resize(size, size, FilterType::Triangle)
+    } else {
+        image
+    };
+    // The WebP encoder only takes 8-bit RGB(A)
+    let resized = if resized.color().has_alpha() {
+        DynamicImage::ImageRgba8(resized.into_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(resized.into_rgb8())
+    };
+
+    let mut encoded = Cursor::new(Vec::new());
+    resized
+        .write_to(&mut encoded, format.image_format())
+        .map_err(|e| AppError::internal(format!("Failed to encode image variant: {}", e)))?;
+    Ok(encoded.into_inner())
+}
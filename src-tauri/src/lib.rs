@@ -1,16 +1,46 @@
 mod background_removal;
-mod env;
-mod geolocation;
-mod image_service;
-mod weather;
-mod weather_cache;
+pub mod connectivity;
+pub mod env;
+pub mod error;
+pub mod geocoding;
+pub mod geohash;
+pub mod geolocation;
+pub mod http_client;
+pub mod image_metadata;
+pub mod image_protocol;
+pub mod image_service;
+pub mod image_store;
+pub mod image_variants;
+pub mod locations;
+mod persist;
+mod single_flight;
+pub mod weather;
+pub mod weather_alerts;
+pub mod weather_cache;
+pub mod weather_classifier;
+pub mod weather_daily;
+pub mod weather_history;
+pub mod weather_preferences;
+pub mod weather_provider;
 
-use geolocation::Coordinates;
+use connectivity::{Connectivity, ConnectivityChanged};
+use error::AppError;
+use geocoding::{Geocoder, Place};
+use geolocation::{Geolocation, GeolocationConfig, Geolocator};
+use locations::{ActiveLocationChanged, LocationInput, LocationQuery, Locations, SavedLocation};
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use tauri::Manager;
-use tauri_specta::{collect_commands, Builder};
-use weather::WeatherData;
+use tauri_specta::{collect_commands, collect_events, Builder};
+use weather::{ForecastPoint, WeatherData, WeatherResponse};
+use weather_alerts::{AlertConfig, WeatherAlert, WeatherAlerts};
+use weather_cache::{
+    WeatherCache, WeatherCacheConfig, WeatherCacheEntry, WeatherCacheKind, WeatherCacheStats,
+};
+use weather_classifier::{ClassifierThresholds, WeatherClassifier, WeatherType};
+use weather_daily::DailyForecast;
+use weather_preferences::{Preferences, WeatherPreferences};
+use weather_provider::WeatherProviderKind;
 
 // Example type-safe command
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -28,22 +58,262 @@ fn greet(name: String) -> GreetResponse {
 
 #[tauri::command]
 #[specta::specta]
-async fn fetch_weather(lat: f64, lon: f64) -> Result<WeatherData, String> {
-    let api_key = &env::EnvConfig::get().open_weather_api_key;
-    weather::fetch_weather(lat, lon, api_key).await
+async fn fetch_weather(location: LocationQuery) -> Result<WeatherResponse<WeatherData>, AppError> {
+    let (lat, lon) = Locations::global().resolve(&location)?;
+    weather::fetch_weather(lat, lon).await
 }
 
 #[tauri::command]
 #[specta::specta]
-async fn fetch_weather_for_date(lat: f64, lon: f64, date: String) -> Result<WeatherData, String> {
-    let api_key = &env::EnvConfig::get().open_weather_api_key;
-    weather::fetch_forecast_for_date(lat, lon, &date, api_key).await
+async fn fetch_weather_for_date(
+    location: LocationQuery,
+    date: String,
+) -> Result<WeatherResponse<WeatherData>, AppError> {
+    let (lat, lon) = Locations::global().resolve(&location)?;
+    weather::fetch_forecast_for_date(lat, lon, &date).await
 }
 
 #[tauri::command]
 #[specta::specta]
-async fn get_location() -> Result<Coordinates, String> {
-    geolocation::get_location_from_ip().await
+async fn fetch_forecast(
+    location: LocationQuery,
+) -> Result<WeatherResponse<Vec<ForecastPoint>>, AppError> {
+    let (lat, lon) = Locations::global().resolve(&location)?;
+    weather::fetch_forecast(lat, lon).await
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn fetch_daily_forecast(
+    location: LocationQuery,
+) -> Result<WeatherResponse<Vec<DailyForecast>>, AppError> {
+    let (lat, lon) = Locations::global().resolve(&location)?;
+    weather::fetch_daily_forecast(lat, lon).await
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_weather_provider() -> WeatherProviderKind {
+    weather::get_provider()
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_weather_provider(provider: WeatherProviderKind) -> Result<(), AppError> {
+    weather::set_provider(provider)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_weather_preferences() -> WeatherPreferences {
+    weather::get_preferences()
+}
+
+/// Change units or condition language. Returns the preferences as stored,
+/// with the language code normalized.
+#[tauri::command]
+#[specta::specta]
+fn set_weather_preferences(
+    preferences: WeatherPreferences,
+) -> Result<WeatherPreferences, AppError> {
+    weather::set_preferences(preferences)
+}
+
+/// Clothing weather tags for a reading, e.g. `["cool", "rainy"]`
+#[tauri::command]
+#[specta::specta]
+fn classify_weather(weather: WeatherData) -> Vec<WeatherType> {
+    WeatherClassifier::global().classify(&weather)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_classifier_thresholds() -> ClassifierThresholds {
+    WeatherClassifier::global().thresholds()
+}
+
+/// Change the classifier thresholds. Returns them as stored.
+#[tauri::command]
+#[specta::specta]
+fn set_classifier_thresholds(
+    thresholds: ClassifierThresholds,
+) -> Result<ClassifierThresholds, AppError> {
+    WeatherClassifier::global().set_thresholds(thresholds)
+}
+
+/// Cached weather responses with their age, newest first
+#[tauri::command]
+#[specta::specta]
+fn list_weather_cache() -> Vec<WeatherCacheEntry> {
+    WeatherCache::global().entries()
+}
+
+/// Drop cached weather of one kind, for one location, or both.
+/// Returns the number of entries removed.
+#[tauri::command]
+#[specta::specta]
+async fn invalidate_weather_cache(
+    kind: Option<WeatherCacheKind>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+) -> Result<u32, AppError> {
+    let location = match (lat, lon) {
+        (Some(lat), Some(lon)) => Some((lat, lon)),
+        (None, None) => None,
+        _ => {
+            return Err(AppError::invalid_input(
+                "Both lat and lon are required to invalidate a location",
+            ))
+        }
+    };
+
+    Ok(WeatherCache::global().invalidate(kind, location).await as u32)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn clear_weather_cache() {
+    WeatherCache::global().clear()
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_weather_cache_stats() -> WeatherCacheStats {
+    WeatherCache::global().stats().await
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_weather_cache_config() -> WeatherCacheConfig {
+    WeatherCache::global().config()
+}
+
+/// Change the cache settings. Returns them as stored.
+#[tauri::command]
+#[specta::specta]
+async fn set_weather_cache_config(
+    config: WeatherCacheConfig,
+) -> Result<WeatherCacheConfig, AppError> {
+    WeatherCache::global().set_config(config).await
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_alert_config() -> AlertConfig {
+    WeatherAlerts::global().config()
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_alert_config(config: AlertConfig) -> Result<(), AppError> {
+    WeatherAlerts::global().set_config(config)
+}
+
+/// Severe weather alerts in effect for the watched locations, or for one
+/// saved location
+#[tauri::command]
+#[specta::specta]
+fn get_active_alerts(location_id: Option<String>) -> Vec<WeatherAlert> {
+    WeatherAlerts::global().active(location_id.as_deref())
+}
+
+/// Check the watched locations now instead of waiting for the next poll
+#[tauri::command]
+#[specta::specta]
+async fn check_weather_alerts() -> Result<Vec<WeatherAlert>, AppError> {
+    let alerts = WeatherAlerts::global();
+    alerts.check().await?;
+    Ok(alerts.active(None))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn list_saved_locations() -> Vec<SavedLocation> {
+    Locations::global().list()
+}
+
+#[tauri::command]
+#[specta::specta]
+fn add_saved_location(location: LocationInput) -> Result<SavedLocation, AppError> {
+    Locations::global().add(location)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn update_saved_location(id: String, location: LocationInput) -> Result<SavedLocation, AppError> {
+    Locations::global().update(&id, location)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn remove_saved_location(id: String) -> Result<(), AppError> {
+    Locations::global().remove(&id)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_default_location() -> Option<SavedLocation> {
+    Locations::global().default_location()
+}
+
+/// Location made active on launch; `null` clears it
+#[tauri::command]
+#[specta::specta]
+fn set_default_location(id: Option<String>) -> Result<(), AppError> {
+    Locations::global().set_default(id.as_deref())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_active_location() -> Option<SavedLocation> {
+    Locations::global().active()
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_active_location(id: String) -> Result<SavedLocation, AppError> {
+    Locations::global().set_active(&id)
+}
+
+/// Whether weather and location services were reachable on the last request
+#[tauri::command]
+#[specta::specta]
+fn get_connectivity() -> Connectivity {
+    connectivity::current()
+}
+
+/// Places matching a name such as "Kraków" or "Portland, Oregon"
+#[tauri::command]
+#[specta::specta]
+async fn geocode(query: String) -> Result<Vec<Place>, AppError> {
+    Geocoder::global().geocode(&query).await
+}
+
+/// Place at or nearest to the coordinates
+#[tauri::command]
+#[specta::specta]
+async fn reverse_geocode(lat: f64, lon: f64) -> Result<Place, AppError> {
+    Geocoder::global().reverse(lat, lon).await
+}
+
+/// Current device location and the source that provided it
+#[tauri::command]
+#[specta::specta]
+async fn get_location() -> Result<Geolocation, AppError> {
+    Geolocator::global().locate().await
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_geolocation_config() -> GeolocationConfig {
+    Geolocator::global().config()
+}
+
+/// Change the location sources; turning off `network_lookup` stops IP lookups
+#[tauri::command]
+#[specta::specta]
+fn set_geolocation_config(config: GeolocationConfig) -> Result<(), AppError> {
+    Geolocator::global().set_config(config)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -51,18 +321,68 @@ pub fn run() {
     // Initialize environment configuration
     env::EnvConfig::init().expect("Failed to load environment variables");
 
-    let builder = Builder::<tauri::Wry>::new().commands(collect_commands![
-        greet,
-        fetch_weather,
-        fetch_weather_for_date,
-        get_location,
-        image_service::save_image,
-        image_service::get_image,
-        image_service::get_image_path,
-        image_service::remove_image,
-        image_service::list_images,
-        background_removal::remove_background
-    ]);
+    // Deprecated image commands stay registered until the next release
+    #[allow(deprecated)]
+    let builder = Builder::<tauri::Wry>::new()
+        .commands(collect_commands![
+            greet,
+            fetch_weather,
+            fetch_weather_for_date,
+            fetch_forecast,
+            fetch_daily_forecast,
+            get_weather_provider,
+            set_weather_provider,
+            get_weather_preferences,
+            set_weather_preferences,
+            classify_weather,
+            get_classifier_thresholds,
+            set_classifier_thresholds,
+            list_weather_cache,
+            invalidate_weather_cache,
+            clear_weather_cache,
+            get_weather_cache_stats,
+            get_weather_cache_config,
+            set_weather_cache_config,
+            get_alert_config,
+            set_alert_config,
+            get_active_alerts,
+            check_weather_alerts,
+            list_saved_locations,
+            add_saved_location,
+            update_saved_location,
+            remove_saved_location,
+            get_default_location,
+            set_default_location,
+            get_active_location,
+            set_active_location,
+            get_connectivity,
+            geocode,
+            reverse_geocode,
+            get_location,
+            get_geolocation_config,
+            set_geolocation_config,
+            image_service::get_image_path,
+            image_service::get_image_variant,
+            image_service::get_image_variant_config,
+            image_service::set_image_variant_config,
+            image_service::get_upload_limits,
+            image_service::set_upload_limits,
+            image_service::get_image_sanitize_options,
+            image_service::set_image_sanitize_options,
+            image_service::retain_image,
+            image_service::release_image,
+            image_service::list_images,
+            image_service::collect_image_garbage,
+            image_service::save_image,
+            image_service::get_image,
+            image_service::remove_image,
+            image_service::remove_background
+        ])
+        .events(collect_events![
+            ConnectivityChanged,
+            WeatherAlert,
+            ActiveLocationChanged
+        ]);
 
     #[cfg(debug_assertions)]
     builder
@@ -77,11 +397,118 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_http::init())
         .invoke_handler(builder.invoke_handler())
+        .register_asynchronous_uri_scheme_protocol(
+            image_protocol::SCHEME,
+            |_ctx, request, responder| {
+                tauri::async_runtime::spawn_blocking(move || {
+                    responder.respond(image_protocol::handle(request))
+                });
+            },
+        )
         .setup(move |app| {
             builder.mount_events(app);
+            connectivity::attach(app.handle().clone());
+            locations::attach(app.handle().clone());
+
+            // Load saved locations, geolocation settings, weather display
+            // preferences, the chosen weather provider, alert settings,
+            // classifier thresholds, recorded weather observations used for
+            // past-date lookups, the weather cache left by the previous run,
+            // and the image store
+            match app.path().app_data_dir() {
+                Ok(data_dir) => {
+                    if let Err(e) = Locations::init(data_dir.join("locations.json")) {
+                        eprintln!("Warning: Failed to load saved locations: {}", e);
+                    }
+                    if let Err(e) = Geolocator::init(data_dir.join("geolocation.json")) {
+                        eprintln!("Warning: Failed to load geolocation settings: {}", e);
+                    }
+                    if let Err(e) = Preferences::init(data_dir.join("weather_preferences.json")) {
+                        eprintln!("Warning: Failed to load weather preferences: {}", e);
+                    }
+                    if let Err(e) = weather_provider::init(data_dir.join("weather_provider.json")) {
+                        eprintln!("Warning: Failed to load weather provider: {}", e);
+                    }
+                    if let Err(e) = WeatherAlerts::init(data_dir.join("weather_alerts.json")) {
+                        eprintln!("Warning: Failed to load alert settings: {}", e);
+                    }
+                    if let Err(e) =
+                        WeatherClassifier::init(data_dir.join("weather_classifier.json"))
+                    {
+                        eprintln!("Warning: Failed to load classifier thresholds: {}", e);
+                    }
+                    if let Err(e) = weather_history::WeatherHistory::init(
+                        data_dir.join("weather_history.jsonl"),
+                    ) {
+                        eprintln!("Warning: Failed to load weather history: {}", e);
+                    }
+                    if let Err(e) = tauri::async_runtime::block_on(WeatherCache::init(
+                        data_dir.join("weather_cache.json"),
+                    )) {
+                        eprintln!("Warning: Failed to load weather cache: {}", e);
+                    }
+                    if let Err(e) = image_store::ImageStore::init(data_dir.join("images")) {
+                        eprintln!("Warning: Failed to open image store: {}", e);
+                    }
+                    if let Err(e) =
+                        image_service::Uploads::init(data_dir.join("upload_limits.json"))
+                    {
+                        eprintln!("Warning: Failed to load upload limits: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Warning: Could not resolve app data dir: {}", e),
+            }
+
+            // Cache settings from the environment, unless they were changed
+            // from the UI since
+            let config = env::EnvConfig::get();
+            let cache = WeatherCache::global();
+            let mut cache_config = cache.config();
+            cache_config.stale_while_revalidate = config.weather_stale_while_revalidate;
+            if let Some(precision) = config.weather_cache_grid_precision {
+                cache_config.grid_precision = precision;
+            }
+            if let Err(e) = tauri::async_runtime::block_on(cache.set_config(cache_config)) {
+                eprintln!("Warning: Invalid weather cache settings: {}", e);
+            }
+            if let Ok(data_dir) = app.path().app_data_dir() {
+                let path = data_dir.join("weather_cache_settings.json");
+                if let Err(e) = tauri::async_runtime::block_on(cache.load_config(path)) {
+                    eprintln!("Warning: Failed to load weather cache settings: {}", e);
+                }
+            }
+
+            // Poll watched locations for severe weather once the cache is ready
+            weather_alerts::attach(app.handle().clone());
+
+            // Clear out images left unclaimed by forms that were never saved
+            tauri::async_runtime::spawn_blocking(|| {
+                let cutoff = chrono::Utc::now().timestamp() - image_store::UNCLAIMED_GRACE_SECS;
+                if let Ok(store) = image_store::ImageStore::global() {
+                    if let Err(e) = store.collect_garbage(cutoff) {
+                        eprintln!("Warning: Failed to clean up unused images: {}", e);
+                    }
+                }
+            });
+
+            // Bundled major cities answer geocoding lookups while offline
+            match app
+                .path()
+                .resolve("cities.json", tauri::path::BaseDirectory::Resource)
+            {
+                Ok(cities_path) => {
+                    if let Err(e) = Geocoder::init(&cities_path) {
+                        eprintln!("Warning: Failed to load offline city list: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Warning: Could not resolve offline city list path: {}", e),
+            }
 
             // Initialize RMBG model (optional - will fail gracefully if model not found)
-            if let Ok(model_path) = app.path().resolve("model.onnx", tauri::path::BaseDirectory::Resource) {
+            if let Ok(model_path) = app
+                .path()
+                .resolve("model.onnx", tauri::path::BaseDirectory::Resource)
+            {
                 if model_path.exists() {
                     if let Err(e) = background_removal::init_rmbg(
                         model_path
@@ -99,6 +526,14 @@ pub fn run() {
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // Cache changes are saved with a delay; write out the last ones
+            if let tauri::RunEvent::Exit = event {
+                if let Err(e) = tauri::async_runtime::block_on(WeatherCache::global().flush()) {
+                    eprintln!("Warning: Failed to save weather cache: {}", e);
+                }
+            }
+        });
 }
@@ -0,0 +1,309 @@
+use crate::error::AppError;
+use crate::persist;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::AppHandle;
+use tauri_specta::Event;
+
+static LOCATIONS: OnceLock<Locations> = OnceLock::new();
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// Named place the user has saved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct SavedLocation {
+    pub id: String,
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Fields of a location to create or replace
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct LocationInput {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Where to fetch weather for: explicit coordinates, a saved location, or
+/// the active one
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LocationQuery {
+    Coordinates { lat: f64, lon: f64 },
+    Saved { id: String },
+    Active,
+}
+
+/// Emitted when the active location is switched, edited or removed
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct ActiveLocationChanged {
+    pub location: Option<SavedLocation>, // Unset when no location is active
+}
+
+/// On-disk layout of the registry
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Registry {
+    locations: Vec<SavedLocation>,
+    default_id: Option<String>,
+}
+
+struct State {
+    registry: Registry,
+    active_id: Option<String>, // Starts at the default on each launch
+}
+
+/// Saved locations, persisted as JSON in the app data dir
+pub struct Locations {
+    path: Option<PathBuf>,
+    state: Mutex<State>,
+}
+
+impl Locations {
+    /// Load the registry file at `path`, starting empty if it does not exist yet.
+    /// Without a path the registry is kept in memory only.
+    pub fn open(path: Option<PathBuf>) -> Result<Self, AppError> {
+        let registry = match &path {
+            Some(path) => persist::load_json::<Registry>(path, "locations")?,
+            None => None,
+        }
+        .unwrap_or_default();
+        let active_id = registry.default_id.clone();
+
+        Ok(Self {
+            path,
+            state: Mutex::new(State {
+                registry,
+                active_id,
+            }),
+        })
+    }
+
+    pub fn init(path: PathBuf) -> Result<(), AppError> {
+        let locations = Self::open(Some(path))?;
+        LOCATIONS
+            .set(locations)
+            .map_err(|_| AppError::internal("Locations already initialized"))
+    }
+
+    pub fn global() -> &'static Locations {
+        LOCATIONS.get_or_init(|| Self::open(None).expect("In-memory locations cannot fail to open"))
+    }
+
+    pub fn list(&self) -> Vec<SavedLocation> {
+        self.lock().registry.locations.clone()
+    }
+
+    pub fn get(&self, id: &str) -> Result<SavedLocation, AppError> {
+        find(&self.lock().registry, id).cloned()
+    }
+
+    pub fn add(&self, input: LocationInput) -> Result<SavedLocation, AppError> {
+        let mut state = self.lock();
+        validate(&state.registry, &input, None)?;
+
+        let location = SavedLocation {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: input.name.trim().to_string(),
+            lat: input.lat,
+            lon: input.lon,
+        };
+        let mut registry = state.registry.clone();
+        registry.locations.push(location.clone());
+
+        // The first saved location becomes the default
+        if registry.default_id.is_none() {
+            registry.default_id = Some(location.id.clone());
+        }
+
+        self.save(&registry)?;
+        state.registry = registry;
+        let became_active = state.active_id.is_none();
+        if became_active {
+            state.active_id = Some(location.id.clone());
+        }
+        drop(state);
+        if became_active {
+            emit(Some(location.clone()));
+        }
+
+        Ok(location)
+    }
+
+    pub fn update(&self, id: &str, input: LocationInput) -> Result<SavedLocation, AppError> {
+        let mut state = self.lock();
+        find(&state.registry, id)?;
+        validate(&state.registry, &input, Some(id))?;
+
+        let mut registry = state.registry.clone();
+        let location = registry
+            .locations
+            .iter_mut()
+            .find(|location| location.id == id)
+            .expect("Location checked above");
+        location.name = input.name.trim().to_string();
+        location.lat = input.lat;
+        location.lon = input.lon;
+        let location = location.clone();
+
+        self.save(&registry)?;
+        state.registry = registry;
+        let is_active = state.active_id.as_deref() == Some(id);
+        drop(state);
+        if is_active {
+            emit(Some(location.clone()));
+        }
+
+        Ok(location)
+    }
+
+    /// Delete a location. A removed default is cleared, and a removed active
+    /// location falls back to the default.
+    pub fn remove(&self, id: &str) -> Result<(), AppError> {
+        let mut state = self.lock();
+        find(&state.registry, id)?;
+
+        let mut registry = state.registry.clone();
+        registry.locations.retain(|location| location.id != id);
+        if registry.default_id.as_deref() == Some(id) {
+            registry.default_id = None;
+        }
+
+        self.save(&registry)?;
+        state.registry = registry;
+        let was_active = state.active_id.as_deref() == Some(id);
+        if was_active {
+            state.active_id = state.registry.default_id.clone();
+        }
+        let active = active(&state);
+        drop(state);
+        if was_active {
+            emit(active);
+        }
+
+        Ok(())
+    }
+
+    pub fn default_location(&self) -> Option<SavedLocation> {
+        let state = self.lock();
+        let id = state.registry.default_id.as_deref()?;
+        find(&state.registry, id).ok().cloned()
+    }
+
+    /// Location used on launch; `None` clears it
+    pub fn set_default(&self, id: Option<&str>) -> Result<(), AppError> {
+        let mut state = self.lock();
+        if let Some(id) = id {
+            find(&state.registry, id)?;
+        }
+
+        let mut registry = state.registry.clone();
+        registry.default_id = id.map(str::to_string);
+        self.save(&registry)?;
+        state.registry = registry;
+        Ok(())
+    }
+
+    pub fn active(&self) -> Option<SavedLocation> {
+        active(&self.lock())
+    }
+
+    pub fn set_active(&self, id: &str) -> Result<SavedLocation, AppError> {
+        let mut state = self.lock();
+        let location = find(&state.registry, id)?.clone();
+
+        let changed = state.active_id.as_deref() != Some(id);
+        state.active_id = Some(id.to_string());
+        drop(state);
+        if changed {
+            emit(Some(location.clone()));
+        }
+
+        Ok(location)
+    }
+
+    /// Coordinates a weather query refers to
+    pub fn resolve(&self, query: &LocationQuery) -> Result<(f64, f64), AppError> {
+        match query {
+            LocationQuery::Coordinates { lat, lon } => {
+                check_coordinates(*lat, *lon)?;
+                Ok((*lat, *lon))
+            }
+            LocationQuery::Saved { id } => {
+                self.get(id).map(|location| (location.lat, location.lon))
+            }
+            LocationQuery::Active => self
+                .active()
+                .map(|location| (location.lat, location.lon))
+                .ok_or_else(|| AppError::not_found("No active location")),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Locations lock poisoned")
+    }
+
+    fn save(&self, registry: &Registry) -> Result<(), AppError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        persist::save_json(path, registry, "locations")
+    }
+}
+
+/// Route active location events to the running app
+pub fn attach(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+fn emit(location: Option<SavedLocation>) {
+    if let Some(app) = APP_HANDLE.get() {
+        if let Err(e) = (ActiveLocationChanged { location }).emit(app) {
+            eprintln!("Warning: Failed to emit active location change: {}", e);
+        }
+    }
+}
+
+fn active(state: &State) -> Option<SavedLocation> {
+    let id = state.active_id.as_deref()?;
+    find(&state.registry, id).ok().cloned()
+}
+
+fn find<'a>(registry: &'a Registry, id: &str) -> Result<&'a SavedLocation, AppError> {
+    registry
+        .locations
+        .iter()
+        .find(|location| location.id == id)
+        .ok_or_else(|| AppError::not_found(format!("Location '{}' not found", id)))
+}
+
+/// Names must be present and unique, ignoring case; `id` is the location being edited
+fn validate(registry: &Registry, input: &LocationInput, id: Option<&str>) -> Result<(), AppError> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid_input("Location name must not be empty"));
+    }
+    if registry.locations.iter().any(|location| {
+        Some(location.id.as_str()) != id && location.name.eq_ignore_ascii_case(name)
+    }) {
+        return Err(AppError::invalid_input(format!(
+            "A location named '{}' already exists",
+            name
+        )));
+    }
+
+    check_coordinates(input.lat, input.lon)
+}
+
+pub(crate) fn check_coordinates(lat: f64, lon: f64) -> Result<(), AppError> {
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(AppError::invalid_input(format!(
+            "Invalid coordinates: {}, {}",
+            lat, lon
+        )));
+    }
+    Ok(())
+}
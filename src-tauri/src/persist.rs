@@ -0,0 +1,58 @@
+use crate::error::AppError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEMP_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// Write through a temporary file next to `path` and rename it into place,
+/// so a crash never leaves a partial file under the final name. Each write
+/// gets its own temporary file, as two threads may write the same path at
+/// once.
+pub fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temp = path.with_extension(format!(
+        "{}-{}.tmp",
+        std::process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = fs::File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Read the JSON file at `path`, or `None` if there is none yet. `what`
+/// names the contents in errors, e.g. "weather preferences".
+pub fn load_json<T: DeserializeOwned>(path: &Path, what: &str) -> Result<Option<T>, AppError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let body = fs::read_to_string(path)
+        .map_err(|e| AppError::io(format!("Failed to read {}: {}", what, e)))?;
+    serde_json::from_str(&body)
+        .map(Some)
+        .map_err(|e| AppError::io(format!("Failed to parse {}: {}", what, e)))
+}
+
+/// Write `value` as JSON to `path`, replacing the file atomically
+pub fn save_json<T: Serialize>(path: &Path, value: &T, what: &str) -> Result<(), AppError> {
+    let body = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::internal(format!("Failed to serialize {}: {}", what, e)))?;
+    write_atomically(path, body.as_bytes())
+        .map_err(|e| AppError::io(format!("Failed to write {}: {}", what, e)))
+}
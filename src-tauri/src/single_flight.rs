@@ -0,0 +1,53 @@
+use crate::error::AppError;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+type Flight<T> = Arc<OnceCell<Result<T, AppError>>>;
+
+/// Deduplicates concurrent work by key: callers arriving while a request for
+/// the same key is in flight wait for it and share its result, success or error.
+pub struct SingleFlight<T> {
+    in_flight: Mutex<HashMap<String, Flight<T>>>,
+}
+
+impl<T: Clone> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> SingleFlight<T> {
+    /// Run `work` unless a call for `key` is already running, in which case
+    /// wait for that call instead. If the running caller is cancelled, a
+    /// waiting caller takes over with its own `work`.
+    pub async fn run<F, Fut>(&self, key: String, work: F) -> Result<T, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let flight = Arc::clone(
+            self.in_flight
+                .lock()
+                .expect("Single-flight lock poisoned")
+                .entry(key.clone())
+                .or_default(),
+        );
+
+        let result = flight.get_or_init(work).await.clone();
+
+        // The first caller to finish retires the flight so later calls start afresh
+        let mut in_flight = self.in_flight.lock().expect("Single-flight lock poisoned");
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &flight))
+        {
+            in_flight.remove(&key);
+        }
+
+        result
+    }
+}
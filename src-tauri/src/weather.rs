@@ -1,202 +1,579 @@
-use crate::weather_cache::WeatherCache;
+use crate::error::AppError;
+use crate::geocoding;
+use crate::single_flight::SingleFlight;
+use crate::weather_cache::{self, Cached, WeatherCache};
+use crate::weather_daily::{self, DailyForecast};
+use crate::weather_history::WeatherHistory;
+use crate::weather_preferences::{Preferences, UnitSystem, WeatherPreferences};
+use crate::weather_provider::{self, Forecast, WeatherProviderKind};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct WeatherData {
-    pub temperature: f64,
-    pub feels_like: f64,
+    pub temperature: f64, // In `units`
+    pub feels_like: f64,  // In `units`
     pub humidity: u32,
     pub description: String,
     pub icon: String,
     pub location: String,
-    pub date: String, // ISO 8601 date string for the forecast
+    pub date: String,    // RFC 3339 in the location's timezone
+    pub wind_speed: f64, // In `units`: m/s, or mph for imperial
+    pub wind_deg: u32,
+    pub wind_gust: Option<f64>,  // In `units`
+    pub pressure: u32,           // hPa
+    pub visibility: Option<u32>, // metres
+    pub clouds: u32,             // Cloud cover, %
+    pub rain: f64,               // mm/h over the last hour, or averaged over a forecast slot
+    pub snow: f64,               // mm/h over the last hour, or averaged over a forecast slot
+    pub sunrise: Option<String>, // RFC 3339
+    pub sunset: Option<String>,  // RFC 3339
+    pub timezone_offset: i32,    // Seconds east of UTC at the location
+    #[serde(default)]
+    pub timezone: Option<String>, // IANA name, when known
+    pub uv_index: Option<f64>,
+    #[serde(default)]
+    pub units: UnitSystem, // Providers report metric; converted on the way out
 }
 
-#[derive(Debug, Deserialize)]
-struct OpenWeatherResponse {
-    main: Main,
-    weather: Vec<Weather>,
-    name: String,
+impl WeatherData {
+    /// The same reading with temperatures and wind speeds in `units`
+    pub fn in_units(mut self, units: UnitSystem) -> Self {
+        let from = self.units;
+        self.temperature = units.temperature(self.temperature, from);
+        self.feels_like = units.temperature(self.feels_like, from);
+        self.wind_speed = units.speed(self.wind_speed, from);
+        self.wind_gust = self.wind_gust.map(|gust| units.speed(gust, from));
+        self.units = units;
+        self
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct Main {
-    temp: f64,
-    feels_like: f64,
-    humidity: u32,
+/// One step of a multi-day forecast
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ForecastPoint {
+    pub date: String,     // RFC 3339 start of the slot
+    pub temperature: f64, // In `units`
+    pub feels_like: f64,  // In `units`
+    pub humidity: u32,
+    pub wind_speed: f64, // In `units`: m/s, or mph for imperial
+    pub wind_deg: u32,
+    pub wind_gust: Option<f64>,  // In `units`
+    pub pressure: u32,           // hPa
+    pub clouds: u32,             // Cloud cover, %
+    pub visibility: Option<u32>, // metres
+    pub pop: f64,                // Probability of precipitation, 0-1
+    pub rain: f64,               // mm/h, averaged over the slot
+    pub snow: f64,               // mm/h, averaged over the slot
+    pub description: String,
+    pub icon: String,
+    #[serde(default)]
+    pub units: UnitSystem,
 }
 
-#[derive(Debug, Deserialize)]
-struct Weather {
-    description: String,
-    icon: String,
+impl ForecastPoint {
+    /// The same slot with temperatures and wind speeds in `units`
+    pub fn in_units(mut self, units: UnitSystem) -> Self {
+        let from = self.units;
+        self.temperature = units.temperature(self.temperature, from);
+        self.feels_like = units.temperature(self.feels_like, from);
+        self.wind_speed = units.speed(self.wind_speed, from);
+        self.wind_gust = self.wind_gust.map(|gust| units.speed(gust, from));
+        self.units = units;
+        self
+    }
 }
 
-pub async fn fetch_weather(lat: f64, lon: f64, api_key: &str) -> Result<WeatherData, String> {
-    let cache = WeatherCache::global();
+/// Weather payload with the time it was fetched from the provider.
+/// `stale` is set once the data has outlived its cache TTL, e.g. while offline.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct WeatherResponse<T> {
+    pub data: T,
+    pub stale: bool,
+    pub fetched_at: String, // RFC 3339
+}
 
-    // Check cache first
-    if let Some(cached) = cache.get_current(lat, lon).await {
-        return Ok(cached);
+impl<T> WeatherResponse<T> {
+    /// Data just received from the provider
+    fn fresh(data: T) -> Self {
+        Self {
+            data,
+            stale: false,
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+        }
     }
 
-    // Cache miss, fetch from API
-    let url = format!(
-        "https://api.openweathermap.org/data/2.5/weather?lat={}&lon={}&appid={}&units=metric",
-        lat, lon, api_key
-    );
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> WeatherResponse<U> {
+        WeatherResponse {
+            data: f(self.data),
+            stale: self.stale,
+            fetched_at: self.fetched_at,
+        }
+    }
+}
 
-    let client = tauri_plugin_http::reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch weather: {}", e))?;
+impl<T> From<Cached<T>> for WeatherResponse<T> {
+    fn from(cached: Cached<T>) -> Self {
+        Self {
+            data: cached.value,
+            stale: cached.stale,
+            fetched_at: chrono::DateTime::from_timestamp(cached.fetched_at, 0)
+                .unwrap_or_default()
+                .to_rfc3339(),
+        }
+    }
+}
 
-    if !response.status().is_success() {
-        return Err(format!("Weather API error: {}", response.status()));
+pub fn get_provider() -> WeatherProviderKind {
+    weather_provider::active().kind()
+}
+
+/// Switch the active provider and drop data cached from the previous one
+pub fn set_provider(kind: WeatherProviderKind) -> Result<(), AppError> {
+    weather_provider::set_active(kind)?;
+    WeatherCache::global().clear();
+    Ok(())
+}
+
+pub fn get_preferences() -> WeatherPreferences {
+    Preferences::global().get()
+}
+
+/// Store new display preferences. Cached data is converted to other units
+/// as it is read, but condition text comes from the provider in the chosen
+/// language, so a language change rebuilds the provider and drops the cache.
+pub fn set_preferences(preferences: WeatherPreferences) -> Result<WeatherPreferences, AppError> {
+    let store = Preferences::global();
+    let language = store.language();
+    let preferences = store.set(preferences)?;
+
+    if preferences.language != language {
+        set_provider(get_provider())?;
     }
+    Ok(preferences)
+}
 
-    let body = response
-        .text()
+pub async fn fetch_weather(lat: f64, lon: f64) -> Result<WeatherResponse<WeatherData>, AppError> {
+    let cache = WeatherCache::global();
+
+    // Check cache first; expired entries are served while a refresh runs
+    let last_known = match cache.get_current(lat, lon).await {
+        Some(cached) if !cached.stale => {
+            return Ok(WeatherResponse::from(cached).map(|data| present_weather(lat, lon, data)));
+        }
+        Some(cached) if cache.stale_while_revalidate() => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = refresh_weather(lat, lon).await {
+                    eprintln!("Warning: Background weather refresh failed: {}", e);
+                }
+            });
+            return Ok(WeatherResponse::from(cached).map(|data| present_weather(lat, lon, data)));
+        }
+        other => other,
+    };
+
+    // Fall back to the last known value when the provider cannot be reached
+    let response = match refresh_weather(lat, lon).await {
+        Ok(weather_data) => WeatherResponse::fresh(weather_data),
+        Err(e) => last_known.map(Into::into).ok_or(e)?,
+    };
+
+    Ok(response.map(|data| present_weather(lat, lon, data)))
+}
+
+/// Fetch current weather from the active provider and update the cache.
+/// Concurrent calls for the same grid cell share one request.
+async fn refresh_weather(lat: f64, lon: f64) -> Result<WeatherData, AppError> {
+    static FLIGHTS: OnceLock<SingleFlight<WeatherData>> = OnceLock::new();
+    let key = WeatherCache::global().cell(lat, lon);
+
+    FLIGHTS
+        .get_or_init(SingleFlight::default)
+        .run(key, || async move {
+            let weather_data = weather_provider::active().fetch_current(lat, lon).await?;
+
+            // Store in cache, and keep the observation for later historical lookups
+            WeatherCache::global()
+                .set_current(lat, lon, weather_data.clone())
+                .await;
+            record_history(
+                lat,
+                lon,
+                chrono::Utc::now().timestamp(),
+                weather_data.clone(),
+            )
+            .await;
+
+            Ok(weather_data)
+        })
         .await
-        .map_err(|e| format!("Failed to read response body: {}", e))?;
-
-    let data: OpenWeatherResponse =
-        serde_json::from_str(&body).map_err(|e| format!("Failed to parse weather data: {}", e))?;
-
-    let weather_data = WeatherData {
-        temperature: data.main.temp,
-        feels_like: data.main.feels_like,
-        humidity: data.main.humidity,
-        description: data
-            .weather
-            .first()
-            .map(|w| w.description.clone())
-            .unwrap_or_else(|| "Unknown".to_string()),
-        icon: data
-            .weather
-            .first()
-            .map(|w| w.icon.clone())
-            .unwrap_or_else(|| "01d".to_string()),
-        location: data.name,
-        date: chrono::Utc::now().to_rfc3339(),
+}
+
+/// Full forecast series for a location, shared by every forecast command
+pub(crate) async fn fetch_forecast_series(
+    lat: f64,
+    lon: f64,
+) -> Result<WeatherResponse<Forecast>, AppError> {
+    let cache = WeatherCache::global();
+
+    let last_known = match cache.get_series(lat, lon).await {
+        Some(cached) if !cached.stale => return Ok(cached.into()),
+        Some(cached) if cache.stale_while_revalidate() => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = refresh_forecast_series(lat, lon).await {
+                    eprintln!("Warning: Background forecast refresh failed: {}", e);
+                }
+            });
+            return Ok(cached.into());
+        }
+        other => other,
     };
 
-    // Store in cache
-    cache.set_current(lat, lon, weather_data.clone()).await;
+    match refresh_forecast_series(lat, lon).await {
+        Ok(forecast) => Ok(WeatherResponse::fresh(forecast)),
+        Err(e) => last_known.map(Into::into).ok_or(e),
+    }
+}
 
-    Ok(weather_data)
+/// Concurrent calls for the same grid cell share one request
+async fn refresh_forecast_series(lat: f64, lon: f64) -> Result<Forecast, AppError> {
+    static FLIGHTS: OnceLock<SingleFlight<Forecast>> = OnceLock::new();
+    let key = WeatherCache::global().cell(lat, lon);
+
+    FLIGHTS
+        .get_or_init(SingleFlight::default)
+        .run(key, || async move {
+            let forecast = weather_provider::active().fetch_forecast(lat, lon).await?;
+            WeatherCache::global()
+                .set_series(lat, lon, forecast.clone())
+                .await;
+
+            Ok(forecast)
+        })
+        .await
 }
 
-#[derive(Debug, Deserialize)]
-struct ForecastResponse {
-    list: Vec<ForecastItem>,
-    city: City,
+pub async fn fetch_forecast(
+    lat: f64,
+    lon: f64,
+) -> Result<WeatherResponse<Vec<ForecastPoint>>, AppError> {
+    let forecast = fetch_forecast_series(lat, lon).await?;
+    let units = Preferences::global().units();
+
+    Ok(forecast.map(|forecast| {
+        let zone = LocationZone::of_forecast(lat, lon, &forecast);
+        forecast
+            .slots
+            .into_iter()
+            .map(|slot| {
+                ForecastPoint {
+                    date: zone.format(slot.timestamp),
+                    ..slot.point
+                }
+                .in_units(units)
+            })
+            .collect()
+    }))
 }
 
-#[derive(Debug, Deserialize)]
-struct ForecastItem {
-    dt: i64,
-    main: Main,
-    weather: Vec<Weather>,
-    dt_txt: String,
+/// Past weather, from the local observation record when available and
+/// otherwise from the historical provider
+async fn fetch_historical(lat: f64, lon: f64, timestamp: i64) -> Result<WeatherData, AppError> {
+    let history = WeatherHistory::global();
+
+    if let Some(observed) = history.find(lat, lon, timestamp) {
+        return Ok(observed);
+    }
+
+    static FLIGHTS: OnceLock<SingleFlight<WeatherData>> = OnceLock::new();
+    let key = format!("{}_{}", WeatherCache::global().cell(lat, lon), timestamp);
+
+    FLIGHTS
+        .get_or_init(SingleFlight::default)
+        .run(key, || async move {
+            let weather_data = weather_provider::historical()
+                .fetch_historical(lat, lon, timestamp)
+                .await?;
+            record_history(lat, lon, timestamp, weather_data.clone()).await;
+
+            Ok(weather_data)
+        })
+        .await
+}
+
+/// Keep an observation for later historical lookups, writing it off the
+/// async runtime's threads
+async fn record_history(lat: f64, lon: f64, timestamp: i64, data: WeatherData) {
+    let recorded = tauri::async_runtime::spawn_blocking(move || {
+        WeatherHistory::global().record(lat, lon, timestamp, data)
+    })
+    .await;
+    if let Err(e) = recorded {
+        eprintln!("Warning: Failed to record weather history: {}", e);
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct City {
-    name: String,
+/// Forecast summarized per local day at the location
+pub async fn fetch_daily_forecast(
+    lat: f64,
+    lon: f64,
+) -> Result<WeatherResponse<Vec<DailyForecast>>, AppError> {
+    let forecast = fetch_forecast_series(lat, lon).await?;
+    let units = Preferences::global().units();
+
+    Ok(forecast.map(|forecast| {
+        let zone = LocationZone::of_forecast(lat, lon, &forecast);
+        weather_daily::aggregate(&forecast, &zone)
+            .into_iter()
+            .map(|day| day.in_units(units))
+            .collect()
+    }))
 }
 
+/// Weather at a point in time. Dates before the start of today at the
+/// location are historical; later ones come from the forecast.
 pub async fn fetch_forecast_for_date(
     lat: f64,
     lon: f64,
     date: &str,
-    api_key: &str,
-) -> Result<WeatherData, String> {
+) -> Result<WeatherResponse<WeatherData>, AppError> {
     let cache = WeatherCache::global();
 
-    // Check cache first
-    if let Some(cached) = cache.get_forecast(lat, lon, date).await {
-        return Ok(cached);
+    // Parse the target date; times within the same hour share a cache entry
+    let target_date = chrono::DateTime::parse_from_rfc3339(date)
+        .map_err(|e| AppError::invalid_input(format!("Invalid date format: {}", e)))?;
+    let target_timestamp = weather_cache::start_of_hour(target_date.timestamp());
+
+    // Check cache first; stale hours are rebuilt from the forecast series,
+    // and served when that fails
+    let last_known = match cache.get_forecast(lat, lon, target_timestamp).await {
+        Some(cached) if !cached.stale => {
+            return Ok(WeatherResponse::from(cached).map(|data| present_weather(lat, lon, data)));
+        }
+        other => other,
+    };
+
+    // Start of today at the location (allows selecting today)
+    let now = chrono::Utc::now().timestamp();
+    let zone = location_zone(lat, lon).await;
+    let start_of_today = zone.start_of_day(zone.date(now));
+
+    // Past dates come from recorded observations or the historical provider
+    if target_timestamp < start_of_today {
+        let response = match fetch_historical(lat, lon, target_timestamp).await {
+            Ok(weather_data) => {
+                cache
+                    .set_forecast(lat, lon, target_timestamp, weather_data.clone())
+                    .await;
+                WeatherResponse::fresh(weather_data)
+            }
+            Err(e) => last_known.map(Into::into).ok_or(e)?,
+        };
+        return Ok(response.map(|data| present_weather(lat, lon, data)));
     }
 
-    // Cache miss, fetch from API
-    // Parse the target date
-    let target_date = chrono::DateTime::parse_from_rfc3339(date)
-        .map_err(|e| format!("Invalid date format: {}", e))?;
+    // Check if the date is within the 5-day forecast range, which takes in
+    // the whole local day it ends on
+    let last_day = zone.date(now + 5 * 24 * 60 * 60);
+    if target_timestamp >= zone.start_of_day(last_day + chrono::Days::new(1)) {
+        return Err(AppError::invalid_input(
+            "Date must be within the next 5 days",
+        ));
+    }
 
-    let target_timestamp = target_date.timestamp();
-    let now = chrono::Utc::now();
+    let series = match fetch_forecast_series(lat, lon).await {
+        Ok(series) => series,
+        Err(e) => {
+            let response = last_known.map(WeatherResponse::from).ok_or(e)?;
+            return Ok(response.map(|data| present_weather(lat, lon, data)));
+        }
+    };
+    let WeatherResponse {
+        data: forecast,
+        stale,
+        fetched_at,
+    } = series;
+
+    // Find the forecast closest to the target date
+    let weather_data = forecast
+        .slots
+        .into_iter()
+        .min_by_key(|slot| (slot.timestamp - target_timestamp).abs())
+        .ok_or_else(|| AppError::not_found("No forecast data available"))?
+        .into_weather_data(
+            forecast.location,
+            forecast.timezone_offset,
+            forecast.timezone,
+        );
+
+    // Hours between slots are not filled by the series download, so store
+    // them too, unless they came from an outdated series
+    if !stale {
+        cache
+            .set_forecast(lat, lon, target_timestamp, weather_data.clone())
+            .await;
+    }
 
-    // Start of today (allows selecting today)
-    let start_of_today = now
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .ok_or("Failed to create start of day")?
-        .and_utc()
-        .timestamp();
+    Ok(WeatherResponse {
+        data: present_weather(lat, lon, weather_data),
+        stale,
+        fetched_at,
+    })
+}
 
-    // Check if the date is within the 5-day forecast range (from start of today)
-    let five_days = 5 * 24 * 60 * 60;
-    if target_timestamp < start_of_today || target_timestamp > now.timestamp() + five_days {
-        return Err("Date must be today or within the next 5 days".to_string());
+/// Timezone that dates at a location are shown in. A named IANA zone
+/// follows daylight saving changes; a fixed offset is all some providers
+/// report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LocationZone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl LocationZone {
+    /// Zone from provider data: its IANA name when given, else the zone of
+    /// the nearest bundled city if that agrees with the provider's UTC
+    /// offset at `at`, else the offset itself
+    pub fn resolve(lat: f64, lon: f64, name: Option<&str>, offset_secs: i32, at: i64) -> Self {
+        if let Some(tz) = name.and_then(|name| name.parse::<Tz>().ok()) {
+            return Self::Named(tz);
+        }
+
+        let nearby = geocoding::timezone_near(lat, lon).and_then(|name| name.parse::<Tz>().ok());
+        match nearby.map(Self::Named) {
+            Some(zone) if zone.offset_secs(at) == offset_secs => zone,
+            _ => Self::fixed(offset_secs),
+        }
     }
 
-    let url = format!(
-        "https://api.openweathermap.org/data/2.5/forecast?lat={}&lon={}&appid={}&units=metric",
-        lat, lon, api_key
-    );
+    /// Zone of a forecast series, whose offset is the one in effect when it
+    /// was fetched
+    pub fn of_forecast(lat: f64, lon: f64, forecast: &Forecast) -> Self {
+        Self::resolve(
+            lat,
+            lon,
+            forecast.timezone.as_deref(),
+            forecast.timezone_offset,
+            chrono::Utc::now().timestamp(),
+        )
+    }
 
-    let client = tauri_plugin_http::reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch forecast: {}", e))?;
+    pub fn fixed(offset_secs: i32) -> Self {
+        Self::Fixed(
+            FixedOffset::east_opt(offset_secs)
+                .unwrap_or_else(|| FixedOffset::east_opt(0).expect("UTC offset is valid")),
+        )
+    }
 
-    if !response.status().is_success() {
-        return Err(format!("Forecast API error: {}", response.status()));
+    /// IANA name, when the zone has one
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Self::Named(tz) => Some(tz.name()),
+            Self::Fixed(_) => None,
+        }
     }
 
-    let body = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response body: {}", e))?;
+    /// Local time at `timestamp`
+    pub fn at(&self, timestamp: i64) -> DateTime<FixedOffset> {
+        let utc = DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
+        match self {
+            Self::Named(tz) => utc.with_timezone(tz).fixed_offset(),
+            Self::Fixed(offset) => utc.with_timezone(offset),
+        }
+    }
+
+    /// RFC 3339 with the local offset
+    pub fn format(&self, timestamp: i64) -> String {
+        self.at(timestamp).to_rfc3339()
+    }
 
-    let data: ForecastResponse =
-        serde_json::from_str(&body).map_err(|e| format!("Failed to parse forecast data: {}", e))?;
+    pub fn offset_secs(&self, timestamp: i64) -> i32 {
+        self.at(timestamp).offset().local_minus_utc()
+    }
 
-    // Find the forecast closest to the target date
-    let closest_forecast = data
-        .list
-        .iter()
-        .min_by_key(|item| (item.dt - target_timestamp).abs())
-        .ok_or("No forecast data available")?;
-
-    let weather_data = WeatherData {
-        temperature: closest_forecast.main.temp,
-        feels_like: closest_forecast.main.feels_like,
-        humidity: closest_forecast.main.humidity,
-        description: closest_forecast
-            .weather
-            .first()
-            .map(|w| w.description.clone())
-            .unwrap_or_else(|| "Unknown".to_string()),
-        icon: closest_forecast
-            .weather
-            .first()
-            .map(|w| w.icon.clone())
-            .unwrap_or_else(|| "01d".to_string()),
-        location: data.city.name,
-        date: closest_forecast.dt_txt.clone(),
+    /// Local calendar date at `timestamp`
+    pub fn date(&self, timestamp: i64) -> NaiveDate {
+        self.at(timestamp).date_naive()
+    }
+
+    /// First instant of a local calendar day
+    pub fn start_of_day(&self, date: NaiveDate) -> i64 {
+        match self {
+            Self::Named(tz) => start_of_day(tz, date),
+            Self::Fixed(offset) => start_of_day(offset, date),
+        }
+    }
+}
+
+/// Midnight, or the first hour after it where daylight saving skips midnight
+fn start_of_day<Z: TimeZone>(zone: &Z, date: NaiveDate) -> i64 {
+    let midnight = date.and_time(NaiveTime::MIN);
+    (0..3)
+        .find_map(|hour| {
+            zone.from_local_datetime(&(midnight + chrono::Duration::hours(hour)))
+                .earliest()
+        })
+        .map_or_else(|| midnight.and_utc().timestamp(), |start| start.timestamp())
+}
+
+/// Zone for date arithmetic before any weather is at hand: the nearest
+/// bundled city's, else the one last reported for the location's cache
+/// cell, else UTC. Never goes to the network.
+async fn location_zone(lat: f64, lon: f64) -> LocationZone {
+    if let Some(tz) = geocoding::timezone_near(lat, lon).and_then(|name| name.parse::<Tz>().ok()) {
+        return LocationZone::Named(tz);
+    }
+
+    match WeatherCache::global().zone(lat, lon).await {
+        Some(zone) => LocationZone::resolve(
+            lat,
+            lon,
+            zone.timezone.as_deref(),
+            zone.timezone_offset,
+            chrono::Utc::now().timestamp(),
+        ),
+        None => LocationZone::fixed(0),
+    }
+}
+
+/// Prepare weather at a location for display: dates in its timezone and
+/// values in the preferred units
+fn present_weather(lat: f64, lon: f64, data: WeatherData) -> WeatherData {
+    let mut data = data.in_units(Preferences::global().units());
+    let Some(timestamp) = parse_timestamp(&data.date) else {
+        return data;
     };
+    let zone = LocationZone::resolve(
+        lat,
+        lon,
+        data.timezone.as_deref(),
+        data.timezone_offset,
+        timestamp,
+    );
 
-    // Store in cache
-    cache
-        .set_forecast(lat, lon, date, weather_data.clone())
-        .await;
+    data.date = zone.format(timestamp);
+    data.timezone_offset = zone.offset_secs(timestamp);
+    data.timezone = zone.name().map(str::to_string).or(data.timezone);
+    for time in [&mut data.sunrise, &mut data.sunset].into_iter().flatten() {
+        if let Some(timestamp) = parse_timestamp(time) {
+            *time = zone.format(timestamp);
+        }
+    }
+
+    data
+}
 
-    Ok(weather_data)
+/// Seconds since the epoch of an RFC 3339 date. Observations recorded
+/// before dates carried an offset are plain UTC date-times.
+fn parse_timestamp(date: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|time| time.timestamp())
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+                .map(|time| time.and_utc().timestamp())
+        })
+        .ok()
 }
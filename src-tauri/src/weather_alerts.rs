@@ -0,0 +1,506 @@
+use crate::error::AppError;
+use crate::locations::{Locations, SavedLocation};
+use crate::persist;
+use crate::weather;
+use crate::weather_preferences::{Preferences, UnitSystem};
+use crate::weather_provider::{self, Forecast, ForecastSlot, ProviderAlert};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use tokio::sync::Notify;
+
+static WEATHER_ALERTS: OnceLock<WeatherAlerts> = OnceLock::new();
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// Conditions that raise an alert when forecast within the lookahead window
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AlertRules {
+    pub heavy_rain_mm_per_hour: f64,
+    pub freezing_temperature: f64, // °C, at or below
+    pub high_wind_speed: f64,      // m/s, sustained or gusts
+    pub lookahead_hours: u32,
+    pub provider_alerts: bool, // Pass on warnings issued by the provider, where it has a feed
+}
+
+impl Default for AlertRules {
+    fn default() -> Self {
+        Self {
+            heavy_rain_mm_per_hour: 7.6,
+            freezing_temperature: 0.0,
+            high_wind_speed: 17.2, // Gale force
+            lookahead_hours: 24,
+            provider_alerts: true,
+        }
+    }
+}
+
+/// Background polling settings, saved in the app data dir
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AlertConfig {
+    pub enabled: bool,
+    pub poll_interval_secs: u32,
+    pub desktop_notifications: bool,
+    pub rules: AlertRules,
+    pub location_ids: Option<Vec<String>>, // Saved locations to watch, all when unset
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            poll_interval_secs: 30 * 60,
+            desktop_notifications: true,
+            rules: AlertRules::default(),
+            location_ids: None,
+        }
+    }
+}
+
+impl AlertConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.poll_interval_secs < 60 {
+            return Err(AppError::invalid_input(
+                "Alert poll interval must be at least 60 seconds",
+            ));
+        }
+        if !(1..=120).contains(&self.rules.lookahead_hours) {
+            return Err(AppError::invalid_input(
+                "Alert lookahead must be between 1 and 120 hours",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Saved locations this config watches
+    fn watched(&self) -> Vec<SavedLocation> {
+        let locations = Locations::global().list();
+        match &self.location_ids {
+            Some(ids) => locations
+                .into_iter()
+                .filter(|location| ids.contains(&location.id))
+                .collect(),
+            None => locations,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherAlertKind {
+    Provider,
+    HeavyRain,
+    Freezing,
+    HighWind,
+}
+
+/// Emitted when a severe condition is first detected for a watched location
+#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+pub struct WeatherAlert {
+    pub id: String, // Kind, location and start; stable while the event lasts
+    pub kind: WeatherAlertKind,
+    pub location_id: String,
+    pub location: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub title: String,
+    pub description: String,
+    pub starts_at: String,       // RFC 3339
+    pub ends_at: Option<String>, // RFC 3339
+    pub notify: bool,            // Show a desktop notification
+}
+
+/// Watches the configured locations and keeps the alerts currently in effect
+pub struct WeatherAlerts {
+    path: Option<PathBuf>,
+    config: RwLock<AlertConfig>,
+    active: Mutex<HashMap<String, WeatherAlert>>,
+    wake: Notify, // Restarts the poll wait after a config change
+}
+
+impl WeatherAlerts {
+    /// Load the settings file at `path`, starting from defaults if it does
+    /// not exist yet. Without a path nothing is persisted.
+    pub fn open(path: Option<PathBuf>) -> Result<Self, AppError> {
+        let config = match &path {
+            Some(path) => persist::load_json::<AlertConfig>(path, "alert settings")?,
+            None => None,
+        };
+        if let Some(config) = &config {
+            config.validate()?;
+        }
+
+        Ok(Self {
+            path,
+            config: RwLock::new(config.unwrap_or_default()),
+            active: Mutex::new(HashMap::new()),
+            wake: Notify::new(),
+        })
+    }
+
+    pub fn init(path: PathBuf) -> Result<(), AppError> {
+        let alerts = Self::open(Some(path))?;
+        WEATHER_ALERTS
+            .set(alerts)
+            .map_err(|_| AppError::internal("Weather alerts already initialized"))
+    }
+
+    pub fn global() -> &'static WeatherAlerts {
+        WEATHER_ALERTS
+            .get_or_init(|| Self::open(None).expect("In-memory weather alerts cannot fail to open"))
+    }
+
+    pub fn config(&self) -> AlertConfig {
+        self.config
+            .read()
+            .expect("Weather alerts config lock poisoned")
+            .clone()
+    }
+
+    pub fn set_config(&self, config: AlertConfig) -> Result<(), AppError> {
+        config.validate()?;
+        let mut current = self
+            .config
+            .write()
+            .expect("Weather alerts config lock poisoned");
+        if let Some(path) = &self.path {
+            persist::save_json(path, &config, "alert settings")?;
+        }
+        *current = config;
+        drop(current);
+
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    /// Alerts in effect after the last check, soonest first, optionally
+    /// for one saved location
+    pub fn active(&self, location_id: Option<&str>) -> Vec<WeatherAlert> {
+        let mut alerts: Vec<_> = self
+            .active
+            .lock()
+            .expect("Weather alerts lock poisoned")
+            .values()
+            .filter(|alert| location_id.is_none_or(|id| alert.location_id == id))
+            .cloned()
+            .collect();
+        alerts.sort_by(|a, b| a.starts_at.cmp(&b.starts_at).then(a.id.cmp(&b.id)));
+        alerts
+    }
+
+    /// Evaluate every watched location and emit alerts not seen on the
+    /// previous check. Returns the newly raised alerts. Locations that fail
+    /// to load keep their previous alerts; the check only fails when every
+    /// location does.
+    pub async fn check(&self) -> Result<Vec<WeatherAlert>, AppError> {
+        let config = self.config();
+        let now = chrono::Utc::now().timestamp();
+        let previous = self
+            .active
+            .lock()
+            .expect("Weather alerts lock poisoned")
+            .clone();
+
+        let watched = config.watched();
+        let mut current = HashMap::new();
+        let mut last_error = None;
+        let mut any_loaded = watched.is_empty();
+
+        for location in &watched {
+            match load(location, config.rules.provider_alerts).await {
+                Ok((forecast, provider_alerts)) => {
+                    any_loaded = true;
+                    let alerts =
+                        evaluate(&config.rules, location, &forecast, &provider_alerts, now);
+                    for mut alert in alerts {
+                        if let Some(earlier) =
+                            previous.values().find(|earlier| continues(earlier, &alert))
+                        {
+                            alert.id = earlier.id.clone();
+                            alert.starts_at = earlier.starts_at.clone();
+                        }
+                        alert.notify = config.desktop_notifications;
+                        current.insert(alert.id.clone(), alert);
+                    }
+                }
+                Err(e) => {
+                    eprintln!(
+                        "Warning: Failed to check weather alerts for {}: {}",
+                        location.name, e
+                    );
+                    for alert in previous.values() {
+                        if alert.location_id == location.id {
+                            current.insert(alert.id.clone(), alert.clone());
+                        }
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        let mut raised: Vec<_> = current
+            .values()
+            .filter(|alert| !previous.contains_key(&alert.id))
+            .cloned()
+            .collect();
+        raised.sort_by(|a, b| a.starts_at.cmp(&b.starts_at).then(a.id.cmp(&b.id)));
+        *self.active.lock().expect("Weather alerts lock poisoned") = current;
+
+        for alert in &raised {
+            emit(alert);
+        }
+
+        match last_error {
+            Some(e) if !any_loaded => Err(e),
+            _ => Ok(raised),
+        }
+    }
+
+    async fn poll(&self) {
+        loop {
+            let config = self.config();
+            if config.enabled {
+                if let Err(e) = self.check().await {
+                    eprintln!("Warning: Weather alert check failed: {}", e);
+                }
+            }
+
+            let interval = Duration::from_secs(config.poll_interval_secs as u64);
+            let _ = tokio::time::timeout(interval, self.wake.notified()).await;
+        }
+    }
+}
+
+/// Route alert events to the running app and start polling
+pub fn attach(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+    tauri::async_runtime::spawn(WeatherAlerts::global().poll());
+}
+
+fn emit(alert: &WeatherAlert) {
+    if let Some(app) = APP_HANDLE.get() {
+        if let Err(e) = alert.clone().emit(app) {
+            eprintln!("Warning: Failed to emit weather alert: {}", e);
+        }
+    }
+}
+
+async fn load(
+    location: &SavedLocation,
+    provider_alerts: bool,
+) -> Result<(Forecast, Vec<ProviderAlert>), AppError> {
+    let forecast = weather::fetch_forecast_series(location.lat, location.lon)
+        .await?
+        .data;
+    let alerts = if provider_alerts {
+        weather_provider::active()
+            .fetch_alerts(location.lat, location.lon)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    Ok((forecast, alerts))
+}
+
+/// Forecast slot with the time it runs until
+struct Window<'a> {
+    slot: &'a ForecastSlot,
+    end: i64,
+}
+
+impl Window<'_> {
+    fn wind(&self) -> f64 {
+        let point = &self.slot.point;
+        point.wind_speed.max(point.wind_gust.unwrap_or(0.0))
+    }
+}
+
+/// Alerts for one location from its forecast and provider warnings.
+/// Each threshold rule yields one alert per run of consecutive forecast
+/// slots that breach it within the lookahead window.
+pub fn evaluate(
+    rules: &AlertRules,
+    location: &SavedLocation,
+    forecast: &Forecast,
+    provider_alerts: &[ProviderAlert],
+    now: i64,
+) -> Vec<WeatherAlert> {
+    let horizon = now + rules.lookahead_hours as i64 * 60 * 60;
+    // Rules and forecasts are metric; descriptions use the preferred units
+    let units = Preferences::global().units();
+    let alert =
+        |kind, id: String, title: String, description: String, start: i64, end: Option<i64>| {
+            WeatherAlert {
+                id,
+                kind,
+                location_id: location.id.clone(),
+                location: location.name.clone(),
+                lat: location.lat,
+                lon: location.lon,
+                title,
+                description,
+                starts_at: to_rfc3339(start),
+                ends_at: end.map(to_rfc3339),
+                notify: false,
+            }
+        };
+
+    let windows: Vec<_> = forecast
+        .slots
+        .iter()
+        .enumerate()
+        .map(|(i, slot)| Window {
+            slot,
+            end: forecast
+                .slots
+                .get(i + 1)
+                .map_or(slot.timestamp + 60 * 60, |next| next.timestamp),
+        })
+        .filter(|window| window.end > now && window.slot.timestamp < horizon)
+        .collect();
+
+    let mut alerts = Vec::new();
+    // One alert per run of consecutive breaching slots, identified by when
+    // it starts so a later, separate event is raised again
+    let mut raise = |kind,
+                     title: &str,
+                     breaching: &dyn Fn(&Window) -> bool,
+                     describe: &dyn Fn(&[&Window]) -> String| {
+        for event in events(windows.iter().filter(|w| breaching(w))) {
+            let (first, last) = (event[0], event[event.len() - 1]);
+            alerts.push(alert(
+                kind,
+                format!(
+                    "{}_{}_{}",
+                    kind_key(kind),
+                    location.id,
+                    first.slot.timestamp
+                ),
+                title.to_string(),
+                describe(&event),
+                first.slot.timestamp,
+                Some(last.end),
+            ));
+        }
+    };
+
+    raise(
+        WeatherAlertKind::HeavyRain,
+        "Heavy rain",
+        &|w| w.slot.point.rain >= rules.heavy_rain_mm_per_hour,
+        &|event| {
+            let peak = event.iter().map(|w| w.slot.point.rain).fold(0.0, f64::max);
+            format!("Up to {:.1} mm of rain per hour", peak)
+        },
+    );
+
+    raise(
+        WeatherAlertKind::Freezing,
+        "Freezing temperatures",
+        &|w| w.slot.point.temperature <= rules.freezing_temperature,
+        &|event| {
+            let low = event
+                .iter()
+                .map(|w| w.slot.point.temperature)
+                .fold(f64::INFINITY, f64::min);
+            format!(
+                "Down to {:.1} {}",
+                units.temperature(low, UnitSystem::Metric),
+                units.temperature_symbol()
+            )
+        },
+    );
+
+    raise(
+        WeatherAlertKind::HighWind,
+        "High wind",
+        &|w| w.wind() >= rules.high_wind_speed,
+        &|event| {
+            let peak = event.iter().map(|w| w.wind()).fold(0.0, f64::max);
+            format!(
+                "Wind up to {:.1} {}",
+                units.speed(peak, UnitSystem::Metric),
+                units.speed_symbol()
+            )
+        },
+    );
+
+    // Provider warnings that have not ended and start within the window
+    for warning in provider_alerts {
+        if warning.end.is_some_and(|end| end <= now) || warning.start >= horizon {
+            continue;
+        }
+        let description = match &warning.sender {
+            Some(sender) => format!("{} ({})", warning.description, sender),
+            None => warning.description.clone(),
+        };
+        alerts.push(alert(
+            WeatherAlertKind::Provider,
+            format!(
+                "provider_{}_{}_{}",
+                location.id, warning.start, warning.event
+            ),
+            warning.event.clone(),
+            description,
+            warning.start,
+            warning.end,
+        ));
+    }
+
+    alerts
+}
+
+/// Breaching windows split into runs that follow on without a gap
+fn events<'w, 'a>(breaching: impl Iterator<Item = &'w Window<'a>>) -> Vec<Vec<&'w Window<'a>>> {
+    let mut events: Vec<Vec<&Window>> = Vec::new();
+    for window in breaching {
+        match events.last_mut() {
+            Some(event) if event[event.len() - 1].end == window.slot.timestamp => {
+                event.push(window)
+            }
+            _ => events.push(vec![window]),
+        }
+    }
+    events
+}
+
+/// Whether `alert` is the rest of `earlier`, a threshold event under way
+/// whose first slots have passed since it was raised
+fn continues(earlier: &WeatherAlert, alert: &WeatherAlert) -> bool {
+    let timestamp = |time: &str| {
+        chrono::DateTime::parse_from_rfc3339(time)
+            .map(|time| time.timestamp())
+            .ok()
+    };
+    let starts = timestamp(&alert.starts_at);
+
+    alert.kind != WeatherAlertKind::Provider
+        && earlier.kind == alert.kind
+        && earlier.location_id == alert.location_id
+        && timestamp(&earlier.starts_at) < starts
+        && earlier
+            .ends_at
+            .as_deref()
+            .and_then(timestamp)
+            .is_some_and(|end| Some(end) >= starts)
+}
+
+fn kind_key(kind: WeatherAlertKind) -> &'static str {
+    match kind {
+        WeatherAlertKind::Provider => "provider",
+        WeatherAlertKind::HeavyRain => "heavy_rain",
+        WeatherAlertKind::Freezing => "freezing",
+        WeatherAlertKind::HighWind => "high_wind",
+    }
+}
+
+fn to_rfc3339(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .to_rfc3339()
+}
@@ -1,52 +1,746 @@
+use crate::error::AppError;
+use crate::geohash;
+use crate::persist;
 use crate::weather::WeatherData;
+use crate::weather_provider::Forecast;
 use moka::future::Cache;
-use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 use std::time::Duration;
 
 static WEATHER_CACHE: OnceLock<WeatherCache> = OnceLock::new();
 
+/// Geohash length for cache cells, about 1 km across
+pub const DEFAULT_GRID_PRECISION: usize = 6;
+
+/// Expired entries are kept this long so they can still be served stale
+const MAX_STALE_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Timezones reported for a cell are kept this long after the last report
+const ZONE_TTL_SECS: u32 = 30 * 24 * 60 * 60;
+
+/// Changes are written to disk this long after the first of them, so a
+/// burst of fetches rewrites the file once
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// Cache settings, adjustable while the app runs and saved in the app data dir
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct WeatherCacheConfig {
+    pub current_ttl_secs: u32,
+    pub forecast_ttl_secs: u32,
+    pub current_capacity: u32,
+    pub forecast_capacity: u32,
+    pub series_capacity: u32,
+    pub stale_while_revalidate: bool, // Serve expired entries while refreshing
+    pub grid_precision: u32,          // Geohash length of the cells locations snap to
+}
+
+impl Default for WeatherCacheConfig {
+    fn default() -> Self {
+        Self {
+            // Current weather is refreshed after 10 minutes, forecasts after 1 hour
+            current_ttl_secs: 10 * 60,
+            forecast_ttl_secs: 60 * 60,
+            current_capacity: 100,
+            forecast_capacity: 1000,
+            series_capacity: 100,
+            stale_while_revalidate: true,
+            grid_precision: DEFAULT_GRID_PRECISION as u32,
+        }
+    }
+}
+
+impl WeatherCacheConfig {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.current_ttl_secs == 0 || self.forecast_ttl_secs == 0 {
+            return Err(AppError::invalid_input(
+                "Cache TTLs must be at least 1 second",
+            ));
+        }
+        if self.current_capacity == 0 || self.forecast_capacity == 0 || self.series_capacity == 0 {
+            return Err(AppError::invalid_input(
+                "Cache capacities must be at least 1",
+            ));
+        }
+        if !(1..=geohash::MAX_PRECISION as u32).contains(&self.grid_precision) {
+            return Err(AppError::invalid_input(format!(
+                "Grid precision must be between 1 and {}",
+                geohash::MAX_PRECISION
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherCacheKind {
+    Current,
+    Forecast,
+    Series,
+}
+
+/// A cached response, as listed for inspection
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct WeatherCacheEntry {
+    pub kind: WeatherCacheKind,
+    pub cell: String,         // Geohash of the cached location
+    pub hour: Option<String>, // RFC 3339, for forecast entries
+    pub location: String,
+    pub fetched_at: String, // RFC 3339
+    pub age_secs: u32,
+    pub stale: bool,
+}
+
+/// Lookup counters since startup, and current entry counts
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct WeatherCacheStats {
+    pub hits: u32,
+    pub stale_hits: u32,
+    pub misses: u32,
+    pub evictions: u32,
+    pub current_entries: u32,
+    pub forecast_entries: u32,
+    pub series_entries: u32,
+}
+
+/// Stored value and when it was fetched from the provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry<T> {
+    value: T,
+    fetched_at: i64, // Unix seconds
+}
+
+/// Value returned from the cache.
+/// `stale` is set once the entry has outlived its TTL.
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    pub value: T,
+    pub fetched_at: i64,
+    pub stale: bool,
+}
+
+/// Timezone the provider reported for a cell, as of the last response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CellZone {
+    pub timezone: Option<String>, // IANA name, when the provider gives one
+    pub timezone_offset: i32,     // Seconds east of UTC when reported
+}
+
+/// On-disk form of the cache
+#[derive(Default, Serialize, Deserialize)]
+struct Snapshot {
+    current: HashMap<String, Entry<WeatherData>>,
+    forecast: HashMap<String, Entry<WeatherData>>,
+    series: HashMap<String, Entry<Forecast>>,
+    #[serde(default)]
+    zones: HashMap<String, Entry<CellZone>>,
+}
+
+/// The moka caches, rebuilt when their capacity or TTL changes
+struct Caches {
+    current: Cache<String, Entry<WeatherData>>,
+    forecast: Cache<String, Entry<WeatherData>>,
+    series: Cache<String, Entry<Forecast>>,
+    zones: Cache<String, Entry<CellZone>>,
+}
+
+impl Caches {
+    fn build(config: &WeatherCacheConfig, evictions: &Arc<AtomicU64>) -> Self {
+        Self {
+            current: build_cache(config.current_capacity, config.current_ttl_secs, evictions),
+            forecast: build_cache(
+                config.forecast_capacity,
+                config.forecast_ttl_secs,
+                evictions,
+            ),
+            series: build_cache(config.series_capacity, config.forecast_ttl_secs, evictions),
+            // Not a response, so its evictions are not counted
+            zones: build_cache(config.current_capacity, ZONE_TTL_SECS, &Arc::default()),
+        }
+    }
+}
+
+/// Where the cache is saved, and whether a save is already scheduled
+struct Persistence {
+    path: PathBuf,
+    scheduled: AtomicBool,
+    write_lock: Mutex<()>,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: Arc<AtomicU64>,
+}
+
+/// Weather responses keyed by location, persisted as JSON in the app data dir
+/// so a restart does not start cold.
+///
+/// Locations are snapped to geohash cells so nearby requests share entries,
+/// and forecast entries are keyed by the hour they describe.
 pub struct WeatherCache {
-    current_cache: Cache<String, WeatherData>,
-    forecast_cache: Cache<String, WeatherData>,
+    persistence: Option<Arc<Persistence>>,
+    config: RwLock<WeatherCacheConfig>,
+    config_path: OnceLock<PathBuf>,
+    caches: Arc<RwLock<Arc<Caches>>>,
+    counters: Counters,
 }
 
 impl WeatherCache {
-    fn new() -> Self {
+    fn new(path: Option<PathBuf>) -> Self {
+        let config = WeatherCacheConfig::default();
+        let counters = Counters::default();
+        let caches = Caches::build(&config, &counters.evictions);
+
         Self {
-            // Cache current weather for 10 minutes
-            current_cache: Cache::builder()
-                .time_to_live(Duration::from_secs(10 * 60))
-                .max_capacity(100)
-                .build(),
-            // Cache forecast for 1 hour
-            forecast_cache: Cache::builder()
-                .time_to_live(Duration::from_secs(60 * 60))
-                .max_capacity(1000)
-                .build(),
+            persistence: path.map(|path| {
+                Arc::new(Persistence {
+                    path,
+                    scheduled: AtomicBool::new(false),
+                    write_lock: Mutex::new(()),
+                })
+            }),
+            config: RwLock::new(config),
+            config_path: OnceLock::new(),
+            caches: Arc::new(RwLock::new(Arc::new(caches))),
+            counters,
         }
     }
 
+    /// Load the cache file at `path`, starting empty if it does not exist yet
+    /// or cannot be read; it is rewritten on the next change. Entries too old
+    /// to be served even stale are dropped. Without a path the cache is kept
+    /// in memory only.
+    pub async fn open(path: Option<PathBuf>) -> Result<Self, AppError> {
+        let snapshot = match &path {
+            Some(path) if path.exists() => fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|body| serde_json::from_str(&body).map_err(|e| e.to_string()))
+                .unwrap_or_else(|e| {
+                    eprintln!("Warning: Discarding unreadable weather cache: {}", e);
+                    Snapshot::default()
+                }),
+            _ => Snapshot::default(),
+        };
+
+        let cache = Self::new(path);
+        let config = cache.config();
+        let caches = cache.caches();
+        let now = chrono::Utc::now().timestamp();
+        let keep = |fetched_at: i64, ttl: u32| now - fetched_at <= ttl as i64 + MAX_STALE_SECS;
+
+        for (key, entry) in snapshot.current {
+            if keep(entry.fetched_at, config.current_ttl_secs) {
+                caches.current.insert(key, entry).await;
+            }
+        }
+        for (key, entry) in snapshot.forecast {
+            if keep(entry.fetched_at, config.forecast_ttl_secs) {
+                caches.forecast.insert(key, entry).await;
+            }
+        }
+        for (key, entry) in snapshot.series {
+            if keep(entry.fetched_at, config.forecast_ttl_secs) {
+                caches.series.insert(key, entry).await;
+            }
+        }
+        for (key, entry) in snapshot.zones {
+            if keep(entry.fetched_at, ZONE_TTL_SECS) {
+                caches.zones.insert(key, entry).await;
+            }
+        }
+
+        Ok(cache)
+    }
+
+    pub async fn init(path: PathBuf) -> Result<(), AppError> {
+        let cache = Self::open(Some(path)).await?;
+        WEATHER_CACHE
+            .set(cache)
+            .map_err(|_| AppError::internal("WeatherCache already initialized"))
+    }
+
     pub fn global() -> &'static WeatherCache {
-        WEATHER_CACHE.get_or_init(Self::new)
+        WEATHER_CACHE.get_or_init(|| Self::new(None))
+    }
+
+    pub fn config(&self) -> WeatherCacheConfig {
+        self.config
+            .read()
+            .expect("Weather cache config lock poisoned")
+            .clone()
+    }
+
+    /// Restore the settings saved at `path`, if any, and save later changes
+    /// there. Returns whether there were saved settings.
+    pub async fn load_config(&self, path: PathBuf) -> Result<bool, AppError> {
+        let saved = persist::load_json::<WeatherCacheConfig>(&path, "weather cache settings");
+        self.config_path
+            .set(path)
+            .map_err(|_| AppError::internal("Weather cache settings already loaded"))?;
+
+        match saved? {
+            Some(config) => {
+                config.validate()?;
+                self.apply_config(config).await;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Apply and save new settings, returning them as stored. Changing a
+    /// capacity or TTL rebuilds the caches, carrying entries over; changing
+    /// the grid precision orphans existing entries, which then age out.
+    pub async fn set_config(
+        &self,
+        config: WeatherCacheConfig,
+    ) -> Result<WeatherCacheConfig, AppError> {
+        config.validate()?;
+        if let Some(path) = self.config_path.get() {
+            persist::save_json(path, &config, "weather cache settings")?;
+        }
+
+        self.apply_config(config.clone()).await;
+        Ok(config)
+    }
+
+    async fn apply_config(&self, config: WeatherCacheConfig) {
+        let previous = self.config();
+        *self
+            .config
+            .write()
+            .expect("Weather cache config lock poisoned") = config.clone();
+
+        let resized = previous.current_ttl_secs != config.current_ttl_secs
+            || previous.forecast_ttl_secs != config.forecast_ttl_secs
+            || previous.current_capacity != config.current_capacity
+            || previous.forecast_capacity != config.forecast_capacity
+            || previous.series_capacity != config.series_capacity;
+        if !resized {
+            return;
+        }
+
+        let old = self.caches();
+        let new = Caches::build(&config, &self.counters.evictions);
+        for (key, entry) in old.current.iter() {
+            new.current.insert(key.as_ref().clone(), entry).await;
+        }
+        for (key, entry) in old.forecast.iter() {
+            new.forecast.insert(key.as_ref().clone(), entry).await;
+        }
+        for (key, entry) in old.series.iter() {
+            new.series.insert(key.as_ref().clone(), entry).await;
+        }
+        for (key, entry) in old.zones.iter() {
+            new.zones.insert(key.as_ref().clone(), entry).await;
+        }
+        *self.caches.write().expect("Weather cache lock poisoned") = Arc::new(new);
+
+        self.persist();
+    }
+
+    /// Whether expired entries are served while a refresh runs in the background
+    pub fn stale_while_revalidate(&self) -> bool {
+        self.config().stale_while_revalidate
     }
 
-    pub async fn get_current(&self, lat: f64, lon: f64) -> Option<WeatherData> {
-        let key = format!("current_{:.4}_{:.4}", lat, lon);
-        self.current_cache.get(&key).await
+    fn caches(&self) -> Arc<Caches> {
+        Arc::clone(&self.caches.read().expect("Weather cache lock poisoned"))
+    }
+
+    /// Grid cell a location is cached under
+    pub fn cell(&self, lat: f64, lon: f64) -> String {
+        geohash::encode(lat, lon, self.config().grid_precision as usize)
+    }
+
+    fn forecast_key(&self, lat: f64, lon: f64, timestamp: i64) -> String {
+        format!(
+            "forecast_{}_{}",
+            self.cell(lat, lon),
+            start_of_hour(timestamp)
+        )
+    }
+
+    pub async fn get_current(&self, lat: f64, lon: f64) -> Option<Cached<WeatherData>> {
+        let key = format!("current_{}", self.cell(lat, lon));
+        let ttl = self.config().current_ttl_secs;
+        self.lookup(&self.caches().current, &key, ttl).await
     }
 
     pub async fn set_current(&self, lat: f64, lon: f64, data: WeatherData) {
-        let key = format!("current_{:.4}_{:.4}", lat, lon);
-        self.current_cache.insert(key, data).await;
+        let caches = self.caches();
+        self.set_zone(&caches, lat, lon, &data.timezone, data.timezone_offset)
+            .await;
+
+        let key = format!("current_{}", self.cell(lat, lon));
+        caches.current.insert(key, entry(data)).await;
+        self.persist();
+    }
+
+    /// Weather for the hour containing `timestamp`
+    pub async fn get_forecast(
+        &self,
+        lat: f64,
+        lon: f64,
+        timestamp: i64,
+    ) -> Option<Cached<WeatherData>> {
+        let key = self.forecast_key(lat, lon, timestamp);
+        let ttl = self.config().forecast_ttl_secs;
+        self.lookup(&self.caches().forecast, &key, ttl).await
+    }
+
+    pub async fn set_forecast(&self, lat: f64, lon: f64, timestamp: i64, data: WeatherData) {
+        let key = self.forecast_key(lat, lon, timestamp);
+        self.caches().forecast.insert(key, entry(data)).await;
+        self.persist();
+    }
+
+    pub async fn get_series(&self, lat: f64, lon: f64) -> Option<Cached<Forecast>> {
+        let key = format!("series_{}", self.cell(lat, lon));
+        let ttl = self.config().forecast_ttl_secs;
+        self.lookup(&self.caches().series, &key, ttl).await
+    }
+
+    /// Store a forecast series along with an hourly entry for each of its slots
+    pub async fn set_series(&self, lat: f64, lon: f64, data: Forecast) {
+        let caches = self.caches();
+
+        for slot in &data.slots {
+            let key = self.forecast_key(lat, lon, slot.timestamp);
+            let weather_data = slot.clone().into_weather_data(
+                data.location.clone(),
+                data.timezone_offset,
+                data.timezone.clone(),
+            );
+            caches.forecast.insert(key, entry(weather_data)).await;
+        }
+
+        self.set_zone(&caches, lat, lon, &data.timezone, data.timezone_offset)
+            .await;
+
+        let key = format!("series_{}", self.cell(lat, lon));
+        caches.series.insert(key, entry(data)).await;
+        self.persist();
     }
 
-    pub async fn get_forecast(&self, lat: f64, lon: f64, date: &str) -> Option<WeatherData> {
-        let key = format!("forecast_{:.4}_{:.4}_{}", lat, lon, date);
-        self.forecast_cache.get(&key).await
+    /// Timezone last reported for the cell containing a location. It outlives
+    /// the responses it came with and is kept when they are invalidated or
+    /// cleared, since switching providers does not move a location.
+    pub async fn zone(&self, lat: f64, lon: f64) -> Option<CellZone> {
+        let key = format!("zone_{}", self.cell(lat, lon));
+        self.caches().zones.get(&key).await.map(|entry| entry.value)
     }
 
-    pub async fn set_forecast(&self, lat: f64, lon: f64, date: &str, data: WeatherData) {
-        let key = format!("forecast_{:.4}_{:.4}_{}", lat, lon, date);
-        self.forecast_cache.insert(key, data).await;
+    async fn set_zone(
+        &self,
+        caches: &Caches,
+        lat: f64,
+        lon: f64,
+        timezone: &Option<String>,
+        timezone_offset: i32,
+    ) {
+        let key = format!("zone_{}", self.cell(lat, lon));
+        let zone = CellZone {
+            timezone: timezone.clone(),
+            timezone_offset,
+        };
+        caches.zones.insert(key, entry(zone)).await;
     }
+
+    /// Every cached entry, newest first
+    pub fn entries(&self) -> Vec<WeatherCacheEntry> {
+        let config = self.config();
+        let caches = self.caches();
+        let now = chrono::Utc::now().timestamp();
+
+        let describe = |kind: WeatherCacheKind, key: &str, location: &str, fetched_at: i64| {
+            let (cell, hour) = parse_key(key);
+            let age = now - fetched_at;
+            let ttl = match kind {
+                WeatherCacheKind::Current => config.current_ttl_secs,
+                _ => config.forecast_ttl_secs,
+            };
+
+            WeatherCacheEntry {
+                kind,
+                cell,
+                hour: hour
+                    .and_then(|hour| chrono::DateTime::from_timestamp(hour, 0))
+                    .map(|hour| hour.to_rfc3339()),
+                location: location.to_string(),
+                fetched_at: to_rfc3339(fetched_at),
+                age_secs: age.clamp(0, u32::MAX as i64) as u32,
+                stale: age > ttl as i64,
+            }
+        };
+
+        let mut entries: Vec<_> = caches
+            .current
+            .iter()
+            .map(|(key, entry)| {
+                describe(
+                    WeatherCacheKind::Current,
+                    &key,
+                    &entry.value.location,
+                    entry.fetched_at,
+                )
+            })
+            .chain(caches.forecast.iter().map(|(key, entry)| {
+                describe(
+                    WeatherCacheKind::Forecast,
+                    &key,
+                    &entry.value.location,
+                    entry.fetched_at,
+                )
+            }))
+            .chain(caches.series.iter().map(|(key, entry)| {
+                describe(
+                    WeatherCacheKind::Series,
+                    &key,
+                    &entry.value.location,
+                    entry.fetched_at,
+                )
+            }))
+            .collect();
+
+        entries.sort_by_key(|entry| entry.age_secs);
+        entries
+    }
+
+    /// Drop entries of one kind, near one location, or both.
+    /// Returns how many entries were removed.
+    pub async fn invalidate(
+        &self,
+        kind: Option<WeatherCacheKind>,
+        location: Option<(f64, f64)>,
+    ) -> usize {
+        let caches = self.caches();
+        let cell = location.map(|(lat, lon)| self.cell(lat, lon));
+
+        let matches = |entry_kind: WeatherCacheKind, key: &str| {
+            kind.is_none_or(|kind| kind == entry_kind)
+                && cell.as_ref().is_none_or(|cell| parse_key(key).0 == *cell)
+        };
+
+        let mut removed = 0;
+        for key in keys(&caches.current) {
+            if matches(WeatherCacheKind::Current, &key) {
+                caches.current.invalidate(&key).await;
+                removed += 1;
+            }
+        }
+        for key in keys(&caches.forecast) {
+            if matches(WeatherCacheKind::Forecast, &key) {
+                caches.forecast.invalidate(&key).await;
+                removed += 1;
+            }
+        }
+        for key in keys(&caches.series) {
+            if matches(WeatherCacheKind::Series, &key) {
+                caches.series.invalidate(&key).await;
+                removed += 1;
+            }
+        }
+
+        self.persist();
+        removed
+    }
+
+    pub async fn stats(&self) -> WeatherCacheStats {
+        let caches = self.caches();
+
+        // Apply pending evictions so the counts are current
+        caches.current.run_pending_tasks().await;
+        caches.forecast.run_pending_tasks().await;
+        caches.series.run_pending_tasks().await;
+
+        let count =
+            |counter: &AtomicU64| counter.load(Ordering::Relaxed).min(u32::MAX as u64) as u32;
+
+        WeatherCacheStats {
+            hits: count(&self.counters.hits),
+            stale_hits: count(&self.counters.stale_hits),
+            misses: count(&self.counters.misses),
+            evictions: count(&self.counters.evictions),
+            current_entries: caches.current.iter().count() as u32,
+            forecast_entries: caches.forecast.iter().count() as u32,
+            series_entries: caches.series.iter().count() as u32,
+        }
+    }
+
+    pub fn clear(&self) {
+        let caches = self.caches();
+        caches.current.invalidate_all();
+        caches.forecast.invalidate_all();
+        caches.series.invalidate_all();
+        self.persist();
+    }
+
+    /// Look up `key`, marking entries past `ttl_secs` as stale and hiding
+    /// entries too old to serve at all
+    async fn lookup<T>(
+        &self,
+        cache: &Cache<String, Entry<T>>,
+        key: &str,
+        ttl_secs: u32,
+    ) -> Option<Cached<T>>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let entry = cache
+            .get(key)
+            .await
+            .filter(|entry| age(entry) <= ttl_secs as i64 + MAX_STALE_SECS);
+
+        let Some(entry) = entry else {
+            self.counters.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+
+        let stale = age(&entry) > ttl_secs as i64;
+        let counter = if stale {
+            &self.counters.stale_hits
+        } else {
+            &self.counters.hits
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        Some(Cached {
+            value: entry.value,
+            fetched_at: entry.fetched_at,
+            stale,
+        })
+    }
+
+    /// Schedule writing the cache to disk, unless a write is already
+    /// scheduled. Failures are logged since the in-memory copy is still valid.
+    fn persist(&self) {
+        let Some(persistence) = &self.persistence else {
+            return;
+        };
+        if persistence.scheduled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let persistence = Arc::clone(persistence);
+        let caches = Arc::clone(&self.caches);
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(SAVE_DELAY).await;
+            if let Err(e) = save(persistence, caches).await {
+                eprintln!("Warning: {}", e);
+            }
+        });
+    }
+
+    /// Write pending changes to disk now, e.g. before the app exits
+    pub async fn flush(&self) -> Result<(), AppError> {
+        match &self.persistence {
+            Some(persistence) => save(Arc::clone(persistence), Arc::clone(&self.caches)).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Write the cache off the async runtime's threads. Saves run one at a
+/// time, each writing the entries as they are when it starts.
+async fn save(
+    persistence: Arc<Persistence>,
+    caches: Arc<RwLock<Arc<Caches>>>,
+) -> Result<(), AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = persistence
+            .write_lock
+            .lock()
+            .expect("Weather cache lock poisoned");
+        // Changes from here on schedule another save
+        persistence.scheduled.store(false, Ordering::Release);
+
+        let caches = Arc::clone(&caches.read().expect("Weather cache lock poisoned"));
+        let snapshot = Snapshot {
+            current: entries(&caches.current),
+            forecast: entries(&caches.forecast),
+            series: entries(&caches.series),
+            zones: entries(&caches.zones),
+        };
+
+        let body = serde_json::to_string(&snapshot)
+            .map_err(|e| AppError::internal(format!("Failed to serialize weather cache: {}", e)))?;
+        persist::write_atomically(&persistence.path, body.as_bytes())
+            .map_err(|e| AppError::io(format!("Failed to write weather cache: {}", e)))
+    })
+    .await
+    .map_err(|e| AppError::internal(format!("Weather cache save failed: {}", e)))?
+}
+
+/// Start of the hour a timestamp falls in; forecast entries are keyed by it
+pub fn start_of_hour(timestamp: i64) -> i64 {
+    timestamp - timestamp.rem_euclid(60 * 60)
+}
+
+fn build_cache<T>(capacity: u32, ttl_secs: u32, evictions: &Arc<AtomicU64>) -> Cache<String, T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let evictions = Arc::clone(evictions);
+
+    Cache::builder()
+        .time_to_live(Duration::from_secs(ttl_secs as u64 + MAX_STALE_SECS as u64))
+        .max_capacity(capacity as u64)
+        .eviction_listener(move |_key, _value, cause| {
+            if cause.was_evicted() {
+                evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .build()
+}
+
+fn entry<T>(value: T) -> Entry<T> {
+    Entry {
+        value,
+        fetched_at: chrono::Utc::now().timestamp(),
+    }
+}
+
+fn age<T>(entry: &Entry<T>) -> i64 {
+    chrono::Utc::now().timestamp() - entry.fetched_at
+}
+
+/// Split a key such as `forecast_u2yhvd_1705320000` into its cell and hour
+fn parse_key(key: &str) -> (String, Option<i64>) {
+    let mut parts = key.splitn(3, '_').skip(1);
+    let cell = parts.next().unwrap_or_default().to_string();
+    let hour = parts.next().and_then(|hour| hour.parse().ok());
+    (cell, hour)
+}
+
+fn to_rfc3339(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .to_rfc3339()
+}
+
+fn keys<T>(cache: &Cache<String, T>) -> Vec<String>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    cache.iter().map(|(key, _)| key.as_ref().clone()).collect()
+}
+
+fn entries<T>(cache: &Cache<String, Entry<T>>) -> HashMap<String, Entry<T>>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    cache
+        .iter()
+        .map(|(key, entry)| (key.as_ref().clone(), entry))
+        .collect()
 }
@@ -0,0 +1,163 @@
+use crate::error::AppError;
+use crate::persist;
+use crate::weather::WeatherData;
+use crate::weather_preferences::UnitSystem;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+static WEATHER_CLASSIFIER: OnceLock<WeatherClassifier> = OnceLock::new();
+
+/// Icon codes of rain, drizzle and thunderstorms; both providers report
+/// OpenWeatherMap-style icons
+const RAIN_ICONS: [&str; 3] = ["09", "10", "11"];
+const SNOW_ICONS: [&str; 1] = ["13"];
+
+/// Weather a piece of clothing is suited for, as tagged in the wardrobe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherType {
+    Hot,
+    Warm,
+    Cool,
+    Cold,
+    Rainy,
+    Snowy,
+}
+
+/// Where one temperature band ends and the next begins, by feels-like
+/// temperature, and how much precipitation makes a day wet
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct ClassifierThresholds {
+    pub hot_feels_like: f64,  // °C, at or above
+    pub warm_feels_like: f64, // °C, at or above
+    pub cool_feels_like: f64, // °C, at or above; anything colder is cold
+    pub rainy_mm: f64,        // Rain at or above this is rainy whatever the condition
+    pub snowy_mm: f64,        // Snow at or above this is snowy whatever the condition
+}
+
+impl Default for ClassifierThresholds {
+    fn default() -> Self {
+        Self {
+            hot_feels_like: 25.0,
+            warm_feels_like: 17.0,
+            cool_feels_like: 8.0,
+            rainy_mm: 0.2,
+            snowy_mm: 0.2,
+        }
+    }
+}
+
+impl ClassifierThresholds {
+    fn validate(&self) -> Result<(), AppError> {
+        if !(self.hot_feels_like > self.warm_feels_like
+            && self.warm_feels_like > self.cool_feels_like)
+        {
+            return Err(AppError::invalid_input(
+                "Temperature thresholds must fall from hot to warm to cool",
+            ));
+        }
+        if !(self.rainy_mm > 0.0 && self.snowy_mm > 0.0) {
+            return Err(AppError::invalid_input(
+                "Precipitation thresholds must be above zero",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Tags a reading with one temperature band, plus rainy and snowy when
+/// precipitation or the reported condition calls for them
+pub fn classify(weather: &WeatherData, thresholds: &ClassifierThresholds) -> Vec<WeatherType> {
+    let feels_like = UnitSystem::Metric.temperature(weather.feels_like, weather.units);
+    let band = if feels_like >= thresholds.hot_feels_like {
+        WeatherType::Hot
+    } else if feels_like >= thresholds.warm_feels_like {
+        WeatherType::Warm
+    } else if feels_like >= thresholds.cool_feels_like {
+        WeatherType::Cool
+    } else {
+        WeatherType::Cold
+    };
+
+    let condition = weather.icon.get(..2).unwrap_or_default();
+    let mut types = vec![band];
+    if weather.rain >= thresholds.rainy_mm || RAIN_ICONS.contains(&condition) {
+        types.push(WeatherType::Rainy);
+    }
+    if weather.snow >= thresholds.snowy_mm || SNOW_ICONS.contains(&condition) {
+        types.push(WeatherType::Snowy);
+    }
+
+    types
+}
+
+/// Classifies weather with thresholds shared by every app, persisted in the
+/// app data dir
+pub struct WeatherClassifier {
+    path: Option<PathBuf>,
+    thresholds: RwLock<ClassifierThresholds>,
+}
+
+impl WeatherClassifier {
+    /// Load the thresholds file at `path`, starting from defaults if it does
+    /// not exist yet. Without a path nothing is persisted.
+    pub fn open(path: Option<PathBuf>) -> Result<Self, AppError> {
+        let thresholds = match &path {
+            Some(path) => {
+                persist::load_json::<ClassifierThresholds>(path, "classifier thresholds")?
+            }
+            None => None,
+        };
+        if let Some(thresholds) = &thresholds {
+            thresholds.validate()?;
+        }
+
+        Ok(Self {
+            path,
+            thresholds: RwLock::new(thresholds.unwrap_or_default()),
+        })
+    }
+
+    pub fn init(path: PathBuf) -> Result<(), AppError> {
+        let classifier = Self::open(Some(path))?;
+        WEATHER_CLASSIFIER
+            .set(classifier)
+            .map_err(|_| AppError::internal("Weather classifier already initialized"))
+    }
+
+    pub fn global() -> &'static WeatherClassifier {
+        WEATHER_CLASSIFIER.get_or_init(|| {
+            Self::open(None).expect("In-memory weather classifier cannot fail to open")
+        })
+    }
+
+    pub fn thresholds(&self) -> ClassifierThresholds {
+        self.thresholds
+            .read()
+            .expect("Classifier lock poisoned")
+            .clone()
+    }
+
+    /// Replace the thresholds, returning them as stored
+    pub fn set_thresholds(
+        &self,
+        thresholds: ClassifierThresholds,
+    ) -> Result<ClassifierThresholds, AppError> {
+        thresholds.validate()?;
+        let mut current = self.thresholds.write().expect("Classifier lock poisoned");
+        if let Some(path) = &self.path {
+            persist::save_json(path, &thresholds, "classifier thresholds")?;
+        }
+        *current = thresholds.clone();
+        Ok(thresholds)
+    }
+
+    pub fn classify(&self, weather: &WeatherData) -> Vec<WeatherType> {
+        classify(
+            weather,
+            &self.thresholds.read().expect("Classifier lock poisoned"),
+        )
+    }
+}
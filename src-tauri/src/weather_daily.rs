@@ -0,0 +1,104 @@
+use crate::weather::LocationZone;
+use crate::weather_preferences::UnitSystem;
+use crate::weather_provider::{Forecast, ForecastSlot};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeMap;
+
+/// Forecast summary for one local calendar day
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DailyForecast {
+    pub date: String,       // Local calendar date, YYYY-MM-DD
+    pub temp_min: f64,      // In `units`
+    pub temp_max: f64,      // In `units`
+    pub humidity: f64,      // Mean over the day's slots
+    pub precipitation: f64, // Total rain and snow over the day's slots, mm
+    pub pop: f64,           // Highest probability of precipitation, 0-1
+    pub description: String,
+    pub icon: String,
+    pub location: String,
+    pub units: UnitSystem,
+}
+
+impl DailyForecast {
+    /// The same day with temperatures in `units`
+    pub fn in_units(mut self, units: UnitSystem) -> Self {
+        self.temp_min = units.temperature(self.temp_min, self.units);
+        self.temp_max = units.temperature(self.temp_max, self.units);
+        self.units = units;
+        self
+    }
+}
+
+/// Group forecast slots by local day at the forecast location and summarize each day.
+/// Partial days at either end of the series are included.
+pub fn aggregate(forecast: &Forecast, zone: &LocationZone) -> Vec<DailyForecast> {
+    let mut days: BTreeMap<NaiveDate, (Vec<&ForecastSlot>, f64)> = BTreeMap::new();
+    for (i, slot) in forecast.slots.iter().enumerate() {
+        let (slots, precipitation) = days.entry(zone.date(slot.timestamp)).or_default();
+        slots.push(slot);
+        // Slots report hourly rates, so weigh them by how long they last
+        *precipitation += (slot.point.rain + slot.point.snow) * forecast.slot_hours(i);
+    }
+
+    days.into_iter()
+        .map(|(date, (slots, precipitation))| {
+            summarize(date, &slots, precipitation, &forecast.location)
+        })
+        .collect()
+}
+
+fn summarize(
+    date: NaiveDate,
+    slots: &[&ForecastSlot],
+    precipitation: f64,
+    location: &str,
+) -> DailyForecast {
+    let points = slots.iter().map(|slot| &slot.point);
+    let (description, icon) = dominant_condition(slots);
+
+    DailyForecast {
+        date: date.format("%Y-%m-%d").to_string(),
+        temp_min: points
+            .clone()
+            .map(|p| p.temperature)
+            .fold(f64::INFINITY, f64::min),
+        temp_max: points
+            .clone()
+            .map(|p| p.temperature)
+            .fold(f64::NEG_INFINITY, f64::max),
+        humidity: points.clone().map(|p| p.humidity as f64).sum::<f64>() / slots.len() as f64,
+        precipitation,
+        pop: points.map(|p| p.pop).fold(0.0, f64::max),
+        description,
+        icon,
+        location: location.to_string(),
+        // Slots of one series share their units
+        units: slots[0].point.units,
+    }
+}
+
+/// Most frequent condition of the day; ties go to the earliest slot.
+/// The icon uses the daytime variant so night slots don't darken a daily summary.
+fn dominant_condition(slots: &[&ForecastSlot]) -> (String, String) {
+    let mut counts: Vec<(&str, &str, usize)> = Vec::new();
+    for slot in slots {
+        let point = &slot.point;
+        match counts.iter_mut().find(|(d, _, _)| *d == point.description) {
+            Some((_, _, count)) => *count += 1,
+            None => counts.push((&point.description, &point.icon, 1)),
+        }
+    }
+
+    // max_by_key keeps the last maximum, so iterate in reverse to favour earlier slots
+    counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, _, count)| *count)
+        .map(|(description, icon, _)| {
+            let code = icon.trim_end_matches(['d', 'n']);
+            (description.to_string(), format!("{}d", code))
+        })
+        .unwrap_or_else(|| ("Unknown".to_string(), "01d".to_string()))
+}
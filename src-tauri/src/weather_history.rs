@@ -0,0 +1,248 @@
+use crate::error::AppError;
+use crate::persist;
+use crate::weather::WeatherData;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+static WEATHER_HISTORY: OnceLock<WeatherHistory> = OnceLock::new();
+
+/// Observations farther apart than this are treated as different places (~5 km)
+const MAX_DISTANCE_DEG: f64 = 0.05;
+
+/// Largest gap between an observation and the requested time
+const MAX_TIME_GAP_SECS: i64 = 90 * 60;
+
+/// Oldest observations are dropped beyond this count
+const MAX_OBSERVATIONS: usize = 10_000;
+
+/// Lines the file may grow past `MAX_OBSERVATIONS` before it is rewritten
+/// without the dropped observations
+const COMPACT_AFTER: usize = 1_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Observation {
+    lat: f64,
+    lon: f64,
+    timestamp: i64,
+    data: WeatherData,
+}
+
+/// Record of past weather, persisted in the app data dir as one JSON
+/// observation per line. Filled from current conditions as they are fetched
+/// and from historical lookups.
+pub struct WeatherHistory {
+    path: Option<PathBuf>,
+    state: Mutex<State>,
+    writing: Mutex<()>, // Held while writing, so the file gets lines in recorded order
+}
+
+struct State {
+    observations: Vec<Observation>,
+    lines: usize, // Observations in the file, including ones dropped since
+}
+
+impl WeatherHistory {
+    /// Load the history file at `path`, starting empty if it does not exist
+    /// yet. Unreadable lines, such as one cut short by a crash, are skipped.
+    /// A `.json` file beside it from before observations were appended is
+    /// taken over and rewritten as lines. Without a path the history is kept
+    /// in memory only.
+    pub fn open(path: Option<PathBuf>) -> Result<Self, AppError> {
+        let history = Self {
+            state: Mutex::new(State {
+                observations: Vec::new(),
+                lines: 0,
+            }),
+            writing: Mutex::new(()),
+            path,
+        };
+        let Some(path) = &history.path else {
+            return Ok(history);
+        };
+
+        let legacy = path.with_extension("json");
+        if legacy != *path && legacy.exists() && !path.exists() {
+            fs::rename(&legacy, path)
+                .map_err(|e| AppError::io(format!("Failed to move old weather history: {}", e)))?;
+        }
+
+        let (mut observations, intact) = load(path)?;
+        let trimmed = trim(&mut observations);
+        let mut state = history.lock();
+        state.lines = observations.len();
+        state.observations = observations;
+        // Appending after a damaged line would merge the next observation into it
+        if !intact || trimmed {
+            history.compact(&state.observations)?;
+        }
+        drop(state);
+
+        Ok(history)
+    }
+
+    pub fn init(path: PathBuf) -> Result<(), AppError> {
+        let history = Self::open(Some(path))?;
+        WEATHER_HISTORY
+            .set(history)
+            .map_err(|_| AppError::internal("WeatherHistory already initialized"))
+    }
+
+    pub fn global() -> &'static WeatherHistory {
+        WEATHER_HISTORY.get_or_init(|| {
+            Self::open(None).expect("In-memory weather history cannot fail to open")
+        })
+    }
+
+    /// Add an observation, visible to `find` at once. The file is written
+    /// after the history is unlocked, so lookups never wait on the disk;
+    /// async code should call this through `spawn_blocking`.
+    pub fn record(&self, lat: f64, lon: f64, timestamp: i64, data: WeatherData) {
+        let observation = Observation {
+            lat,
+            lon,
+            timestamp,
+            data,
+        };
+        let _writing = self.writing.lock().expect("Weather history lock poisoned");
+
+        let compacted = {
+            let mut state = self.lock();
+            state.lines += 1;
+            state.observations.push(observation.clone());
+            trim(&mut state.observations);
+
+            (state.lines > MAX_OBSERVATIONS + COMPACT_AFTER).then(|| {
+                state.lines = state.observations.len();
+                state.observations.clone()
+            })
+        };
+
+        let written = match compacted {
+            Some(observations) => self.compact(&observations),
+            None => self.append(&observation),
+        };
+        if let Err(e) = written {
+            eprintln!("Warning: {}", e);
+        }
+    }
+
+    /// Observation nearest to `timestamp` at the given place, if one is close enough
+    pub fn find(&self, lat: f64, lon: f64, timestamp: i64) -> Option<WeatherData> {
+        self.lock()
+            .observations
+            .iter()
+            .filter(|o| {
+                (o.lat - lat).abs() <= MAX_DISTANCE_DEG && (o.lon - lon).abs() <= MAX_DISTANCE_DEG
+            })
+            .filter(|o| (o.timestamp - timestamp).abs() <= MAX_TIME_GAP_SECS)
+            .min_by_key(|o| (o.timestamp - timestamp).abs())
+            .map(|o| o.data.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Weather history lock poisoned")
+    }
+
+    fn append(&self, observation: &Observation) -> Result<(), AppError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut line = serde_json::to_string(observation).map_err(|e| {
+            AppError::internal(format!("Failed to serialize weather history: {}", e))
+        })?;
+        line.push('\n');
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                AppError::io(format!("Failed to create weather history directory: {}", e))
+            })?;
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| AppError::io(format!("Failed to write weather history: {}", e)))
+    }
+
+    /// Rewrite the file with only the observations kept in memory
+    fn compact(&self, observations: &[Observation]) -> Result<(), AppError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut body = String::new();
+        for observation in observations {
+            let line = serde_json::to_string(observation).map_err(|e| {
+                AppError::internal(format!("Failed to serialize weather history: {}", e))
+            })?;
+            body.push_str(&line);
+            body.push('\n');
+        }
+        persist::write_atomically(path, body.as_bytes())
+            .map_err(|e| AppError::io(format!("Failed to write weather history: {}", e)))
+    }
+}
+
+/// Observations in the file at `path`, and whether every line could be read.
+/// Files written before observations were appended hold one JSON array, and
+/// are reported as damaged so they get rewritten.
+fn load(path: &Path) -> Result<(Vec<Observation>, bool), AppError> {
+    let body = match fs::read(path) {
+        Ok(body) => String::from_utf8_lossy(&body).into_owned(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), true)),
+        Err(e) => {
+            return Err(AppError::io(format!(
+                "Failed to read weather history: {}",
+                e
+            )))
+        }
+    };
+
+    if body.trim_start().starts_with('[') {
+        return Ok(match serde_json::from_str(&body) {
+            Ok(observations) => (observations, false),
+            Err(e) => {
+                eprintln!("Warning: Discarding unreadable weather history: {}", e);
+                (Vec::new(), false)
+            }
+        });
+    }
+
+    let mut observations = Vec::new();
+    let mut skipped = 0;
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(observation) => observations.push(observation),
+            Err(_) => skipped += 1,
+        }
+    }
+    if skipped > 0 {
+        eprintln!(
+            "Warning: Skipped {} unreadable weather history entries",
+            skipped
+        );
+    }
+
+    Ok((
+        observations,
+        skipped == 0 && (body.is_empty() || body.ends_with('\n')),
+    ))
+}
+
+/// Drop the oldest observations beyond `MAX_OBSERVATIONS`, returning whether
+/// any were dropped
+fn trim(observations: &mut Vec<Observation>) -> bool {
+    if observations.len() <= MAX_OBSERVATIONS {
+        return false;
+    }
+
+    observations.sort_by_key(|o| o.timestamp);
+    let excess = observations.len() - MAX_OBSERVATIONS;
+    observations.drain(..excess);
+    true
+}
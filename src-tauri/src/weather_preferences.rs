@@ -0,0 +1,179 @@
+use crate::error::AppError;
+use crate::persist;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+static PREFERENCES: OnceLock<Preferences> = OnceLock::new();
+
+/// Units weather values are reported in, named after OpenWeatherMap's
+/// `units` parameter. Only temperature and wind speed differ between them;
+/// pressure stays in hPa, visibility in metres and precipitation in mm.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSystem {
+    #[default]
+    Metric, // °C, m/s
+    Imperial, // °F, mph
+    Standard, // K, m/s
+}
+
+impl UnitSystem {
+    /// Temperature given in `from` units, converted to these
+    pub fn temperature(self, value: f64, from: UnitSystem) -> f64 {
+        let celsius = match from {
+            Self::Metric => value,
+            Self::Imperial => (value - 32.0) * 5.0 / 9.0,
+            Self::Standard => value - 273.15,
+        };
+
+        match self {
+            Self::Metric => celsius,
+            Self::Imperial => celsius * 9.0 / 5.0 + 32.0,
+            Self::Standard => celsius + 273.15,
+        }
+    }
+
+    /// Speed given in `from` units, converted to these
+    pub fn speed(self, value: f64, from: UnitSystem) -> f64 {
+        const METRES_PER_MILE: f64 = 1609.344;
+
+        let metres_per_second = match from {
+            Self::Imperial => value * METRES_PER_MILE / 3600.0,
+            Self::Metric | Self::Standard => value,
+        };
+
+        match self {
+            Self::Imperial => metres_per_second * 3600.0 / METRES_PER_MILE,
+            Self::Metric | Self::Standard => metres_per_second,
+        }
+    }
+
+    pub fn temperature_symbol(self) -> &'static str {
+        match self {
+            Self::Metric => "°C",
+            Self::Imperial => "°F",
+            Self::Standard => "K",
+        }
+    }
+
+    pub fn speed_symbol(self) -> &'static str {
+        match self {
+            Self::Imperial => "mph",
+            Self::Metric | Self::Standard => "m/s",
+        }
+    }
+}
+
+/// How weather is presented to the user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct WeatherPreferences {
+    pub units: UnitSystem,
+    pub language: String, // Language code for condition text, e.g. "de" or "pt_br"
+}
+
+impl Default for WeatherPreferences {
+    fn default() -> Self {
+        Self {
+            units: UnitSystem::Metric,
+            language: "en".to_string(),
+        }
+    }
+}
+
+impl WeatherPreferences {
+    /// Check the language code and bring it to the lowercase, underscore
+    /// separated form providers expect
+    fn normalize(mut self) -> Result<Self, AppError> {
+        let language = self.language.trim().to_ascii_lowercase().replace('-', "_");
+        let (primary, region) = match language.split_once('_') {
+            Some((primary, region)) => (primary, Some(region)),
+            None => (language.as_str(), None),
+        };
+        let letters = |part: &str, len: std::ops::RangeInclusive<usize>| {
+            len.contains(&part.len()) && part.bytes().all(|b| b.is_ascii_lowercase())
+        };
+
+        if !letters(primary, 2..=3) || region.is_some_and(|region| !letters(region, 2..=2)) {
+            return Err(AppError::invalid_input(format!(
+                "Invalid language code: {}",
+                self.language
+            )));
+        }
+
+        self.language = language;
+        Ok(self)
+    }
+}
+
+/// Weather display preferences, persisted in the app data dir
+pub struct Preferences {
+    path: Option<PathBuf>,
+    current: Mutex<WeatherPreferences>,
+}
+
+impl Preferences {
+    /// Load the preferences file at `path`, starting from defaults if it does
+    /// not exist yet. Without a path nothing is persisted.
+    pub fn open(path: Option<PathBuf>) -> Result<Self, AppError> {
+        let current = match &path {
+            Some(path) => persist::load_json::<WeatherPreferences>(path, "weather preferences")?,
+            None => None,
+        }
+        .unwrap_or_default();
+
+        Ok(Self {
+            path,
+            current: Mutex::new(current),
+        })
+    }
+
+    pub fn init(path: PathBuf) -> Result<(), AppError> {
+        let preferences = Self::open(Some(path))?;
+        PREFERENCES
+            .set(preferences)
+            .map_err(|_| AppError::internal("Weather preferences already initialized"))
+    }
+
+    pub fn global() -> &'static Preferences {
+        PREFERENCES.get_or_init(|| {
+            Self::open(None).expect("In-memory weather preferences cannot fail to open")
+        })
+    }
+
+    pub fn get(&self) -> WeatherPreferences {
+        self.lock().clone()
+    }
+
+    pub fn units(&self) -> UnitSystem {
+        self.lock().units
+    }
+
+    pub fn language(&self) -> String {
+        self.lock().language.clone()
+    }
+
+    /// Replace the preferences, returning them as stored
+    pub fn set(&self, preferences: WeatherPreferences) -> Result<WeatherPreferences, AppError> {
+        let preferences = preferences.normalize()?;
+        let mut current = self.lock();
+        self.save(&preferences)?;
+        *current = preferences.clone();
+        Ok(preferences)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WeatherPreferences> {
+        self.current
+            .lock()
+            .expect("Weather preferences lock poisoned")
+    }
+
+    fn save(&self, preferences: &WeatherPreferences) -> Result<(), AppError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        persist::save_json(path, preferences, "weather preferences")
+    }
+}
@@ -0,0 +1,278 @@
+mod mock;
+mod open_meteo;
+mod open_weather_map;
+
+use crate::env::EnvConfig;
+use crate::error::AppError;
+use crate::http_client::HttpClient;
+use crate::persist;
+use crate::weather::{ForecastPoint, WeatherData};
+use crate::weather_preferences::Preferences;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub use mock::MockProvider;
+pub use open_meteo::OpenMeteoProvider;
+pub use open_weather_map::OpenWeatherMapProvider;
+
+static ACTIVE_PROVIDER: OnceLock<RwLock<Arc<dyn WeatherProvider>>> = OnceLock::new();
+static SETTINGS_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherProviderKind {
+    OpenWeatherMap,
+    OpenMeteo,
+    Mock,
+}
+
+impl FromStr for WeatherProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "open_weather_map" | "openweathermap" => Ok(Self::OpenWeatherMap),
+            "open_meteo" | "openmeteo" => Ok(Self::OpenMeteo),
+            "mock" => Ok(Self::Mock),
+            other => Err(format!("Unknown weather provider: {}", other)),
+        }
+    }
+}
+
+/// A single forecast step returned by a provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastSlot {
+    pub timestamp: i64,
+    pub point: ForecastPoint,
+}
+
+impl ForecastSlot {
+    /// Present a forecast step as point-in-time weather
+    pub fn into_weather_data(
+        self,
+        location: String,
+        timezone_offset: i32,
+        timezone: Option<String>,
+    ) -> WeatherData {
+        let point = self.point;
+        let date = chrono::DateTime::from_timestamp(self.timestamp, 0)
+            .map(|time| time.to_rfc3339())
+            .unwrap_or(point.date);
+
+        WeatherData {
+            temperature: point.temperature,
+            feels_like: point.feels_like,
+            humidity: point.humidity,
+            description: point.description,
+            icon: point.icon,
+            location,
+            date,
+            wind_speed: point.wind_speed,
+            wind_deg: point.wind_deg,
+            wind_gust: point.wind_gust,
+            pressure: point.pressure,
+            visibility: point.visibility,
+            clouds: point.clouds,
+            rain: point.rain,
+            snow: point.snow,
+            // Forecast slots carry no daylight or UV data
+            sunrise: None,
+            sunset: None,
+            timezone_offset,
+            timezone,
+            uv_index: None,
+            units: point.units,
+        }
+    }
+}
+
+/// Forecast series for one location
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Forecast {
+    pub location: String,
+    /// Seconds east of UTC for the forecast location
+    pub timezone_offset: i32,
+    /// IANA timezone of the location, when the provider reports it
+    #[serde(default)]
+    pub timezone: Option<String>,
+    pub slots: Vec<ForecastSlot>,
+}
+
+impl Forecast {
+    /// Hours the slot at `index` covers: until the next slot, with the last
+    /// one as long as the slot before it, or an hour when it is the only one
+    pub fn slot_hours(&self, index: usize) -> f64 {
+        let span = |from: &ForecastSlot, to: &ForecastSlot| to.timestamp - from.timestamp;
+        let secs = match (self.slots.get(index), self.slots.get(index + 1)) {
+            (Some(slot), Some(next)) => span(slot, next),
+            (Some(slot), None) if index > 0 => span(&self.slots[index - 1], slot),
+            _ => 60 * 60,
+        };
+        secs as f64 / 3600.0
+    }
+}
+
+/// Warning issued by a weather service for an area
+#[derive(Debug, Clone)]
+pub struct ProviderAlert {
+    pub event: String,
+    pub description: String,
+    pub sender: Option<String>,
+    pub start: i64,
+    pub end: Option<i64>,
+}
+
+/// Source of current conditions and forecasts
+#[async_trait]
+pub trait WeatherProvider: Send + Sync {
+    fn kind(&self) -> WeatherProviderKind;
+
+    async fn fetch_current(&self, lat: f64, lon: f64) -> Result<WeatherData, AppError>;
+
+    /// Forecast slots ordered by time, covering at least the next 5 days
+    async fn fetch_forecast(&self, lat: f64, lon: f64) -> Result<Forecast, AppError>;
+
+    /// Weather at the recorded time closest to a past `timestamp`
+    async fn fetch_historical(
+        &self,
+        _lat: f64,
+        _lon: f64,
+        _timestamp: i64,
+    ) -> Result<WeatherData, AppError> {
+        Err(AppError::not_found(
+            "Historical weather is not available from this provider",
+        ))
+    }
+
+    /// Warnings in effect or announced for the area. Providers without an
+    /// alerts feed report none.
+    async fn fetch_alerts(&self, _lat: f64, _lon: f64) -> Result<Vec<ProviderAlert>, AppError> {
+        Ok(Vec::new())
+    }
+}
+
+/// Build a provider from the environment configuration, describing
+/// conditions in the preferred language
+pub fn create(kind: WeatherProviderKind) -> Result<Arc<dyn WeatherProvider>, AppError> {
+    let config = EnvConfig::get();
+
+    match kind {
+        WeatherProviderKind::OpenWeatherMap => {
+            let api_key = config.open_weather_api_key.clone().ok_or_else(|| {
+                AppError::invalid_input("OPEN_WEATHER_API_KEY environment variable not set")
+            })?;
+            Ok(Arc::new(
+                OpenWeatherMapProvider::new(api_key, config.open_weather_base_url.clone())
+                    .with_one_call_base_url(config.open_weather_one_call_base_url.clone())
+                    .with_language(Preferences::global().language()),
+            ))
+        }
+        WeatherProviderKind::OpenMeteo => Ok(Arc::new(open_meteo(config))),
+        WeatherProviderKind::Mock => {
+            let path = config.weather_mock_file.clone().ok_or_else(|| {
+                AppError::invalid_input("WEATHER_MOCK_FILE environment variable not set")
+            })?;
+            Ok(Arc::new(MockProvider::new(path)))
+        }
+    }
+}
+
+fn open_meteo(config: &EnvConfig) -> OpenMeteoProvider {
+    OpenMeteoProvider::new(
+        config.open_meteo_base_url.clone(),
+        config.open_meteo_archive_base_url.clone(),
+    )
+    .with_language(Preferences::global().language())
+}
+
+/// Provider used when none has been selected explicitly.
+/// Falls back to Open-Meteo when no OpenWeatherMap key is available.
+fn default_kind() -> WeatherProviderKind {
+    let config = EnvConfig::get();
+
+    config
+        .weather_provider
+        .unwrap_or(if config.open_weather_api_key.is_some() {
+            WeatherProviderKind::OpenWeatherMap
+        } else {
+            WeatherProviderKind::OpenMeteo
+        })
+}
+
+fn active_slot() -> &'static RwLock<Arc<dyn WeatherProvider>> {
+    ACTIVE_PROVIDER.get_or_init(|| {
+        let provider = create(default_kind()).unwrap_or_else(|e| {
+            eprintln!("Warning: {}, falling back to Open-Meteo", e);
+            Arc::new(open_meteo(EnvConfig::get()))
+        });
+        RwLock::new(provider)
+    })
+}
+
+pub fn active() -> Arc<dyn WeatherProvider> {
+    active_slot()
+        .read()
+        .expect("Weather provider lock poisoned")
+        .clone()
+}
+
+/// On-disk layout of the provider settings file
+#[derive(Debug, Serialize, Deserialize)]
+struct ProviderSettings {
+    provider: WeatherProviderKind,
+}
+
+/// Save the provider chosen from now on to `path`, and switch to the one
+/// saved there by a previous run. A saved provider that can no longer be
+/// created, e.g. after its API key was removed, leaves the default active.
+pub fn init(path: PathBuf) -> Result<(), AppError> {
+    let saved = persist::load_json::<ProviderSettings>(&path, "weather provider")?;
+    SETTINGS_PATH
+        .set(path)
+        .map_err(|_| AppError::internal("Weather provider already initialized"))?;
+
+    if let Some(saved) = saved {
+        let provider = create(saved.provider)?;
+        *active_slot()
+            .write()
+            .expect("Weather provider lock poisoned") = provider;
+    }
+    Ok(())
+}
+
+pub fn set_active(kind: WeatherProviderKind) -> Result<(), AppError> {
+    let provider = create(kind)?;
+    let mut active = active_slot()
+        .write()
+        .expect("Weather provider lock poisoned");
+    if let Some(path) = SETTINGS_PATH.get() {
+        persist::save_json(
+            path,
+            &ProviderSettings { provider: kind },
+            "weather provider",
+        )?;
+    }
+    *active = provider;
+    Ok(())
+}
+
+/// Provider for past dates. OpenWeatherMap only serves history on paid plans,
+/// so it hands historical lookups to the Open-Meteo archive.
+pub fn historical() -> Arc<dyn WeatherProvider> {
+    let provider = active();
+
+    match provider.kind() {
+        WeatherProviderKind::OpenWeatherMap => Arc::new(open_meteo(EnvConfig::get())),
+        _ => provider,
+    }
+}
+
+/// GET `url` with the shared client and deserialize the JSON body
+async fn get_json<T: DeserializeOwned>(url: &str, what: &str) -> Result<T, AppError> {
+    HttpClient::global().get_json(url, what).await
+}
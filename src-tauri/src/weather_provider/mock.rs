@@ -0,0 +1,235 @@
+use super::{Forecast, ForecastSlot, ProviderAlert, WeatherProvider, WeatherProviderKind};
+use crate::error::AppError;
+use crate::weather::{ForecastPoint, WeatherData};
+use crate::weather_preferences::UnitSystem;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// On-disk fixture read by [`MockProvider`]
+#[derive(Debug, Deserialize)]
+struct MockFixture {
+    location: String,
+    #[serde(default)]
+    timezone_offset: i32,
+    #[serde(default)]
+    timezone: Option<String>, // IANA name
+    current: MockConditions,
+    forecast: Vec<MockSlot>,
+    /// Past observations, with negative offsets
+    #[serde(default)]
+    history: Vec<MockSlot>,
+    #[serde(default)]
+    alerts: Vec<MockAlert>,
+}
+
+/// Conditions shared by current weather and forecast slots.
+/// Only the basic fields are required; the rest default to calm, dry weather.
+#[derive(Debug, Deserialize)]
+struct MockConditions {
+    temperature: f64,
+    feels_like: f64,
+    humidity: u32,
+    description: String,
+    icon: String,
+    #[serde(default)]
+    wind_speed: f64,
+    #[serde(default)]
+    wind_deg: u32,
+    #[serde(default)]
+    wind_gust: Option<f64>,
+    #[serde(default = "default_pressure")]
+    pressure: u32,
+    #[serde(default)]
+    visibility: Option<u32>,
+    #[serde(default)]
+    clouds: u32,
+    #[serde(default)]
+    rain: f64,
+    #[serde(default)]
+    snow: f64,
+    #[serde(default)]
+    uv_index: Option<f64>,
+}
+
+fn default_pressure() -> u32 {
+    1013
+}
+
+/// Slots are relative to the current hour so fixtures never go stale
+#[derive(Debug, Deserialize)]
+struct MockSlot {
+    offset_hours: i64,
+    #[serde(flatten)]
+    conditions: MockConditions,
+    #[serde(default)]
+    pop: f64,
+}
+
+impl MockSlot {
+    fn into_forecast_slot(self) -> Result<ForecastSlot, AppError> {
+        let hour = 60 * 60;
+        let timestamp = chrono::Utc::now().timestamp() / hour * hour + self.offset_hours * hour;
+        let date = chrono::DateTime::from_timestamp(timestamp, 0)
+            .ok_or_else(|| AppError::invalid_input("Invalid mock slot offset"))?
+            .to_rfc3339();
+        let conditions = self.conditions;
+
+        Ok(ForecastSlot {
+            timestamp,
+            point: ForecastPoint {
+                date,
+                temperature: conditions.temperature,
+                feels_like: conditions.feels_like,
+                humidity: conditions.humidity,
+                wind_speed: conditions.wind_speed,
+                wind_deg: conditions.wind_deg,
+                wind_gust: conditions.wind_gust,
+                pressure: conditions.pressure,
+                clouds: conditions.clouds,
+                visibility: conditions.visibility,
+                pop: self.pop,
+                rain: conditions.rain,
+                snow: conditions.snow,
+                description: conditions.description,
+                icon: conditions.icon,
+                units: UnitSystem::Metric,
+            },
+        })
+    }
+}
+
+/// Provider warning, timed relative to the current hour like forecast slots
+#[derive(Debug, Deserialize)]
+struct MockAlert {
+    event: String,
+    description: String,
+    #[serde(default)]
+    sender: Option<String>,
+    start_offset_hours: i64,
+    #[serde(default)]
+    end_offset_hours: Option<i64>,
+}
+
+impl MockAlert {
+    fn into_provider_alert(self) -> ProviderAlert {
+        let hour = 60 * 60;
+        let base = chrono::Utc::now().timestamp() / hour * hour;
+
+        ProviderAlert {
+            event: self.event,
+            description: self.description,
+            sender: self.sender,
+            start: base + self.start_offset_hours * hour,
+            end: self.end_offset_hours.map(|offset| base + offset * hour),
+        }
+    }
+}
+
+/// Serves weather from a JSON fixture file, for offline use and tests.
+/// The file is re-read on every call so it can be edited while the app runs.
+pub struct MockProvider {
+    path: PathBuf,
+}
+
+impl MockProvider {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn load(&self) -> Result<MockFixture, AppError> {
+        let body = std::fs::read_to_string(&self.path).map_err(|e| {
+            AppError::io(format!(
+                "Failed to read mock weather file {:?}: {}",
+                self.path, e
+            ))
+        })?;
+
+        serde_json::from_str(&body)
+            .map_err(|e| AppError::io(format!("Failed to parse mock weather file: {}", e)))
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for MockProvider {
+    fn kind(&self) -> WeatherProviderKind {
+        WeatherProviderKind::Mock
+    }
+
+    async fn fetch_current(&self, _lat: f64, _lon: f64) -> Result<WeatherData, AppError> {
+        let fixture = self.load()?;
+        let current = fixture.current;
+
+        Ok(WeatherData {
+            temperature: current.temperature,
+            feels_like: current.feels_like,
+            humidity: current.humidity,
+            description: current.description,
+            icon: current.icon,
+            location: fixture.location,
+            date: chrono::Utc::now().to_rfc3339(),
+            wind_speed: current.wind_speed,
+            wind_deg: current.wind_deg,
+            wind_gust: current.wind_gust,
+            pressure: current.pressure,
+            visibility: current.visibility,
+            clouds: current.clouds,
+            rain: current.rain,
+            snow: current.snow,
+            sunrise: None,
+            sunset: None,
+            timezone_offset: fixture.timezone_offset,
+            timezone: fixture.timezone,
+            uv_index: current.uv_index,
+            units: UnitSystem::Metric,
+        })
+    }
+
+    async fn fetch_forecast(&self, _lat: f64, _lon: f64) -> Result<Forecast, AppError> {
+        let fixture = self.load()?;
+
+        let slots = fixture
+            .forecast
+            .into_iter()
+            .map(MockSlot::into_forecast_slot)
+            .collect::<Result<_, AppError>>()?;
+
+        Ok(Forecast {
+            location: fixture.location,
+            timezone_offset: fixture.timezone_offset,
+            timezone: fixture.timezone,
+            slots,
+        })
+    }
+
+    async fn fetch_historical(
+        &self,
+        _lat: f64,
+        _lon: f64,
+        timestamp: i64,
+    ) -> Result<WeatherData, AppError> {
+        let fixture = self.load()?;
+
+        let slots = fixture
+            .history
+            .into_iter()
+            .map(MockSlot::into_forecast_slot)
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        Ok(slots
+            .into_iter()
+            .min_by_key(|slot| (slot.timestamp - timestamp).abs())
+            .ok_or_else(|| AppError::not_found("Mock weather file has no history"))?
+            .into_weather_data(fixture.location, fixture.timezone_offset, fixture.timezone))
+    }
+
+    async fn fetch_alerts(&self, _lat: f64, _lon: f64) -> Result<Vec<ProviderAlert>, AppError> {
+        let fixture = self.load()?;
+
+        Ok(fixture
+            .alerts
+            .into_iter()
+            .map(MockAlert::into_provider_alert)
+            .collect())
+    }
+}
@@ -0,0 +1,464 @@
+use super::{get_json, Forecast, ForecastSlot, WeatherProvider, WeatherProviderKind};
+use crate::error::AppError;
+use crate::weather::{ForecastPoint, WeatherData};
+use crate::weather_preferences::UnitSystem;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+const CURRENT_VARIABLES: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,\
+weather_code,is_day,wind_speed_10m,wind_direction_10m,wind_gusts_10m,pressure_msl,cloud_cover,\
+visibility,rain,snowfall,uv_index";
+
+const HOURLY_VARIABLES: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,\
+weather_code,is_day,wind_speed_10m,wind_direction_10m,wind_gusts_10m,pressure_msl,cloud_cover,\
+visibility,precipitation_probability,rain,snowfall";
+
+/// The archive has no visibility or precipitation probability
+const HISTORICAL_VARIABLES: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,\
+weather_code,is_day,wind_speed_10m,wind_direction_10m,wind_gusts_10m,pressure_msl,cloud_cover,\
+rain,snowfall";
+
+/// How far back the forecast API serves past days. Older dates go to the
+/// archive, which in turn lags a few days behind.
+const FORECAST_API_PAST_DAYS: i64 = 90;
+
+#[derive(Debug, Deserialize)]
+struct CurrentResponse {
+    #[serde(default)]
+    utc_offset_seconds: i32,
+    timezone: Option<String>,
+    current: Current,
+    daily: Option<Daily>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Current {
+    temperature_2m: f64,
+    apparent_temperature: f64,
+    relative_humidity_2m: f64,
+    weather_code: u32,
+    is_day: u8,
+    #[serde(default)]
+    wind_speed_10m: f64,
+    #[serde(default)]
+    wind_direction_10m: f64,
+    wind_gusts_10m: Option<f64>,
+    #[serde(default)]
+    pressure_msl: f64,
+    #[serde(default)]
+    cloud_cover: f64,
+    visibility: Option<f64>,
+    #[serde(default)]
+    rain: f64,
+    #[serde(default)]
+    snowfall: f64,
+    uv_index: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Daily {
+    sunrise: Vec<i64>,
+    sunset: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastResponse {
+    #[serde(default)]
+    utc_offset_seconds: i32,
+    timezone: Option<String>,
+    hourly: Hourly,
+    daily: Option<Daily>,
+}
+
+/// Hourly series. Any value may be `null` where the model has no data,
+/// which is common in past days and the archive.
+#[derive(Debug, Deserialize)]
+struct Hourly {
+    time: Vec<i64>,
+    temperature_2m: Vec<Option<f64>>,
+    apparent_temperature: Vec<Option<f64>>,
+    relative_humidity_2m: Vec<Option<f64>>,
+    weather_code: Vec<Option<u32>>,
+    is_day: Vec<Option<u8>>,
+    #[serde(default)]
+    wind_speed_10m: Vec<Option<f64>>,
+    #[serde(default)]
+    wind_direction_10m: Vec<Option<f64>>,
+    #[serde(default)]
+    wind_gusts_10m: Vec<Option<f64>>,
+    #[serde(default)]
+    pressure_msl: Vec<Option<f64>>,
+    #[serde(default)]
+    cloud_cover: Vec<Option<f64>>,
+    #[serde(default)]
+    visibility: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_probability: Vec<Option<f64>>,
+    #[serde(default)]
+    rain: Vec<Option<f64>>,
+    #[serde(default)]
+    snowfall: Vec<Option<f64>>,
+}
+
+impl Hourly {
+    /// Point for hour `i`, or `None` when its temperature, humidity or
+    /// conditions are missing. Other missing values read as zero or unknown.
+    fn to_forecast_point(&self, i: usize, language: &str) -> Option<ForecastPoint> {
+        let get = |series: &[Option<f64>]| series.get(i).copied().flatten();
+        let value = |series: &[Option<f64>]| get(series).unwrap_or_default();
+        let (description, icon) = describe_weather_code(
+            self.weather_code.get(i).copied().flatten()?,
+            self.is_day.get(i).copied().flatten().unwrap_or(1) != 0,
+            language,
+        );
+
+        Some(ForecastPoint {
+            date: chrono::DateTime::from_timestamp(*self.time.get(i)?, 0)?.to_rfc3339(),
+            temperature: get(&self.temperature_2m)?,
+            feels_like: get(&self.apparent_temperature)?,
+            humidity: get(&self.relative_humidity_2m)?.round() as u32,
+            wind_speed: value(&self.wind_speed_10m),
+            wind_deg: value(&self.wind_direction_10m).round() as u32,
+            wind_gust: get(&self.wind_gusts_10m),
+            pressure: value(&self.pressure_msl).round() as u32,
+            clouds: value(&self.cloud_cover).round() as u32,
+            visibility: get(&self.visibility).map(|v| v.round() as u32),
+            pop: value(&self.precipitation_probability) / 100.0,
+            rain: value(&self.rain),
+            snow: snowfall_to_mm(value(&self.snowfall)),
+            description: description.to_string(),
+            icon,
+            units: UnitSystem::Metric,
+        })
+    }
+}
+
+/// Open-Meteo backend. Free to use without an API key.
+pub struct OpenMeteoProvider {
+    base_url: String,
+    archive_base_url: String,
+    language: String,
+}
+
+impl OpenMeteoProvider {
+    pub fn new(base_url: String, archive_base_url: String) -> Self {
+        Self {
+            base_url,
+            archive_base_url,
+            language: "en".to_string(),
+        }
+    }
+
+    /// Describe conditions in `language`, falling back to English for
+    /// languages without a translation
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = language.into();
+        self
+    }
+}
+
+/// Centimetres of fresh snow per 10 mm of water, the ratio Open-Meteo itself
+/// derives snowfall with
+const SNOW_CM_PER_10_MM_WATER: f64 = 7.0;
+
+/// Snowfall is reported as cm of fresh snow; convert it to mm of water
+/// equivalent, the unit OpenWeatherMap reports snow in
+fn snowfall_to_mm(cm: f64) -> f64 {
+    cm * 10.0 / SNOW_CM_PER_10_MM_WATER
+}
+
+fn to_rfc3339(timestamp: Option<&i64>) -> Option<String> {
+    Some(chrono::DateTime::from_timestamp(*timestamp?, 0)?.to_rfc3339())
+}
+
+/// Open-Meteo has no place names, so label results by coordinates
+fn location_label(lat: f64, lon: f64) -> String {
+    format!("{:.2}, {:.2}", lat, lon)
+}
+
+/// Map a WMO weather interpretation code to a description and an
+/// OpenWeatherMap-style icon code, so the frontend can render either provider
+fn describe_weather_code(code: u32, is_day: bool, language: &str) -> (&'static str, String) {
+    let (description, icon) = match code {
+        0 => ("clear sky", "01"),
+        1 => ("mainly clear", "02"),
+        2 => ("partly cloudy", "03"),
+        3 => ("overcast", "04"),
+        45 | 48 => ("fog", "50"),
+        51 | 53 | 55 => ("drizzle", "09"),
+        56 | 57 => ("freezing drizzle", "09"),
+        61 | 63 | 65 => ("rain", "10"),
+        66 | 67 => ("freezing rain", "13"),
+        71 | 73 | 75 => ("snow", "13"),
+        77 => ("snow grains", "13"),
+        80..=82 => ("rain showers", "09"),
+        85 | 86 => ("snow showers", "13"),
+        95 => ("thunderstorm", "11"),
+        96 | 99 => ("thunderstorm with hail", "11"),
+        _ => ("Unknown", "01"),
+    };
+
+    (
+        translate(description, language),
+        format!("{}{}", icon, if is_day { "d" } else { "n" }),
+    )
+}
+
+/// Languages condition text is translated to, in the column order of `TRANSLATIONS`
+const LANGUAGES: [&str; 5] = ["de", "es", "fr", "it", "pl"];
+
+/// Open-Meteo only reports weather codes, so descriptions are translated here
+const TRANSLATIONS: &[(&str, [&str; 5])] = &[
+    (
+        "clear sky",
+        [
+            "klarer Himmel",
+            "cielo despejado",
+            "ciel dégagé",
+            "cielo sereno",
+            "bezchmurnie",
+        ],
+    ),
+    (
+        "mainly clear",
+        [
+            "überwiegend klar",
+            "mayormente despejado",
+            "généralement dégagé",
+            "prevalentemente sereno",
+            "przeważnie bezchmurnie",
+        ],
+    ),
+    (
+        "partly cloudy",
+        [
+            "teilweise bewölkt",
+            "parcialmente nublado",
+            "partiellement nuageux",
+            "parzialmente nuvoloso",
+            "częściowe zachmurzenie",
+        ],
+    ),
+    (
+        "overcast",
+        ["bedeckt", "cubierto", "couvert", "coperto", "pochmurno"],
+    ),
+    ("fog", ["Nebel", "niebla", "brouillard", "nebbia", "mgła"]),
+    (
+        "drizzle",
+        ["Nieselregen", "llovizna", "bruine", "pioviggine", "mżawka"],
+    ),
+    (
+        "freezing drizzle",
+        [
+            "gefrierender Nieselregen",
+            "llovizna helada",
+            "bruine verglaçante",
+            "pioviggine gelata",
+            "marznąca mżawka",
+        ],
+    ),
+    ("rain", ["Regen", "lluvia", "pluie", "pioggia", "deszcz"]),
+    (
+        "freezing rain",
+        [
+            "gefrierender Regen",
+            "lluvia helada",
+            "pluie verglaçante",
+            "pioggia gelata",
+            "marznący deszcz",
+        ],
+    ),
+    ("snow", ["Schnee", "nieve", "neige", "neve", "śnieg"]),
+    (
+        "snow grains",
+        [
+            "Schneegriesel",
+            "cinarra",
+            "neige en grains",
+            "neve granulosa",
+            "śnieg ziarnisty",
+        ],
+    ),
+    (
+        "rain showers",
+        [
+            "Regenschauer",
+            "chubascos",
+            "averses de pluie",
+            "rovesci di pioggia",
+            "przelotne opady deszczu",
+        ],
+    ),
+    (
+        "snow showers",
+        [
+            "Schneeschauer",
+            "chubascos de nieve",
+            "averses de neige",
+            "rovesci di neve",
+            "przelotne opady śniegu",
+        ],
+    ),
+    (
+        "thunderstorm",
+        ["Gewitter", "tormenta", "orage", "temporale", "burza"],
+    ),
+    (
+        "thunderstorm with hail",
+        [
+            "Gewitter mit Hagel",
+            "tormenta con granizo",
+            "orage avec grêle",
+            "temporale con grandine",
+            "burza z gradem",
+        ],
+    ),
+];
+
+/// English `description` in `language`, matched on the primary language
+/// so "pt_br" and "pt" share a column
+fn translate(description: &'static str, language: &str) -> &'static str {
+    let primary = language.split('_').next().unwrap_or(language);
+    let Some(column) = LANGUAGES.iter().position(|code| *code == primary) else {
+        return description;
+    };
+
+    TRANSLATIONS
+        .iter()
+        .find(|(english, _)| *english == description)
+        .map_or(description, |(_, translated)| translated[column])
+}
+
+#[async_trait]
+impl WeatherProvider for OpenMeteoProvider {
+    fn kind(&self) -> WeatherProviderKind {
+        WeatherProviderKind::OpenMeteo
+    }
+
+    async fn fetch_current(&self, lat: f64, lon: f64) -> Result<WeatherData, AppError> {
+        let url = format!(
+            "{}/forecast?latitude={}&longitude={}&current={}&daily=sunrise,sunset&forecast_days=1&wind_speed_unit=ms&timezone=auto&timeformat=unixtime",
+            self.base_url, lat, lon, CURRENT_VARIABLES
+        );
+
+        let data: CurrentResponse = get_json(&url, "weather").await?;
+        let current = data.current;
+        let (description, icon) =
+            describe_weather_code(current.weather_code, current.is_day != 0, &self.language);
+
+        Ok(WeatherData {
+            temperature: current.temperature_2m,
+            feels_like: current.apparent_temperature,
+            humidity: current.relative_humidity_2m.round() as u32,
+            description: description.to_string(),
+            icon,
+            location: location_label(lat, lon),
+            date: chrono::Utc::now().to_rfc3339(),
+            wind_speed: current.wind_speed_10m,
+            wind_deg: current.wind_direction_10m.round() as u32,
+            wind_gust: current.wind_gusts_10m,
+            pressure: current.pressure_msl.round() as u32,
+            visibility: current.visibility.map(|v| v.round() as u32),
+            clouds: current.cloud_cover.round() as u32,
+            rain: current.rain,
+            snow: snowfall_to_mm(current.snowfall),
+            sunrise: to_rfc3339(data.daily.as_ref().and_then(|d| d.sunrise.first())),
+            sunset: to_rfc3339(data.daily.as_ref().and_then(|d| d.sunset.first())),
+            timezone_offset: data.utc_offset_seconds,
+            timezone: data.timezone,
+            uv_index: current.uv_index,
+            units: UnitSystem::Metric,
+        })
+    }
+
+    async fn fetch_forecast(&self, lat: f64, lon: f64) -> Result<Forecast, AppError> {
+        let url = format!(
+            "{}/forecast?latitude={}&longitude={}&hourly={}&forecast_days=6&wind_speed_unit=ms&timezone=auto&timeformat=unixtime",
+            self.base_url, lat, lon, HOURLY_VARIABLES
+        );
+
+        let data: ForecastResponse = get_json(&url, "forecast").await?;
+        let timezone_offset = data.utc_offset_seconds;
+        let hourly = data.hourly;
+
+        let slots = hourly
+            .time
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &timestamp)| {
+                Some(ForecastSlot {
+                    timestamp,
+                    point: hourly.to_forecast_point(i, &self.language)?,
+                })
+            })
+            .collect();
+
+        Ok(Forecast {
+            location: location_label(lat, lon),
+            timezone_offset,
+            timezone: data.timezone,
+            slots,
+        })
+    }
+
+    async fn fetch_historical(
+        &self,
+        lat: f64,
+        lon: f64,
+        timestamp: i64,
+    ) -> Result<WeatherData, AppError> {
+        let target = chrono::DateTime::from_timestamp(timestamp, 0)
+            .ok_or_else(|| AppError::invalid_input("Invalid date"))?;
+        let endpoint = if (chrono::Utc::now() - target).num_days() > FORECAST_API_PAST_DAYS {
+            format!("{}/archive", self.archive_base_url)
+        } else {
+            format!("{}/forecast", self.base_url)
+        };
+
+        // Request the neighbouring days too, since the range is in local time
+        let day = chrono::Duration::days(1);
+        let url = format!(
+            "{}?latitude={}&longitude={}&hourly={}&daily=sunrise,sunset&start_date={}&end_date={}&wind_speed_unit=ms&timezone=auto&timeformat=unixtime",
+            endpoint,
+            lat,
+            lon,
+            HISTORICAL_VARIABLES,
+            (target - day).format("%Y-%m-%d"),
+            (target + day).format("%Y-%m-%d"),
+        );
+
+        let data: ForecastResponse = get_json(&url, "historical weather").await?;
+        let hourly = &data.hourly;
+
+        // Closest hour with complete readings
+        let slot = hourly
+            .time
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &time)| {
+                Some(ForecastSlot {
+                    timestamp: time,
+                    point: hourly.to_forecast_point(i, &self.language)?,
+                })
+            })
+            .min_by_key(|slot| (slot.timestamp - timestamp).abs())
+            .ok_or_else(|| AppError::not_found("No historical data available"))?;
+
+        let mut weather_data = slot.into_weather_data(
+            location_label(lat, lon),
+            data.utc_offset_seconds,
+            data.timezone.clone(),
+        );
+
+        // Daylight for the day closest to the requested time
+        if let Some(daily) = &data.daily {
+            let day_index =
+                (0..daily.sunrise.len()).min_by_key(|&i| (daily.sunrise[i] - timestamp).abs());
+            weather_data.sunrise = to_rfc3339(day_index.and_then(|i| daily.sunrise.get(i)));
+            weather_data.sunset = to_rfc3339(day_index.and_then(|i| daily.sunset.get(i)));
+        }
+
+        Ok(weather_data)
+    }
+}
@@ -0,0 +1,276 @@
+use super::{
+    get_json, Forecast, ForecastSlot, ProviderAlert, WeatherProvider, WeatherProviderKind,
+};
+use crate::error::AppError;
+use crate::weather::{ForecastPoint, WeatherData};
+use crate::weather_preferences::UnitSystem;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct OpenWeatherResponse {
+    main: Main,
+    weather: Vec<Weather>,
+    wind: Wind,
+    clouds: Clouds,
+    visibility: Option<u32>,
+    rain: Option<Volume>,
+    snow: Option<Volume>,
+    sys: Sys,
+    #[serde(default)]
+    timezone: i32,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Main {
+    temp: f64,
+    feels_like: f64,
+    humidity: u32,
+    pressure: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Clouds {
+    all: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct Sys {
+    sunrise: Option<i64>,
+    sunset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Weather {
+    description: String,
+    icon: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastResponse {
+    list: Vec<ForecastItem>,
+    city: City,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastItem {
+    dt: i64,
+    main: Main,
+    weather: Vec<Weather>,
+    wind: Wind,
+    clouds: Clouds,
+    visibility: Option<u32>,
+    #[serde(default)]
+    pop: f64,
+    rain: Option<Volume>,
+    snow: Option<Volume>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Wind {
+    speed: f64,
+    deg: u32,
+    gust: Option<f64>,
+}
+
+/// Precipitation volume in mm. Current conditions report the last hour,
+/// forecast items the last 3 hours.
+#[derive(Debug, Deserialize)]
+struct Volume {
+    #[serde(rename = "1h", default)]
+    one_hour: f64,
+    #[serde(rename = "3h", default)]
+    three_hours: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct City {
+    name: String,
+    #[serde(default)]
+    timezone: i32,
+}
+
+/// One Call response with everything but alerts excluded
+#[derive(Debug, Deserialize)]
+struct OneCallResponse {
+    #[serde(default)]
+    alerts: Vec<OneCallAlert>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OneCallAlert {
+    sender_name: Option<String>,
+    event: String,
+    start: i64,
+    end: Option<i64>,
+    #[serde(default)]
+    description: String,
+}
+
+pub struct OpenWeatherMapProvider {
+    api_key: String,
+    base_url: String,
+    one_call_base_url: String,
+    language: String,
+}
+
+impl OpenWeatherMapProvider {
+    pub fn new(api_key: String, base_url: String) -> Self {
+        Self {
+            api_key,
+            one_call_base_url: base_url.clone(),
+            base_url,
+            language: "en".to_string(),
+        }
+    }
+
+    /// Serve alerts from One Call at `base_url`, which is versioned apart
+    /// from the current weather and forecast endpoints
+    pub fn with_one_call_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.one_call_base_url = base_url.into();
+        self
+    }
+
+    /// Ask for condition text in `language`, one of OpenWeatherMap's `lang` codes
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = language.into();
+        self
+    }
+}
+
+fn condition(weather: &[Weather]) -> (String, String) {
+    (
+        weather
+            .first()
+            .map(|w| w.description.clone())
+            .unwrap_or_else(|| "Unknown".to_string()),
+        weather
+            .first()
+            .map(|w| w.icon.clone())
+            .unwrap_or_else(|| "01d".to_string()),
+    )
+}
+
+fn to_rfc3339(timestamp: Option<i64>) -> Option<String> {
+    Some(chrono::DateTime::from_timestamp(timestamp?, 0)?.to_rfc3339())
+}
+
+fn to_forecast_point(item: &ForecastItem) -> Option<ForecastPoint> {
+    let (description, icon) = condition(&item.weather);
+
+    Some(ForecastPoint {
+        date: chrono::DateTime::from_timestamp(item.dt, 0)?.to_rfc3339(),
+        temperature: item.main.temp,
+        feels_like: item.main.feels_like,
+        humidity: item.main.humidity,
+        wind_speed: item.wind.speed,
+        wind_deg: item.wind.deg,
+        wind_gust: item.wind.gust,
+        pressure: item.main.pressure,
+        clouds: item.clouds.all,
+        visibility: item.visibility,
+        pop: item.pop,
+        // Forecast volumes cover the 3 hour slot; report them as hourly rates
+        rain: item.rain.as_ref().map_or(0.0, |v| v.three_hours / 3.0),
+        snow: item.snow.as_ref().map_or(0.0, |v| v.three_hours / 3.0),
+        description,
+        icon,
+        units: UnitSystem::Metric,
+    })
+}
+
+#[async_trait]
+impl WeatherProvider for OpenWeatherMapProvider {
+    fn kind(&self) -> WeatherProviderKind {
+        WeatherProviderKind::OpenWeatherMap
+    }
+
+    async fn fetch_current(&self, lat: f64, lon: f64) -> Result<WeatherData, AppError> {
+        let url = format!(
+            "{}/weather?lat={}&lon={}&appid={}&units=metric&lang={}",
+            self.base_url, lat, lon, self.api_key, self.language
+        );
+
+        let data: OpenWeatherResponse = get_json(&url, "weather").await?;
+        let (description, icon) = condition(&data.weather);
+
+        Ok(WeatherData {
+            temperature: data.main.temp,
+            feels_like: data.main.feels_like,
+            humidity: data.main.humidity,
+            description,
+            icon,
+            location: data.name,
+            date: chrono::Utc::now().to_rfc3339(),
+            wind_speed: data.wind.speed,
+            wind_deg: data.wind.deg,
+            wind_gust: data.wind.gust,
+            pressure: data.main.pressure,
+            visibility: data.visibility,
+            clouds: data.clouds.all,
+            rain: data.rain.as_ref().map_or(0.0, |v| v.one_hour),
+            snow: data.snow.as_ref().map_or(0.0, |v| v.one_hour),
+            sunrise: to_rfc3339(data.sys.sunrise),
+            sunset: to_rfc3339(data.sys.sunset),
+            timezone_offset: data.timezone,
+            // Only the UTC offset is reported
+            timezone: None,
+            // Not part of the 2.5 current weather endpoint
+            uv_index: None,
+            units: UnitSystem::Metric,
+        })
+    }
+
+    async fn fetch_forecast(&self, lat: f64, lon: f64) -> Result<Forecast, AppError> {
+        let url = format!(
+            "{}/forecast?lat={}&lon={}&appid={}&units=metric&lang={}",
+            self.base_url, lat, lon, self.api_key, self.language
+        );
+
+        let data: ForecastResponse = get_json(&url, "forecast").await?;
+
+        Ok(Forecast {
+            location: data.city.name,
+            timezone_offset: data.city.timezone,
+            timezone: None,
+            slots: data
+                .list
+                .iter()
+                .filter_map(|item| {
+                    Some(ForecastSlot {
+                        timestamp: item.dt,
+                        point: to_forecast_point(item)?,
+                    })
+                })
+                .collect(),
+        })
+    }
+
+    async fn fetch_alerts(&self, lat: f64, lon: f64) -> Result<Vec<ProviderAlert>, AppError> {
+        let url = format!(
+            "{}/onecall?lat={}&lon={}&exclude=current,minutely,hourly,daily&appid={}&lang={}",
+            self.one_call_base_url, lat, lon, self.api_key, self.language
+        );
+
+        let data: OneCallResponse = match get_json(&url, "weather alerts").await {
+            Ok(data) => data,
+            // One Call needs its own subscription; without it only threshold
+            // alerts apply
+            Err(AppError::Upstream { status: 401, .. }) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        Ok(data
+            .alerts
+            .into_iter()
+            .map(|alert| ProviderAlert {
+                event: alert.event,
+                description: alert.description.trim().to_string(),
+                sender: alert.sender_name.filter(|sender| !sender.is_empty()),
+                start: alert.start,
+                end: alert.end,
+            })
+            .collect())
+    }
+}
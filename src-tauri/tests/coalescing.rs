@@ -0,0 +1,102 @@
+mod common;
+
+use command_center_4_lib::env::EnvConfig;
+use command_center_4_lib::weather;
+use common::{FixtureServer, Route};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// OpenWeatherMap stand-in that answers slowly enough for calls to overlap
+fn configured_server() -> &'static FixtureServer {
+    static SERVER: OnceLock<FixtureServer> = OnceLock::new();
+
+    SERVER.get_or_init(|| {
+        let delay = Duration::from_millis(300);
+        let server = FixtureServer::start(vec![
+            Route::fixture("/weather", "openweather_current.json").delayed(delay),
+            Route::fixture("/forecast", "openweather_forecast.json").delayed(delay),
+            Route::status("/archive", 400, "{}").delayed(delay),
+        ]);
+        std::env::set_var("WEATHER_PROVIDER", "open_weather_map");
+        std::env::set_var("OPEN_WEATHER_API_KEY", "test-key");
+        std::env::set_var("OPEN_WEATHER_BASE_URL", server.base_url());
+        std::env::set_var("OPEN_METEO_ARCHIVE_BASE_URL", server.base_url());
+        EnvConfig::init().expect("Failed to initialize EnvConfig");
+        server
+    })
+}
+
+/// Upstream requests made for a location, matched on its latitude
+fn requests_for(server: &FixtureServer, path: &str, lat: &str) -> usize {
+    server
+        .requests()
+        .iter()
+        .filter(|target| target.split('?').next() == Some(path))
+        .filter(|target| target.contains(&format!("lat={}", lat)))
+        .count()
+}
+
+#[tokio::test]
+async fn concurrent_current_weather_calls_share_one_request() {
+    let server = configured_server();
+
+    let calls = (0..5).map(|_| tokio::spawn(weather::fetch_weather(52.2297, 21.0122)));
+    let mut responses = Vec::new();
+    for call in calls.collect::<Vec<_>>() {
+        responses.push(call.await.unwrap().unwrap());
+    }
+
+    assert!(responses.iter().all(|r| r.data.location == "Kraków"));
+    assert_eq!(requests_for(server, "/weather", "52.2297"), 1);
+}
+
+#[tokio::test]
+async fn nearby_locations_in_one_cell_share_one_request() {
+    let server = configured_server();
+
+    let (first, second) = tokio::join!(
+        weather::fetch_weather(48.8566, 2.3522),
+        weather::fetch_weather(48.8567, 2.3523),
+    );
+
+    assert_eq!(first.unwrap().data.location, "Kraków");
+    assert_eq!(second.unwrap().data.location, "Kraków");
+    let total =
+        requests_for(server, "/weather", "48.8566") + requests_for(server, "/weather", "48.8567");
+    assert_eq!(total, 1);
+}
+
+#[tokio::test]
+async fn forecast_commands_share_one_download() {
+    let server = configured_server();
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let (points, days, at_date) = tokio::join!(
+        weather::fetch_forecast(45.4642, 9.19),
+        weather::fetch_daily_forecast(45.4642, 9.19),
+        weather::fetch_forecast_for_date(45.4642, 9.19, &now),
+    );
+
+    assert_eq!(points.unwrap().data.len(), 8);
+    assert_eq!(days.unwrap().data.len(), 2);
+    at_date.unwrap();
+    assert_eq!(requests_for(server, "/forecast", "45.4642"), 1);
+}
+
+#[tokio::test]
+async fn waiting_callers_share_the_error() {
+    let server = configured_server();
+    let date = "2024-01-15T12:00:00Z";
+
+    let (first, second) = tokio::join!(
+        weather::fetch_forecast_for_date(41.9028, 12.4964, date),
+        weather::fetch_forecast_for_date(41.9028, 12.4964, date),
+    );
+
+    assert_eq!(first.unwrap_err(), second.unwrap_err());
+    assert_eq!(server.request_count("/archive"), 1);
+
+    // The failed flight is retired, so a later call tries again
+    let _ = weather::fetch_forecast_for_date(41.9028, 12.4964, date).await;
+    assert_eq!(server.request_count("/archive"), 2);
+}
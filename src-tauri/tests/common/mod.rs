@@ -0,0 +1,245 @@
+//! Local HTTP stand-in for the external weather and geolocation services.
+//!
+//! The server answers each route with a recorded JSON fixture from
+//! `tests/fixtures` and records every request target it receives.
+
+#![allow(dead_code)]
+
+use command_center_4_lib::weather::WeatherData;
+use command_center_4_lib::weather_preferences::UnitSystem;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Missing fixture {}: {}", path, e))
+}
+
+/// Plain weather reading for tests that only care about a few fields
+pub fn sample_weather() -> WeatherData {
+    WeatherData {
+        temperature: 15.0,
+        feels_like: 14.0,
+        humidity: 60,
+        description: "clear sky".to_string(),
+        icon: "01d".to_string(),
+        location: "Testville".to_string(),
+        date: "2024-01-15 12:00:00".to_string(),
+        wind_speed: 2.0,
+        wind_deg: 180,
+        wind_gust: None,
+        pressure: 1013,
+        visibility: Some(10000),
+        clouds: 0,
+        rain: 0.0,
+        snow: 0.0,
+        sunrise: None,
+        sunset: None,
+        timezone_offset: 0,
+        timezone: None,
+        uv_index: None,
+        units: UnitSystem::Metric,
+    }
+}
+
+#[derive(Clone)]
+pub struct Route {
+    path: String,
+    status: u16,
+    body: String,
+    delay: Duration,
+    headers: Vec<(String, String)>,
+    remaining: Option<Arc<AtomicUsize>>, // Uses left, unlimited when unset
+    hang_up: bool,                       // Close the connection without answering
+}
+
+impl Route {
+    /// Serve a fixture file with `200 OK`
+    pub fn fixture(path: &str, name: &str) -> Self {
+        Self::status(path, 200, fixture(name))
+    }
+
+    pub fn status(path: &str, status: u16, body: impl Into<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            status,
+            body: body.into(),
+            delay: Duration::ZERO,
+            headers: Vec::new(),
+            remaining: None,
+            hang_up: false,
+        }
+    }
+
+    /// Close the connection after reading the request, as a reset
+    /// connection would
+    pub fn hang_up(path: &str) -> Self {
+        Self {
+            hang_up: true,
+            ..Self::status(path, 0, "")
+        }
+    }
+
+    /// Hold the response back, so concurrent requests overlap
+    pub fn delayed(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Answer only the first `n` matching requests, leaving later ones to
+    /// the next route for the same path
+    pub fn times(mut self, n: usize) -> Self {
+        self.remaining = Some(Arc::new(AtomicUsize::new(n)));
+        self
+    }
+
+    fn claim(&self) -> bool {
+        self.remaining.as_ref().is_none_or(|remaining| {
+            remaining
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+        })
+    }
+}
+
+pub struct FixtureServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<String>>>,
+    user_agents: Arc<Mutex<Vec<String>>>,
+}
+
+impl FixtureServer {
+    pub fn start(routes: Vec<Route>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind fixture server");
+        let addr = listener
+            .local_addr()
+            .expect("Fixture server has no address");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let user_agents = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&requests);
+        let agents = Arc::clone(&user_agents);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let routes = routes.clone();
+                let recorded = Arc::clone(&recorded);
+                let agents = Arc::clone(&agents);
+                thread::spawn(move || handle(stream, &routes, &recorded, &agents));
+            }
+        });
+
+        Self {
+            addr,
+            requests,
+            user_agents,
+        }
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Request targets (path and query) received so far
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// `User-Agent` headers received so far, in request order
+    pub fn user_agents(&self) -> Vec<String> {
+        self.user_agents.lock().unwrap().clone()
+    }
+
+    pub fn request_count(&self, path: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|target| target.split('?').next() == Some(path))
+            .count()
+    }
+}
+
+fn handle(
+    mut stream: TcpStream,
+    routes: &[Route],
+    recorded: &Mutex<Vec<String>>,
+    user_agents: &Mutex<Vec<String>>,
+) {
+    let mut reader = BufReader::new(stream.try_clone().expect("Failed to clone stream"));
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+
+    // Drain headers; requests from the services under test carry no body
+    let mut line = String::new();
+    while reader.read_line(&mut line).map(|n| n > 2).unwrap_or(false) {
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("user-agent") {
+                user_agents.lock().unwrap().push(value.trim().to_string());
+            }
+        }
+        line.clear();
+    }
+
+    let target = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or("/")
+        .to_string();
+    recorded.lock().unwrap().push(target.clone());
+
+    let path = target.split('?').next().unwrap_or("/");
+    let route = routes
+        .iter()
+        .find(|route| route.path == path && route.claim());
+    if route.is_some_and(|route| route.hang_up) {
+        let _ = stream.shutdown(std::net::Shutdown::Both);
+        return;
+    }
+    let (status, body, delay) = route
+        .map(|route| (route.status, route.body.clone(), route.delay))
+        .unwrap_or((
+            404,
+            r#"{"message":"not found"}"#.to_string(),
+            Duration::ZERO,
+        ));
+    let headers: String = route
+        .map(|route| &route.headers[..])
+        .unwrap_or_default()
+        .iter()
+        .map(|(name, value)| format!("{}: {}\r\n", name, value))
+        .collect();
+    thread::sleep(delay);
+
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        headers,
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
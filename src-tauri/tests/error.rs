@@ -0,0 +1,33 @@
+use command_center_4_lib::error::AppError;
+use serde_json::json;
+
+#[test]
+fn errors_serialize_as_tagged_union() {
+    let value = serde_json::to_value(AppError::not_found("Image 'a.png' not found")).unwrap();
+    assert_eq!(
+        value,
+        json!({ "kind": "NotFound", "message": "Image 'a.png' not found" })
+    );
+
+    let value = serde_json::to_value(AppError::upstream(502, "Weather API error")).unwrap();
+    assert_eq!(
+        value,
+        json!({ "kind": "Upstream", "status": 502, "message": "Weather API error" })
+    );
+
+    let value = serde_json::to_value(AppError::rate_limited(Some(30), "Slow down")).unwrap();
+    assert_eq!(
+        value,
+        json!({ "kind": "RateLimited", "retry_after_secs": 30, "message": "Slow down" })
+    );
+}
+
+#[test]
+fn errors_round_trip_and_display_their_message() {
+    let error = AppError::model_unavailable("RMBG model not initialized");
+
+    let parsed: AppError = serde_json::from_str(&serde_json::to_string(&error).unwrap()).unwrap();
+
+    assert_eq!(parsed, error);
+    assert_eq!(error.to_string(), "RMBG model not initialized");
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::geocoding::{
+    Geocoder, GeocodingProvider, OfflineGeocoder, OpenMeteoGeocoder,
+};
+use common::{FixtureServer, Route};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The city list bundled with the app
+fn bundled_cities() -> OfflineGeocoder {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/cities.json");
+    OfflineGeocoder::load(&path).unwrap()
+}
+
+/// Base URL with nothing listening behind it
+fn unreachable_base_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{}", addr)
+}
+
+fn names(places: &[command_center_4_lib::geocoding::Place]) -> Vec<&str> {
+    places.iter().map(|place| place.name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn offline_search_ignores_case_and_diacritics() {
+    let cities = bundled_cities();
+
+    for query in ["Kraków", "krakow", "KRAKÓW", "Cracow"] {
+        let places = cities.geocode(query).await.unwrap();
+        assert_eq!(names(&places).first(), Some(&"Kraków"), "query {}", query);
+    }
+
+    let krakow = &cities.geocode("krakow").await.unwrap()[0];
+    assert_eq!(krakow.country, "Poland");
+    assert_eq!(krakow.country_code.as_deref(), Some("PL"));
+    assert_eq!(krakow.admin_area.as_deref(), Some("Lesser Poland"));
+    assert_eq!(krakow.timezone, "Europe/Warsaw");
+}
+
+#[tokio::test]
+async fn offline_search_ranks_exact_then_prefix_then_population() {
+    let cities = bundled_cities();
+
+    // Exact "San Jose" beats the more populous "San Antonio" prefix match
+    let places = cities.geocode("san jose").await.unwrap();
+    assert_eq!(names(&places)[..2], ["San Jose", "San José"]);
+
+    // Among prefix matches the larger city comes first
+    let places = cities.geocode("san").await.unwrap();
+    let position = |name| names(&places).iter().position(|n| *n == name).unwrap();
+    assert!(position("San Antonio") < position("San Diego"));
+
+    // Text after a comma narrows by country or region
+    let places = cities.geocode("Portland, Oregon").await.unwrap();
+    assert_eq!(names(&places), ["Portland"]);
+    let places = cities.geocode("Santiago, Chile").await.unwrap();
+    assert_eq!(places[0].country, "Chile");
+    assert!(cities.geocode("Kraków, Germany").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn offline_reverse_finds_nearest_city_within_range() {
+    let cities = bundled_cities();
+
+    let place = cities.reverse(50.05, 19.95).await.unwrap();
+    assert_eq!(place.name, "Kraków");
+
+    // Mid-Pacific is far from every bundled city
+    let err = cities.reverse(0.0, -150.0).await.unwrap_err();
+    assert!(matches!(err, AppError::NotFound { .. }), "{:?}", err);
+}
+
+#[tokio::test]
+async fn open_meteo_results_are_parsed_and_incomplete_ones_skipped() {
+    let server = FixtureServer::start(vec![Route::fixture("/search", "open_meteo_geocoding.json")]);
+    let provider = OpenMeteoGeocoder::new(server.base_url());
+
+    let places = provider.geocode("Kraków").await.unwrap();
+
+    assert_eq!(names(&places), ["Kraków", "Kraków District"]);
+    assert_eq!(places[0].admin_area.as_deref(), Some("Lesser Poland"));
+    assert_eq!(places[0].timezone, "Europe/Warsaw");
+    assert_eq!(places[1].admin_area, None);
+    assert_eq!(
+        server.requests(),
+        vec!["/search?name=Krak%C3%B3w&count=10&language=en&format=json".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn open_meteo_without_results_finds_nothing() {
+    let server = FixtureServer::start(vec![Route::status(
+        "/search",
+        200,
+        r#"{"generationtime_ms": 0.2}"#,
+    )]);
+    let geocoder = Geocoder::new(
+        Some(Arc::new(OpenMeteoGeocoder::new(server.base_url()))),
+        bundled_cities(),
+    );
+
+    // An online answer is trusted even when it is empty
+    assert!(geocoder.geocode("Atlantis").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn unreachable_provider_falls_back_to_bundled_cities() {
+    let geocoder = Geocoder::new(
+        Some(Arc::new(OpenMeteoGeocoder::new(unreachable_base_url()))),
+        bundled_cities(),
+    );
+
+    let places = geocoder.geocode("Kraków").await.unwrap();
+    assert_eq!(places[0].name, "Kraków");
+
+    let place = geocoder.reverse(51.51, -0.13).await.unwrap();
+    assert_eq!(place.name, "London");
+}
+
+#[tokio::test]
+async fn invalid_queries_are_rejected() {
+    let geocoder = Geocoder::new(None, bundled_cities());
+
+    let err = geocoder.geocode("   ").await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+
+    let err = geocoder.reverse(91.0, 0.0).await.unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+}
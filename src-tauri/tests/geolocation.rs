@@ -0,0 +1,239 @@
+mod common;
+
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::geolocation::{
+    Coordinates, GeolocationConfig, Geolocator, IpApiProvider, IpLocationProvider, IpProviderKind,
+    IpWhoIsProvider, IpapiCoProvider, LocationSource,
+};
+use command_center_4_lib::locations::{LocationInput, Locations};
+use common::{FixtureServer, Route};
+use std::sync::Arc;
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// One server standing in for all three IP lookup services
+fn providers(server: &FixtureServer) -> Vec<Arc<dyn IpLocationProvider>> {
+    vec![
+        Arc::new(IpWhoIsProvider::new(format!("{}/ipwho", server.base_url()))),
+        Arc::new(IpapiCoProvider::new(format!("{}/ipapi", server.base_url()))),
+        Arc::new(IpApiProvider::new(server.base_url())),
+    ]
+}
+
+/// Only IP lookups, so saved locations from other tests do not interfere
+fn ip_only() -> GeolocationConfig {
+    GeolocationConfig {
+        use_saved_default: false,
+        ..GeolocationConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn ip_providers_parse_coordinates() {
+    let server = FixtureServer::start(vec![
+        Route::fixture("/json/", "ip_api.json"),
+        Route::fixture("/ipwho/", "ipwho_is.json"),
+        Route::fixture("/ipapi/json/", "ipapi_co.json"),
+    ]);
+    let [ipwho_is, ipapi_co, ip_api] = <[_; 3]>::try_from(providers(&server)).ok().unwrap();
+
+    let location = ip_api.locate().await.unwrap();
+    assert_eq!((location.lat, location.lon), (50.0614, 19.9366));
+    assert_eq!(location.city.as_deref(), Some("Kraków"));
+
+    let location = ipwho_is.locate().await.unwrap();
+    assert_eq!((location.lat, location.lon), (52.2297, 21.0122));
+
+    let location = ipapi_co.locate().await.unwrap();
+    assert_eq!((location.lat, location.lon), (54.352, 18.6466));
+
+    assert_eq!(
+        server.requests(),
+        vec![
+            "/json/?fields=status,message,lat,lon,city".to_string(),
+            "/ipwho/?fields=success,message,latitude,longitude,city".to_string(),
+            "/ipapi/json/".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn ip_provider_reports_error_status_and_failed_lookups() {
+    let server = FixtureServer::start(vec![
+        Route::status("/json/", 503, ""),
+        Route::status(
+            "/ipwho/",
+            200,
+            r#"{"success": false, "message": "Reserved range"}"#,
+        ),
+    ]);
+    let providers = providers(&server);
+
+    let err = providers[2].locate().await.unwrap_err();
+    assert!(
+        matches!(err, AppError::Upstream { status: 503, .. }),
+        "unexpected error: {:?}",
+        err
+    );
+
+    let err = providers[0].locate().await.unwrap_err();
+    assert_eq!(
+        err,
+        AppError::upstream(200, "Location lookup failed: Reserved range")
+    );
+}
+
+#[tokio::test]
+async fn providers_are_tried_in_order_and_the_answer_cached() {
+    let server = FixtureServer::start(vec![
+        Route::status("/ipwho/", 200, r#"{"success": false}"#),
+        Route::fixture("/ipapi/json/", "ipapi_co.json"),
+        Route::fixture("/json/", "ip_api.json"),
+    ]);
+    let geolocator = Geolocator::open(None, providers(&server)).unwrap();
+    geolocator.set_config(ip_only()).unwrap();
+
+    let location = geolocator.locate().await.unwrap();
+    assert_eq!(
+        location.source,
+        LocationSource::IpLookup {
+            provider: IpProviderKind::IpapiCo
+        }
+    );
+    assert_eq!(location.name.as_deref(), Some("Gdańsk"));
+    assert!(location.accuracy_km > 0.0);
+    assert_eq!(server.request_count("/json/"), 0);
+
+    // A fresh lookup is reused without asking again
+    let location = geolocator.locate().await.unwrap();
+    assert!(
+        matches!(
+            location.source,
+            LocationSource::Cached {
+                provider: IpProviderKind::IpapiCo,
+                ..
+            }
+        ),
+        "{:?}",
+        location.source
+    );
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn manual_override_and_saved_default_come_first() {
+    let server = FixtureServer::start(vec![Route::fixture("/json/", "ip_api.json")]);
+    let geolocator = Geolocator::open(None, providers(&server)).unwrap();
+
+    let home = Locations::global()
+        .add(LocationInput {
+            name: "Geolocation home".to_string(),
+            lat: 59.3293,
+            lon: 18.0686,
+        })
+        .unwrap();
+    Locations::global().set_default(Some(&home.id)).unwrap();
+
+    let location = geolocator.locate().await.unwrap();
+    assert_eq!(
+        location.source,
+        LocationSource::SavedDefault {
+            location_id: home.id.clone()
+        }
+    );
+    assert_eq!((location.lat, location.lon), (59.3293, 18.0686));
+    assert_eq!(location.accuracy_km, 0.0);
+
+    geolocator
+        .set_config(GeolocationConfig {
+            manual_override: Some(Coordinates {
+                latitude: 48.8566,
+                longitude: 2.3522,
+            }),
+            ..GeolocationConfig::default()
+        })
+        .unwrap();
+    let location = geolocator.locate().await.unwrap();
+    assert_eq!(location.source, LocationSource::Manual);
+    assert_eq!((location.lat, location.lon), (48.8566, 2.3522));
+
+    let err = geolocator
+        .set_config(GeolocationConfig {
+            manual_override: Some(Coordinates {
+                latitude: 120.0,
+                longitude: 0.0,
+            }),
+            ..GeolocationConfig::default()
+        })
+        .unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+
+    Locations::global().remove(&home.id).unwrap();
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn privacy_setting_stops_network_lookups() {
+    let server = FixtureServer::start(vec![Route::fixture("/json/", "ip_api.json")]);
+    let geolocator = Geolocator::open(None, providers(&server)).unwrap();
+    let private = GeolocationConfig {
+        network_lookup: false,
+        ..ip_only()
+    };
+    geolocator.set_config(private.clone()).unwrap();
+
+    let err = geolocator.locate().await.unwrap_err();
+    assert!(matches!(err, AppError::NotFound { .. }), "{:?}", err);
+    assert!(server.requests().is_empty());
+
+    // Look up once, then expire the cache and go private again: the old
+    // answer is served rather than asking the network
+    geolocator
+        .set_config(GeolocationConfig {
+            ip_providers: vec![IpProviderKind::IpApi],
+            ..ip_only()
+        })
+        .unwrap();
+    geolocator.locate().await.unwrap();
+    geolocator
+        .set_config(GeolocationConfig {
+            cache_ttl_secs: 0,
+            ..private
+        })
+        .unwrap();
+
+    let location = geolocator.locate().await.unwrap();
+    assert!(
+        matches!(location.source, LocationSource::Cached { .. }),
+        "{:?}",
+        location.source
+    );
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn settings_and_last_lookup_persist() {
+    let path = temp_path("geolocation");
+    let server = FixtureServer::start(vec![Route::fixture("/json/", "ip_api.json")]);
+
+    let geolocator = Geolocator::open(Some(path.clone()), providers(&server)).unwrap();
+    geolocator
+        .set_config(GeolocationConfig {
+            ip_providers: vec![IpProviderKind::IpApi],
+            ..ip_only()
+        })
+        .unwrap();
+    geolocator.locate().await.unwrap();
+
+    let reopened = Geolocator::open(Some(path.clone()), providers(&server)).unwrap();
+    assert_eq!(reopened.config().ip_providers, vec![IpProviderKind::IpApi]);
+    let location = reopened.locate().await.unwrap();
+    assert!(matches!(location.source, LocationSource::Cached { .. }));
+    assert_eq!(server.requests().len(), 1);
+
+    let _ = std::fs::remove_file(&path);
+}
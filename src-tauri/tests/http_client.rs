@@ -0,0 +1,177 @@
+mod common;
+
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::http_client::{HttpClient, HttpConfig};
+use common::{FixtureServer, Route};
+use std::time::{Duration, Instant};
+
+/// Client with delays short enough for tests
+fn client(max_retries: u32) -> HttpClient {
+    HttpClient::new(HttpConfig {
+        max_retries,
+        base_backoff: Duration::from_millis(20),
+        max_backoff: Duration::from_millis(100),
+        max_retry_after: Duration::from_secs(2),
+        host_interval: Duration::ZERO,
+        ..HttpConfig::default()
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn retries_server_errors_until_success() {
+    let server = FixtureServer::start(vec![
+        Route::status("/data", 503, "").times(2),
+        Route::status("/data", 200, r#"{"ok":true}"#),
+    ]);
+
+    let response = client(2)
+        .get(&format!("{}/data", server.base_url()))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(server.request_count("/data"), 3);
+}
+
+#[tokio::test]
+async fn retries_dropped_connections() {
+    let server = FixtureServer::start(vec![
+        Route::hang_up("/data").times(1),
+        Route::status("/data", 200, r#"{"ok":true}"#),
+    ]);
+
+    let response = client(2)
+        .get(&format!("{}/data", server.base_url()))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(server.request_count("/data"), 2);
+}
+
+#[tokio::test]
+async fn returns_transport_error_once_retries_run_out() {
+    let server = FixtureServer::start(vec![Route::hang_up("/data")]);
+
+    let err = client(1)
+        .get(&format!("{}/data", server.base_url()))
+        .await
+        .unwrap_err();
+
+    assert!(err.is_request(), "{:?}", err);
+    assert_eq!(server.request_count("/data"), 2);
+}
+
+#[tokio::test]
+async fn returns_last_error_status_once_retries_run_out() {
+    let server = FixtureServer::start(vec![Route::status("/data", 500, "")]);
+
+    let response = client(1)
+        .get(&format!("{}/data", server.base_url()))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 500);
+    assert_eq!(server.request_count("/data"), 2);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = FixtureServer::start(vec![Route::status("/data", 404, "")]);
+
+    let response = client(3)
+        .get(&format!("{}/data", server.base_url()))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 404);
+    assert_eq!(server.request_count("/data"), 1);
+}
+
+#[tokio::test]
+async fn honours_retry_after_on_rate_limit() {
+    let server = FixtureServer::start(vec![
+        Route::status("/data", 429, "")
+            .with_header("Retry-After", "1")
+            .times(1),
+        Route::status("/data", 200, "{}"),
+    ]);
+
+    let started = Instant::now();
+    let response = client(1)
+        .get(&format!("{}/data", server.base_url()))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(server.request_count("/data"), 2);
+}
+
+#[tokio::test]
+async fn gives_up_when_retry_after_is_too_long() {
+    let server = FixtureServer::start(vec![
+        Route::status("/data", 429, "").with_header("Retry-After", "3600")
+    ]);
+
+    let response = client(3)
+        .get(&format!("{}/data", server.base_url()))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), 429);
+    assert_eq!(server.request_count("/data"), 1);
+}
+
+#[tokio::test]
+async fn get_json_reports_rate_limits_with_retry_after() {
+    let server = FixtureServer::start(vec![
+        Route::status("/data", 429, "").with_header("Retry-After", "3600")
+    ]);
+
+    let err = client(0)
+        .get_json::<serde_json::Value>(&format!("{}/data", server.base_url()), "weather")
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        AppError::rate_limited(Some(3600), "Weather API rate limit reached")
+    );
+}
+
+#[tokio::test]
+async fn spaces_out_requests_to_one_host() {
+    let server = FixtureServer::start(vec![Route::status("/data", 200, "{}")]);
+    let client = HttpClient::new(HttpConfig {
+        host_interval: Duration::from_millis(200),
+        ..HttpConfig::default()
+    })
+    .unwrap();
+    let url = format!("{}/data", server.base_url());
+
+    let started = Instant::now();
+    let (first, second, third) = tokio::join!(client.get(&url), client.get(&url), client.get(&url));
+
+    assert!(first.is_ok() && second.is_ok() && third.is_ok());
+    assert!(started.elapsed() >= Duration::from_millis(400));
+    assert_eq!(server.request_count("/data"), 3);
+}
+
+#[tokio::test]
+async fn sends_user_agent() {
+    let server = FixtureServer::start(vec![Route::status("/data", 200, "{}")]);
+
+    client(0)
+        .get(&format!("{}/data", server.base_url()))
+        .await
+        .unwrap();
+
+    let user_agent = server.user_agents().pop().unwrap();
+    assert!(
+        user_agent.starts_with("command-center-4/"),
+        "{}",
+        user_agent
+    );
+}
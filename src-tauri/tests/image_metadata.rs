@@ -0,0 +1,131 @@
+use command_center_4_lib::image_metadata::{self, SanitizeOptions};
+use command_center_4_lib::image_store::ImageStore;
+use exif::experimental::Writer;
+use exif::{Field, In, Rational, Tag, Value};
+use image::{ImageFormat, RgbImage};
+use img_parts::jpeg::Jpeg;
+use img_parts::{Bytes, ImageEXIF};
+use std::fs;
+use std::io::Cursor;
+
+fn rational(values: [u32; 3]) -> Value {
+    Value::Rational(
+        values
+            .iter()
+            .map(|&num| Rational { num, denom: 1 })
+            .collect(),
+    )
+}
+
+/// A 40x20 JPEG with EXIF like a phone photo held upright
+fn phone_photo(orientation: u16) -> Vec<u8> {
+    let mut jpeg = Cursor::new(Vec::new());
+    RgbImage::from_fn(40, 20, |x, _| image::Rgb([(x * 6) as u8, 90, 200]))
+        .write_to(&mut jpeg, ImageFormat::Jpeg)
+        .unwrap();
+
+    let fields = [
+        (Tag::Orientation, Value::Short(vec![orientation])),
+        (
+            Tag::DateTimeOriginal,
+            Value::Ascii(vec![b"2024:06:01 14:30:00".to_vec()]),
+        ),
+        (Tag::GPSLatitudeRef, Value::Ascii(vec![b"N".to_vec()])),
+        (Tag::GPSLatitude, rational([52, 13, 30])),
+        (Tag::GPSLongitudeRef, Value::Ascii(vec![b"W".to_vec()])),
+        (Tag::GPSLongitude, rational([21, 0, 36])),
+    ]
+    .map(|(tag, value)| Field {
+        tag,
+        ifd_num: In::PRIMARY,
+        value,
+    });
+    let mut writer = Writer::new();
+    for field in &fields {
+        writer.push_field(field);
+    }
+    let mut exif = Cursor::new(Vec::new());
+    writer.write(&mut exif, false).unwrap();
+
+    let mut photo = Jpeg::from_bytes(Bytes::from(jpeg.into_inner())).unwrap();
+    photo.set_exif(Some(Bytes::from(exif.into_inner())));
+    photo.encoder().bytes().to_vec()
+}
+
+#[test]
+fn exif_details_are_read() {
+    let metadata = image_metadata::read(&phone_photo(6)).unwrap();
+
+    // Orientation 6 turns the photo a quarter, so it is displayed portrait
+    assert_eq!((metadata.width, metadata.height), (20, 40));
+    assert_eq!(metadata.format, "image/jpeg");
+    assert_eq!(metadata.orientation, 6);
+    assert_eq!(metadata.captured_at.as_deref(), Some("2024-06-01T14:30:00"));
+    let location = metadata.location.unwrap();
+    assert!((location.latitude - 52.225).abs() < 1e-9);
+    assert!((location.longitude + 21.01).abs() < 1e-9);
+}
+
+#[test]
+fn photos_are_turned_upright_and_stripped() {
+    let photo = phone_photo(6);
+
+    let stored = image_metadata::sanitize(&photo, &SanitizeOptions::default()).unwrap();
+    let metadata = image_metadata::read(&stored).unwrap();
+    assert_eq!((metadata.width, metadata.height), (20, 40));
+    assert_eq!(metadata.orientation, 1);
+    assert!(metadata.location.is_none());
+    assert!(metadata.captured_at.is_none());
+
+    let untouched = SanitizeOptions {
+        auto_rotate: false,
+        strip_exif: false,
+    };
+    assert_eq!(image_metadata::sanitize(&photo, &untouched).unwrap(), photo);
+
+    // Upright photos lose their EXIF without being re-encoded
+    let upright = phone_photo(1);
+    let strip_only = SanitizeOptions {
+        auto_rotate: false,
+        strip_exif: true,
+    };
+    let stripped = image_metadata::sanitize(&upright, &strip_only).unwrap();
+    assert!(image_metadata::read(&stripped).unwrap().location.is_none());
+    assert_eq!(
+        image::load_from_memory(&stripped).unwrap(),
+        image::load_from_memory(&upright).unwrap()
+    );
+
+    // Anything that is not an image is stored as it is
+    assert_eq!(
+        image_metadata::sanitize(b"notes", &SanitizeOptions::default()).unwrap(),
+        b"notes"
+    );
+}
+
+#[test]
+fn listed_images_carry_their_metadata() {
+    let root = std::env::temp_dir().join(format!("image-metadata-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let store = ImageStore::open(root.join("images")).unwrap();
+
+    let photo = store.save(&phone_photo(6), Some("friend:1")).unwrap();
+    let notes = store.save(b"notes", Some("friend:2")).unwrap();
+    let listed = store.list();
+
+    let metadata = listed
+        .iter()
+        .find(|image| image.id == photo.as_str())
+        .and_then(|image| image.metadata.clone())
+        .unwrap();
+    assert_eq!((metadata.width, metadata.height), (20, 40));
+    assert_eq!(metadata.orientation, 1);
+    // The capture date is kept from the original; the location is not
+    assert_eq!(metadata.captured_at.as_deref(), Some("2024-06-01T14:30:00"));
+    assert!(metadata.location.is_none());
+
+    let notes = listed.iter().find(|image| image.id == notes.as_str());
+    assert!(notes.unwrap().metadata.is_none());
+
+    let _ = fs::remove_dir_all(&root);
+}
@@ -0,0 +1,161 @@
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::image_protocol;
+use command_center_4_lib::image_store::ImageStore;
+use image::{ImageFormat, RgbaImage};
+use std::fs;
+use tauri::http::{header, Method, Request, Response, StatusCode};
+
+const APP_ORIGIN: &str = "tauri://localhost";
+
+fn request(method: Method, uri: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    request_from(APP_ORIGIN, method, uri, body)
+}
+
+fn request_from(origin: &str, method: Method, uri: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    image_protocol::handle(
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::ORIGIN, origin)
+            .body(body)
+            .unwrap(),
+    )
+}
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    RgbaImage::new(width, height)
+        .write_to(&mut encoded, ImageFormat::Png)
+        .unwrap();
+    encoded.into_inner()
+}
+
+fn error(response: &Response<Vec<u8>>) -> AppError {
+    serde_json::from_slice(response.body()).unwrap()
+}
+
+#[test]
+fn images_travel_as_raw_bodies() {
+    let root = std::env::temp_dir().join(format!("image-protocol-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    ImageStore::init(root.join("images")).unwrap();
+    let image = png(600, 300);
+
+    let uploaded = request(
+        Method::POST,
+        "image://localhost/?owner=clothing%3A1",
+        image.clone(),
+    );
+    assert_eq!(uploaded.status(), StatusCode::CREATED);
+    let id = String::from_utf8(uploaded.body().clone()).unwrap();
+    let stored = ImageStore::global().unwrap().info(&id).unwrap();
+    assert_eq!(stored.owners, vec!["clothing:1"]);
+
+    // Served by ID with its type, cached for good, and revalidated by tag
+    let served = request(Method::GET, &format!("image://localhost/{}", id), vec![]);
+    assert_eq!(served.status(), StatusCode::OK);
+    assert_eq!(served.body(), &image);
+    assert_eq!(served.headers()[header::CONTENT_TYPE], "image/png");
+    assert!(served.headers()[header::CACHE_CONTROL]
+        .to_str()
+        .unwrap()
+        .contains("immutable"));
+    let etag = served.headers()[header::ETAG].clone();
+    let revalidated = image_protocol::handle(
+        Request::builder()
+            .uri(format!("http://image.localhost/{}", id))
+            .header(header::IF_NONE_MATCH, etag)
+            .body(vec![])
+            .unwrap(),
+    );
+    assert_eq!(revalidated.status(), StatusCode::NOT_MODIFIED);
+    assert!(revalidated.body().is_empty());
+
+    let thumbnail = request(
+        Method::GET,
+        &format!("image://localhost/{}?size=100", id),
+        vec![],
+    );
+    assert_eq!(thumbnail.status(), StatusCode::OK);
+    assert_eq!(thumbnail.headers()[header::CONTENT_TYPE], "image/webp");
+    assert_ne!(
+        thumbnail.headers()[header::ETAG],
+        served.headers()[header::ETAG]
+    );
+    let decoded = image::load_from_memory(thumbnail.body()).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (128, 64));
+
+    // Failures carry the error the commands would return
+    let missing = request(
+        Method::GET,
+        &format!("image://localhost/{}", "0".repeat(64)),
+        vec![],
+    );
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    assert!(matches!(error(&missing), AppError::NotFound { .. }));
+    let rejected = request(Method::POST, "image://localhost/", b"not an image".to_vec());
+    assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
+    assert!(matches!(error(&rejected), AppError::InvalidInput { .. }));
+    let cutout = request(
+        Method::POST,
+        "image://localhost/background-removal",
+        b"not an image".to_vec(),
+    );
+    assert_eq!(cutout.status(), StatusCode::BAD_REQUEST);
+    let escaping = request(Method::GET, "image://localhost/..%2Fsecret.txt", vec![]);
+    assert!(matches!(error(&escaping), AppError::InvalidPath { .. }));
+
+    let preflight = request(Method::OPTIONS, "image://localhost/", vec![]);
+    assert_eq!(preflight.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        preflight.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        APP_ORIGIN
+    );
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn other_origins_may_not_write() {
+    let page = "https://example.com";
+    for (method, uri) in [
+        (Method::OPTIONS, "image://localhost/"),
+        (Method::POST, "image://localhost/?owner=clothing%3A1"),
+        (Method::POST, "image://localhost/background-removal"),
+    ] {
+        let refused = request_from(page, method, uri, png(8, 8));
+        assert_eq!(refused.status(), StatusCode::FORBIDDEN, "{}", uri);
+        assert!(!refused
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+        assert_eq!(refused.headers()[header::VARY], "Origin");
+        assert!(matches!(error(&refused), AppError::InvalidInput { .. }));
+    }
+}
+
+#[test]
+fn requests_without_an_origin_are_the_apps() {
+    let anonymous = |method: Method, uri: &str, body: Vec<u8>| {
+        image_protocol::handle(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(body)
+                .unwrap(),
+        )
+    };
+
+    let preflight = anonymous(Method::OPTIONS, "image://localhost/", vec![]);
+    assert_eq!(preflight.status(), StatusCode::NO_CONTENT);
+    assert!(!preflight
+        .headers()
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+    // Processed rather than refused, so the bad body is what fails
+    let cutout = anonymous(
+        Method::POST,
+        "image://localhost/background-removal",
+        b"not an image".to_vec(),
+    );
+    assert_eq!(cutout.status(), StatusCode::BAD_REQUEST);
+}
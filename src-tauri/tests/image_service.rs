@@ -0,0 +1,200 @@
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::image_service::{ImageName, UploadFormat, UploadLimits, Uploads};
+use command_center_4_lib::image_store::ImageStore;
+use image::{ImageFormat, RgbaImage};
+use std::fs;
+use std::path::PathBuf;
+
+/// Scratch directory holding an `images` store and a file beside it that
+/// escaping names would reach
+fn scratch(name: &str) -> (PathBuf, ImageStore) {
+    let root = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("secret.txt"), b"keep me").unwrap();
+
+    let store = ImageStore::open(root.join("images")).unwrap();
+    (root, store)
+}
+
+const ESCAPING_NAMES: [&str; 9] = [
+    "../secret.txt",
+    "..\\secret.txt",
+    "..",
+    ".",
+    "/etc/passwd",
+    "C:\\Windows\\win.ini",
+    "C:secret.txt",
+    "nested/image.png",
+    "image.png\0.txt",
+];
+
+const RESERVED_NAMES: [&str; 5] = ["CON", "nul.png", "Com1.jpg", "lpt9", "photo.png."];
+
+fn assert_invalid_path(result: Result<ImageName, AppError>, name: &str) {
+    match result {
+        Err(AppError::InvalidPath { message }) => {
+            assert!(message.contains("Invalid image name"), "{}", message)
+        }
+        other => panic!("{:?} was not rejected: {:?}", name, other),
+    }
+}
+
+#[test]
+fn image_names_reject_traversal_and_reserved_names() {
+    for name in ESCAPING_NAMES.iter().chain(&RESERVED_NAMES) {
+        assert_invalid_path(ImageName::new(name), name);
+    }
+    assert_invalid_path(ImageName::new(""), "");
+    assert_invalid_path(ImageName::new(&"a".repeat(256)), "long name");
+
+    for name in [
+        "photo.png",
+        "shirt 2.jpg",
+        "..hidden.png",
+        "zdjęcie.webp",
+        "console.png",
+    ] {
+        assert_eq!(ImageName::new(name).unwrap().as_str(), name);
+    }
+}
+
+#[test]
+fn every_image_command_stays_inside_the_store() {
+    let (root, store) = scratch("image-store-commands");
+
+    // Legacy names that would reach the neighbouring file never get to the disk
+    for escaping in ESCAPING_NAMES {
+        for result in [
+            store.read(escaping).map(|_| ()),
+            store.path(escaping).map(|_| ()),
+            store.retain(escaping, "clothing:1"),
+            store.release(escaping, "clothing:1").map(|_| ()),
+        ] {
+            let err = result.unwrap_err();
+            assert!(matches!(err, AppError::InvalidPath { .. }), "{:?}", err);
+        }
+    }
+    assert_eq!(fs::read(root.join("secret.txt")).unwrap(), b"keep me");
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[cfg(unix)]
+#[test]
+fn links_inside_the_store_are_not_followed() {
+    let root = std::env::temp_dir().join(format!("image-store-links-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("images")).unwrap();
+    fs::write(root.join("secret.txt"), b"keep me").unwrap();
+    std::os::unix::fs::symlink(
+        root.join("secret.txt"),
+        root.join("images").join("link.png"),
+    )
+    .unwrap();
+
+    // Only plain files are migrated from name-based storage
+    let store = ImageStore::open(root.join("images")).unwrap();
+    let err = store.read("link.png").unwrap_err();
+    assert!(matches!(err, AppError::NotFound { .. }), "{:?}", err);
+    assert!(store.list().is_empty());
+    assert_eq!(fs::read(root.join("secret.txt")).unwrap(), b"keep me");
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    RgbaImage::from_fn(width, height, |x, y| {
+        image::Rgba([x as u8, y as u8, 120, 255])
+    })
+    .write_to(&mut encoded, ImageFormat::Png)
+    .unwrap();
+    encoded.into_inner()
+}
+
+#[test]
+fn uploads_are_sniffed_limited_and_normalized() {
+    let uploads = Uploads::global();
+    let image = png(40, 30);
+    assert_eq!(uploads.prepare(&image).unwrap(), image);
+
+    // Not an image, whatever the caller named it
+    let err = uploads.prepare(b"MZ\x90\x00 not a picture").unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+    // A valid header over a corrupt body is refused before it is stored
+    let err = uploads.prepare(&image[..image.len() / 2]).unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+
+    uploads
+        .set_limits(UploadLimits {
+            max_bytes: 64,
+            ..UploadLimits::default()
+        })
+        .unwrap();
+    let err = uploads.prepare(&image).unwrap_err();
+    assert!(matches!(err, AppError::TooLarge { .. }), "{:?}", err);
+
+    // Dimensions are checked from the header, before anything is decoded
+    uploads
+        .set_limits(UploadLimits {
+            max_pixels: 1000,
+            ..UploadLimits::default()
+        })
+        .unwrap();
+    let err = uploads.check(&image).unwrap_err();
+    assert!(matches!(err, AppError::TooLarge { .. }), "{:?}", err);
+    assert!(uploads.check(&png(40, 25)).is_ok());
+
+    uploads
+        .set_limits(UploadLimits {
+            normalize_to: Some(UploadFormat::Jpeg),
+            ..UploadLimits::default()
+        })
+        .unwrap();
+    let normalized = uploads.prepare(&image).unwrap();
+    assert_eq!(image::guess_format(&normalized).unwrap(), ImageFormat::Jpeg);
+    assert_eq!(
+        image::load_from_memory(&normalized)
+            .unwrap()
+            .into_rgb8()
+            .dimensions(),
+        (40, 30)
+    );
+
+    let err = uploads
+        .set_limits(UploadLimits {
+            max_bytes: 0,
+            ..UploadLimits::default()
+        })
+        .unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+    uploads.set_limits(UploadLimits::default()).unwrap();
+}
+
+#[test]
+fn upload_limits_survive_a_restart() {
+    let path = std::env::temp_dir().join(format!("upload-limits-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let uploads = Uploads::open(Some(path.clone())).unwrap();
+    assert_eq!(uploads.limits(), UploadLimits::default());
+    let limits = UploadLimits {
+        max_bytes: 1024,
+        max_pixels: 4096,
+        normalize_to: Some(UploadFormat::Webp),
+    };
+    assert_eq!(uploads.set_limits(limits.clone()).unwrap(), limits);
+    // Refused limits leave the stored ones alone
+    assert!(uploads
+        .set_limits(UploadLimits {
+            max_pixels: 0,
+            ..UploadLimits::default()
+        })
+        .is_err());
+
+    let reopened = Uploads::open(Some(path.clone())).unwrap();
+    assert_eq!(reopened.limits(), limits);
+
+    let _ = std::fs::remove_file(&path);
+}
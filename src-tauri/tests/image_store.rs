@@ -0,0 +1,257 @@
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::image_metadata::SanitizeOptions;
+use command_center_4_lib::image_store::{ImageId, ImageStore};
+use command_center_4_lib::image_variants::{VariantConfig, VariantFormat};
+use image::{ImageFormat, RgbaImage};
+use std::fs;
+use std::path::PathBuf;
+
+fn scratch(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    root
+}
+
+/// Far enough ahead that every unclaimed image counts as abandoned
+fn future() -> i64 {
+    chrono::Utc::now().timestamp() + 60
+}
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    RgbaImage::new(width, height)
+        .write_to(&mut encoded, ImageFormat::Png)
+        .unwrap();
+    encoded.into_inner()
+}
+
+#[test]
+fn identical_bytes_are_stored_once() {
+    let root = scratch("image-store-dedup");
+    let store = ImageStore::open(root.join("images")).unwrap();
+
+    let first = store.save(b"png bytes", Some("clothing:1")).unwrap();
+    let second = store.save(b"png bytes", Some("friend:7")).unwrap();
+    let other = store.save(b"other bytes", Some("clothing:2")).unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(first, ImageId::of(b"png bytes"));
+    assert_ne!(first, other);
+    assert_eq!(first.as_str().len(), 64);
+    assert_eq!(store.read(first.as_str()).unwrap(), b"png bytes");
+
+    let listed = store.list();
+    assert_eq!(listed.len(), 2);
+    let shared = listed
+        .iter()
+        .find(|image| image.id == first.as_str())
+        .unwrap();
+    assert_eq!(shared.owners, vec!["clothing:1", "friend:7"]);
+    assert_eq!(shared.size, 9);
+    assert_eq!(store.path(first.as_str()).unwrap(), shared.path);
+
+    // The index survives reopening
+    drop(store);
+    let store = ImageStore::open(root.join("images")).unwrap();
+    assert_eq!(store.list().len(), 2);
+    assert_eq!(store.read(other.as_str()).unwrap(), b"other bytes");
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn images_are_deleted_with_their_last_owner() {
+    let root = scratch("image-store-refcount");
+    let store = ImageStore::open(root.join("images")).unwrap();
+
+    let id = store.save(b"shirt", Some("clothing:1")).unwrap();
+    store.retain(id.as_str(), "fit:3").unwrap();
+    let path = store.path(id.as_str()).unwrap();
+
+    assert!(!store.release(id.as_str(), "clothing:1").unwrap());
+    assert_eq!(store.read(id.as_str()).unwrap(), b"shirt");
+    // Owners that never held it change nothing
+    assert!(!store.release(id.as_str(), "clothing:2").unwrap());
+    assert_eq!(store.info(id.as_str()).unwrap().owners, vec!["fit:3"]);
+    assert!(store.release(id.as_str(), "fit:3").unwrap());
+    assert!(!PathBuf::from(path).exists());
+
+    let err = store.read(id.as_str()).unwrap_err();
+    assert!(matches!(err, AppError::NotFound { .. }), "{:?}", err);
+    let err = store.retain(id.as_str(), "fit:3").unwrap_err();
+    assert!(matches!(err, AppError::NotFound { .. }), "{:?}", err);
+
+    let err = store.save(b"shirt", Some(" ")).unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn releasing_an_unclaimed_image_leaves_it_to_collection() {
+    let root = scratch("image-store-stray-release");
+    let store = ImageStore::open(root.join("images")).unwrap();
+
+    let id = store.save(b"draft", None).unwrap();
+    assert!(!store.release(id.as_str(), "clothing:1").unwrap());
+    assert_eq!(store.read(id.as_str()).unwrap(), b"draft");
+
+    // Still claimable within its grace period
+    store.retain(id.as_str(), "clothing:1").unwrap();
+    assert_eq!(store.collect_garbage(future()).unwrap(), 0);
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn garbage_collection_removes_unclaimed_and_stray_files() {
+    let root = scratch("image-store-gc");
+    let store = ImageStore::open(root.join("images")).unwrap();
+
+    let kept = store.save(b"claimed", Some("friend:1")).unwrap();
+    let abandoned = store.save(b"never claimed", None).unwrap();
+    let stray = root.join("images").join("blobs").join("ab").join("ab.tmp");
+    fs::create_dir_all(stray.parent().unwrap()).unwrap();
+    fs::write(&stray, b"half written").unwrap();
+
+    // Within the grace period only the stray file goes
+    assert_eq!(store.collect_garbage(0).unwrap(), 1);
+    assert!(!stray.exists());
+    assert_eq!(store.read(abandoned.as_str()).unwrap(), b"never claimed");
+
+    assert_eq!(store.collect_garbage(future()).unwrap(), 1);
+    let err = store.read(abandoned.as_str()).unwrap_err();
+    assert!(matches!(err, AppError::NotFound { .. }), "{:?}", err);
+    assert_eq!(store.read(kept.as_str()).unwrap(), b"claimed");
+
+    // Entries whose file disappeared are dropped from the index
+    fs::remove_file(store.path(kept.as_str()).unwrap()).unwrap();
+    assert_eq!(store.collect_garbage(0).unwrap(), 1);
+    assert!(store.list().is_empty());
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn saving_an_unclaimed_image_again_restarts_its_grace_period() {
+    let root = scratch("image-store-resave");
+    let store = ImageStore::open(root.join("images")).unwrap();
+
+    let id = store.save(b"picked twice", None).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    // The first save is now past the grace period
+    let cutoff = chrono::Utc::now().timestamp();
+    assert_eq!(store.save(b"picked twice", None).unwrap(), id);
+
+    assert_eq!(store.collect_garbage(cutoff).unwrap(), 0);
+    assert_eq!(store.read(id.as_str()).unwrap(), b"picked twice");
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn named_images_are_migrated_and_stay_reachable() {
+    let root = scratch("image-store-migration");
+    let dir = root.join("images");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("clothing-1-shirt.png"), b"shirt").unwrap();
+    fs::write(dir.join("avatar-2-ana.png"), b"shirt").unwrap();
+    fs::write(dir.join("fit-preview-3.png"), b"fit").unwrap();
+
+    let store = ImageStore::open(dir.clone()).unwrap();
+    assert!(!dir.join("clothing-1-shirt.png").exists());
+    assert_eq!(store.list().len(), 2);
+    assert_eq!(store.read("clothing-1-shirt.png").unwrap(), b"shirt");
+    assert_eq!(
+        store.path("avatar-2-ana.png").unwrap(),
+        store.path(ImageId::of(b"shirt").as_str()).unwrap()
+    );
+
+    // Released by its old name, an image goes once no other name uses it
+    assert!(!store.release("clothing-1-shirt.png", "clothing:1").unwrap());
+    assert_eq!(store.read("avatar-2-ana.png").unwrap(), b"shirt");
+    assert!(store.release("avatar-2-ana.png", "friend:2").unwrap());
+    assert!(store.release("fit-preview-3.png", "fit:3").unwrap());
+    assert!(store.list().is_empty());
+
+    // Nothing is left to migrate the next time
+    drop(store);
+    let store = ImageStore::open(dir).unwrap();
+    assert!(store.list().is_empty());
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn variants_are_downscaled_to_configured_sizes() {
+    let root = scratch("image-store-variants");
+    let store = ImageStore::open(root.join("images")).unwrap();
+    let id = store.save(&png(600, 300), Some("clothing:1")).unwrap();
+
+    // Requests snap up to the next configured size and never upscale
+    let small = store.variant(id.as_str(), 100).unwrap();
+    assert!(small.ends_with(".webp"), "{}", small);
+    assert!(PathBuf::from(&small).starts_with(root.join("images").join("variants").join("128")));
+    assert_eq!(image::image_dimensions(&small).unwrap(), (128, 64));
+    let large = store.variant(id.as_str(), 5000).unwrap();
+    assert_eq!(image::image_dimensions(&large).unwrap(), (600, 300));
+
+    store.generate_variants(id.as_str()).unwrap();
+    let generated = fs::read_dir(root.join("images").join("variants")).unwrap();
+    assert_eq!(generated.count(), 3);
+
+    let err = store
+        .set_variant_config(VariantConfig {
+            sizes: vec![],
+            ..VariantConfig::default()
+        })
+        .unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+    let config = store
+        .set_variant_config(VariantConfig {
+            sizes: vec![256, 64, 256],
+            format: VariantFormat::Png,
+        })
+        .unwrap();
+    assert_eq!(config.sizes, vec![64, 256]);
+    let reopened = ImageStore::open(root.join("images")).unwrap();
+    assert_eq!(reopened.variant_config(), config);
+    drop(reopened);
+
+    // Variants no longer configured go with the next collection
+    let thumb = store.variant(id.as_str(), 64).unwrap();
+    assert!(thumb.ends_with(".png"), "{}", thumb);
+    assert_eq!(store.collect_garbage(0).unwrap(), 3);
+    assert!(!PathBuf::from(&small).exists());
+    assert!(PathBuf::from(&thumb).exists());
+
+    // and with their image
+    assert!(store.release(id.as_str(), "clothing:1").unwrap());
+    assert!(!PathBuf::from(&thumb).exists());
+
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn sanitize_options_survive_a_restart() {
+    let root = scratch("image-store-sanitize");
+    let store = ImageStore::open(root.join("images")).unwrap();
+    assert_eq!(store.sanitize_options(), SanitizeOptions::default());
+
+    let options = SanitizeOptions {
+        auto_rotate: false,
+        strip_exif: false,
+    };
+    assert_eq!(
+        store.set_sanitize_options(options.clone()).unwrap(),
+        options
+    );
+    drop(store);
+
+    let store = ImageStore::open(root.join("images")).unwrap();
+    assert_eq!(store.sanitize_options(), options);
+    assert_eq!(store.variant_config(), VariantConfig::default());
+
+    let _ = fs::remove_dir_all(&root);
+}
@@ -0,0 +1,148 @@
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::locations::{LocationInput, LocationQuery, Locations};
+
+fn input(name: &str, lat: f64, lon: f64) -> LocationInput {
+    LocationInput {
+        name: name.to_string(),
+        lat,
+        lon,
+    }
+}
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn first_location_becomes_default_and_active() {
+    let locations = Locations::open(None).unwrap();
+
+    let home = locations.add(input("Home", 50.0614, 19.9366)).unwrap();
+    let office = locations.add(input("Office", 50.0647, 19.945)).unwrap();
+
+    assert_eq!(locations.list(), vec![home.clone(), office]);
+    assert_eq!(locations.default_location(), Some(home.clone()));
+    assert_eq!(locations.active(), Some(home));
+}
+
+#[test]
+fn names_must_be_present_and_unique() {
+    let locations = Locations::open(None).unwrap();
+    locations.add(input("Home", 50.0, 19.0)).unwrap();
+
+    let duplicate = locations.add(input(" home ", 51.0, 20.0)).unwrap_err();
+    let empty = locations.add(input("  ", 51.0, 20.0)).unwrap_err();
+    let off_map = locations.add(input("Nowhere", 95.0, 20.0)).unwrap_err();
+
+    assert_eq!(
+        duplicate,
+        AppError::invalid_input("A location named 'home' already exists")
+    );
+    assert!(matches!(empty, AppError::InvalidInput { .. }));
+    assert!(matches!(off_map, AppError::InvalidInput { .. }));
+}
+
+#[test]
+fn update_and_remove_keep_default_and_active_consistent() {
+    let locations = Locations::open(None).unwrap();
+    let home = locations.add(input("Home", 50.0, 19.0)).unwrap();
+    let moms = locations.add(input("Mom's", 52.0, 21.0)).unwrap();
+
+    let renamed = locations
+        .update(&moms.id, input("Mum's", 52.1, 21.1))
+        .unwrap();
+    assert_eq!(renamed.id, moms.id);
+    assert_eq!(locations.get(&moms.id).unwrap().name, "Mum's");
+
+    locations.set_active(&moms.id).unwrap();
+    locations.remove(&moms.id).unwrap();
+    assert_eq!(locations.active(), Some(home.clone()));
+
+    locations.remove(&home.id).unwrap();
+    assert_eq!(locations.default_location(), None);
+    assert_eq!(locations.active(), None);
+
+    assert!(matches!(
+        locations.remove(&home.id).unwrap_err(),
+        AppError::NotFound { .. }
+    ));
+}
+
+#[test]
+fn registry_persists_with_default_as_active_on_reopen() {
+    let path = temp_path("locations");
+
+    let office_id = {
+        let locations = Locations::open(Some(path.clone())).unwrap();
+        locations.add(input("Home", 50.0, 19.0)).unwrap();
+        let office = locations.add(input("Office", 50.1, 19.1)).unwrap();
+        locations.set_default(Some(&office.id)).unwrap();
+        office.id
+    };
+
+    let reopened = Locations::open(Some(path)).unwrap();
+
+    assert_eq!(reopened.list().len(), 2);
+    assert_eq!(reopened.active().unwrap().id, office_id);
+}
+
+#[test]
+fn failed_saves_leave_the_registry_unchanged() {
+    let dir = std::env::temp_dir().join(format!("locations-unwritable-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let locations = Locations::open(Some(dir.join("locations.json"))).unwrap();
+    let home = locations.add(input("Home", 50.0, 19.0)).unwrap();
+
+    // A file where the directory was makes every save fail
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::fs::write(&dir, "").unwrap();
+
+    assert!(locations.add(input("Office", 50.1, 19.1)).is_err());
+    assert!(locations
+        .update(&home.id, input("Flat", 50.2, 19.2))
+        .is_err());
+    assert!(locations.set_default(None).is_err());
+    assert!(locations.remove(&home.id).is_err());
+
+    assert_eq!(locations.list(), vec![home.clone()]);
+    assert_eq!(locations.default_location(), Some(home.clone()));
+    assert_eq!(locations.active(), Some(home));
+
+    std::fs::remove_file(&dir).unwrap();
+}
+
+#[test]
+fn queries_resolve_to_coordinates() {
+    let locations = Locations::open(None).unwrap();
+
+    assert!(matches!(
+        locations.resolve(&LocationQuery::Active).unwrap_err(),
+        AppError::NotFound { .. }
+    ));
+
+    let home = locations.add(input("Home", 50.0614, 19.9366)).unwrap();
+
+    assert_eq!(
+        locations
+            .resolve(&LocationQuery::Saved { id: home.id })
+            .unwrap(),
+        (50.0614, 19.9366)
+    );
+    assert_eq!(
+        locations.resolve(&LocationQuery::Active).unwrap(),
+        (50.0614, 19.9366)
+    );
+    assert_eq!(
+        locations
+            .resolve(&LocationQuery::Coordinates { lat: 1.0, lon: 2.0 })
+            .unwrap(),
+        (1.0, 2.0)
+    );
+    assert!(locations
+        .resolve(&LocationQuery::Saved {
+            id: "missing".to_string()
+        })
+        .is_err());
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use command_center_4_lib::connectivity;
+use command_center_4_lib::env::EnvConfig;
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::geohash;
+use command_center_4_lib::geolocation::{IpApiProvider, IpLocationProvider};
+use command_center_4_lib::weather;
+use command_center_4_lib::weather_cache::{
+    WeatherCache, WeatherCacheConfig, DEFAULT_GRID_PRECISION,
+};
+use common::{sample_weather, FixtureServer, Route};
+use std::net::TcpListener;
+use tokio::sync::{Mutex, MutexGuard, OnceCell};
+
+/// Base URL with nothing listening behind it
+fn unreachable_base_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{}", addr)
+}
+
+/// Location with an expired entry in the cache file loaded at startup
+const CACHED_LAT: f64 = 59.3293;
+const CACHED_LON: f64 = 18.0686;
+
+/// Hour with an expired forecast entry at the cached location
+const CACHED_DATE: &str = "2024-03-10T12:00:00Z";
+
+/// Age of the restored entry, well past the current weather TTL
+const CACHED_AGE_SECS: i64 = 2 * 60 * 60;
+
+/// Global config pointing the weather provider at a closed port, with a
+/// restored cache. Stale-while-revalidate is off so expired entries only
+/// come back once the provider has failed.
+///
+/// Connectivity state is global, so tests hold the returned guard to run
+/// one at a time.
+async fn offline() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::const_new(());
+    static CONFIGURED: OnceCell<()> = OnceCell::const_new();
+
+    let guard = LOCK.lock().await;
+
+    CONFIGURED
+        .get_or_init(|| async {
+            std::env::set_var("WEATHER_PROVIDER", "open_weather_map");
+            std::env::set_var("OPEN_WEATHER_API_KEY", "test-key");
+            std::env::set_var("OPEN_WEATHER_BASE_URL", unreachable_base_url());
+            EnvConfig::init().expect("Failed to initialize EnvConfig");
+
+            let path =
+                std::env::temp_dir().join(format!("offline-cache-{}.json", std::process::id()));
+            let body = serde_json::json!({
+                "current": {
+                    format!("current_{}", geohash::encode(CACHED_LAT, CACHED_LON, DEFAULT_GRID_PRECISION)): {
+                        "value": sample_weather(),
+                        "fetched_at": chrono::Utc::now().timestamp() - CACHED_AGE_SECS,
+                    }
+                },
+                "forecast": {
+                    format!(
+                        "forecast_{}_{}",
+                        geohash::encode(CACHED_LAT, CACHED_LON, DEFAULT_GRID_PRECISION),
+                        chrono::DateTime::parse_from_rfc3339(CACHED_DATE).unwrap().timestamp()
+                    ): {
+                        "value": sample_weather(),
+                        "fetched_at": chrono::Utc::now().timestamp() - CACHED_AGE_SECS,
+                    }
+                },
+                "series": {}
+            });
+            std::fs::write(&path, body.to_string()).unwrap();
+            WeatherCache::init(path).await.unwrap();
+            let cache = WeatherCache::global();
+            let config = WeatherCacheConfig {
+                stale_while_revalidate: false,
+                ..cache.config()
+            };
+            cache.set_config(config).await.unwrap();
+        })
+        .await;
+
+    guard
+}
+
+#[tokio::test]
+async fn unreachable_provider_without_cached_data_is_an_error() {
+    let _guard = offline().await;
+
+    let err = weather::fetch_weather(35.6762, 139.6503).await.unwrap_err();
+
+    assert!(matches!(err, AppError::Network { .. }), "{:?}", err);
+    assert!(
+        err.to_string().starts_with("Failed to fetch weather"),
+        "{}",
+        err
+    );
+    assert!(!connectivity::current().online);
+}
+
+#[tokio::test]
+async fn unreachable_provider_serves_last_known_weather_as_stale() {
+    let _guard = offline().await;
+
+    let response = weather::fetch_weather(CACHED_LAT, CACHED_LON)
+        .await
+        .unwrap();
+
+    assert!(response.stale);
+    assert_eq!(response.data.location, "Testville");
+
+    let fetched_at = chrono::DateTime::parse_from_rfc3339(&response.fetched_at).unwrap();
+    let age = chrono::Utc::now().timestamp() - fetched_at.timestamp();
+    assert!((CACHED_AGE_SECS..CACHED_AGE_SECS + 60).contains(&age));
+    assert!(!connectivity::current().online);
+}
+
+#[tokio::test]
+async fn unreachable_provider_serves_last_known_weather_for_a_date_as_stale() {
+    let _guard = offline().await;
+
+    let response = weather::fetch_forecast_for_date(CACHED_LAT, CACHED_LON, CACHED_DATE)
+        .await
+        .unwrap();
+
+    assert!(response.stale);
+    assert_eq!(response.data.location, "Testville");
+}
+
+#[tokio::test]
+async fn connectivity_follows_request_outcomes() {
+    let _guard = offline().await;
+
+    let _ = weather::fetch_weather(-33.8688, 151.2093).await;
+    let state = connectivity::current();
+    assert!(!state.online);
+    assert!(state.checked_at.is_some());
+
+    // Any response from a service, even an error status, means we are online
+    let server = FixtureServer::start(vec![Route::status("/json/", 500, "{}")]);
+    let _ = IpApiProvider::new(server.base_url()).locate().await;
+    let state = connectivity::current();
+    assert!(state.online);
+    assert!(state.last_online_at.is_some());
+}
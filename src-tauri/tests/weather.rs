@@ -0,0 +1,598 @@
+mod common;
+
+use command_center_4_lib::env::EnvConfig;
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::weather::{self, LocationZone, WeatherData};
+use command_center_4_lib::weather_history::WeatherHistory;
+use command_center_4_lib::weather_provider::{
+    MockProvider, OpenMeteoProvider, OpenWeatherMapProvider, WeatherProvider,
+};
+use common::{FixtureServer, Route};
+use std::sync::OnceLock;
+
+fn openweather_routes() -> Vec<Route> {
+    vec![
+        Route::fixture("/weather", "openweather_current.json"),
+        Route::fixture("/forecast", "openweather_forecast.json"),
+        Route::fixture("/archive", "open_meteo_archive.json"),
+    ]
+}
+
+fn open_meteo(server: &FixtureServer) -> OpenMeteoProvider {
+    OpenMeteoProvider::new(server.base_url(), server.base_url())
+}
+
+/// 2024-01-15T12:00:00Z, old enough to be served by the archive
+const ARCHIVE_TIMESTAMP: i64 = 1705320000;
+
+/// Server backing the global `EnvConfig`, shared by every test in this file
+fn configured_server() -> &'static FixtureServer {
+    static SERVER: OnceLock<FixtureServer> = OnceLock::new();
+
+    SERVER.get_or_init(|| {
+        let server = FixtureServer::start(openweather_routes());
+        std::env::set_var("WEATHER_PROVIDER", "open_weather_map");
+        std::env::set_var("OPEN_WEATHER_API_KEY", "test-key");
+        std::env::set_var("OPEN_WEATHER_BASE_URL", server.base_url());
+        std::env::set_var("OPEN_METEO_ARCHIVE_BASE_URL", server.base_url());
+        EnvConfig::init().expect("Failed to initialize EnvConfig");
+        server
+    })
+}
+
+#[tokio::test]
+async fn open_weather_map_parses_current_conditions() {
+    let server = FixtureServer::start(openweather_routes());
+    let provider = OpenWeatherMapProvider::new("test-key".to_string(), server.base_url());
+
+    let data = provider.fetch_current(50.0614, 19.9366).await.unwrap();
+
+    assert_eq!(data.temperature, 14.62);
+    assert_eq!(data.feels_like, 13.87);
+    assert_eq!(data.humidity, 71);
+    assert_eq!(data.description, "broken clouds");
+    assert_eq!(data.icon, "04d");
+    assert_eq!(data.location, "Kraków");
+    assert_eq!(data.wind_speed, 3.6);
+    assert_eq!(data.wind_deg, 250);
+    assert_eq!(data.wind_gust, Some(6.2));
+    assert_eq!(data.pressure, 1017);
+    assert_eq!(data.visibility, Some(10000));
+    assert_eq!(data.clouds, 75);
+    assert_eq!(data.rain, 0.21);
+    assert_eq!(data.snow, 0.0);
+    assert_eq!(data.sunrise.as_deref(), Some("2025-10-17T05:10:45+00:00"));
+    assert_eq!(data.sunset.as_deref(), Some("2025-10-17T15:50:51+00:00"));
+    assert_eq!(data.timezone_offset, 7200);
+    assert_eq!(data.uv_index, None);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].contains("appid=test-key"));
+    assert!(requests[0].contains("units=metric"));
+}
+
+#[tokio::test]
+async fn open_weather_map_returns_every_forecast_slot() {
+    let server = FixtureServer::start(openweather_routes());
+    let provider = OpenWeatherMapProvider::new("test-key".to_string(), server.base_url());
+
+    let forecast = provider.fetch_forecast(50.0614, 19.9366).await.unwrap();
+    let slots = &forecast.slots;
+
+    assert_eq!(forecast.location, "Kraków");
+    assert_eq!(slots.len(), 8);
+    assert!(slots.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+    assert_eq!(slots[0].point.date, "2025-10-17T15:00:00+00:00");
+
+    let rainy = &slots[2].point;
+    assert_eq!(rainy.description, "light rain");
+    assert_eq!(rainy.pop, 0.64);
+    // 1.12 mm over the 3 hour slot, as an hourly rate
+    assert_eq!(rainy.rain, 1.12 / 3.0);
+    assert_eq!(rainy.snow, 0.0);
+    assert_eq!(rainy.wind_speed, 3.9);
+    assert_eq!(rainy.wind_deg, 250);
+}
+
+#[tokio::test]
+async fn open_weather_map_reports_error_status() {
+    let server = FixtureServer::start(vec![Route::status(
+        "/weather",
+        401,
+        r#"{"cod":401,"message":"Invalid API key"}"#,
+    )]);
+    let provider = OpenWeatherMapProvider::new("bad-key".to_string(), server.base_url());
+
+    let err = provider.fetch_current(50.0614, 19.9366).await.unwrap_err();
+
+    assert!(
+        matches!(err, AppError::Upstream { status: 401, .. }),
+        "unexpected error: {:?}",
+        err
+    );
+    assert!(err.to_string().contains("401"), "unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn open_meteo_maps_weather_codes() {
+    let server = FixtureServer::start(vec![Route::fixture("/forecast", "open_meteo_current.json")]);
+    let provider = open_meteo(&server);
+
+    let data = provider.fetch_current(50.0614, 19.9366).await.unwrap();
+
+    assert_eq!(data.temperature, 14.3);
+    assert_eq!(data.humidity, 70);
+    assert_eq!(data.description, "overcast");
+    assert_eq!(data.icon, "04d");
+    assert_eq!(data.location, "50.06, 19.94");
+    assert_eq!(data.wind_gust, Some(7.1));
+    assert_eq!(data.pressure, 1017);
+    assert_eq!(data.visibility, Some(24140));
+    assert_eq!(data.clouds, 92);
+    assert_eq!(data.uv_index, Some(1.35));
+    assert_eq!(data.sunrise.as_deref(), Some("2025-10-17T05:10:45+00:00"));
+    assert_eq!(data.timezone_offset, 7200);
+    assert!(server.requests()[0].contains("latitude=50.0614"));
+}
+
+#[tokio::test]
+async fn open_weather_map_passes_on_one_call_alerts() {
+    let server = FixtureServer::start(vec![Route::fixture("/onecall", "openweather_onecall.json")]);
+    let provider = OpenWeatherMapProvider::new("test-key".to_string(), server.base_url());
+
+    let alerts = provider.fetch_alerts(50.0614, 19.9366).await.unwrap();
+
+    assert_eq!(alerts.len(), 2);
+    assert_eq!(alerts[0].event, "Strong wind");
+    assert_eq!(alerts[0].description, "Wind gusts up to 75 km/h expected.");
+    assert_eq!(alerts[0].sender.as_deref(), Some("IMGW-PIB"));
+    assert_eq!(alerts[0].start, 1760720400);
+    assert_eq!(alerts[0].end, Some(1760763600));
+    assert_eq!(alerts[1].sender, None);
+    assert!(server.requests()[0].contains("exclude=current,minutely,hourly,daily"));
+}
+
+#[tokio::test]
+async fn open_weather_map_without_one_call_access_reports_no_alerts() {
+    let server = FixtureServer::start(vec![Route::status(
+        "/onecall",
+        401,
+        r#"{"cod":401,"message":"Invalid API key"}"#,
+    )]);
+    let provider = OpenWeatherMapProvider::new("test-key".to_string(), server.base_url());
+
+    let alerts = provider.fetch_alerts(50.0614, 19.9366).await.unwrap();
+
+    assert!(alerts.is_empty());
+}
+
+#[tokio::test]
+async fn open_meteo_returns_hourly_forecast() {
+    let server = FixtureServer::start(vec![Route::fixture(
+        "/forecast",
+        "open_meteo_forecast.json",
+    )]);
+    let provider = open_meteo(&server);
+
+    let forecast = provider.fetch_forecast(50.0614, 19.9366).await.unwrap();
+    let slots = &forecast.slots;
+
+    assert_eq!(slots.len(), 6);
+    assert_eq!(slots[0].point.date, "2025-10-17T15:00:00+00:00");
+    assert_eq!(slots[2].point.description, "rain");
+    assert_eq!(slots[2].point.icon, "10n");
+    assert_eq!(slots[2].point.pop, 0.55);
+    // 0.2 cm of snow is reported as its water equivalent, 7 cm to 10 mm
+    assert!((slots[5].point.snow - 2.0 / 7.0).abs() < 1e-9);
+    assert!(server.requests()[0].contains("wind_speed_unit=ms"));
+}
+
+#[tokio::test]
+async fn open_meteo_tolerates_missing_hourly_values() {
+    let server = FixtureServer::start(vec![
+        Route::fixture("/forecast", "open_meteo_forecast_gaps.json"),
+        Route::fixture("/archive", "open_meteo_forecast_gaps.json"),
+    ]);
+    let provider = open_meteo(&server);
+
+    // The hour without a temperature is left out; other gaps read as unknown
+    let slots = provider
+        .fetch_forecast(50.0614, 19.9366)
+        .await
+        .unwrap()
+        .slots;
+    let times: Vec<_> = slots.iter().map(|slot| slot.timestamp).collect();
+    assert_eq!(
+        times,
+        vec![1760713200, 1760716800, 1760720400, 1760724000, 1760731200]
+    );
+    assert_eq!(slots[1].point.visibility, None);
+    assert_eq!(slots[2].point.wind_gust, None);
+    assert_eq!(slots[3].point.pop, 0.0);
+    assert_eq!(slots[3].point.rain, 2.1);
+
+    // Past weather comes from the closest complete hour
+    let data = provider
+        .fetch_historical(50.0614, 19.9366, 1760727600)
+        .await
+        .unwrap();
+    assert_eq!(data.temperature, 11.1);
+}
+
+#[tokio::test]
+async fn mock_provider_serves_fixture_relative_to_now() {
+    let path = format!(
+        "{}/tests/fixtures/mock_weather.json",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let provider = MockProvider::new(path);
+
+    let current = provider.fetch_current(0.0, 0.0).await.unwrap();
+    assert_eq!(current.location, "Mockville");
+    assert_eq!(current.temperature, 18.5);
+
+    let slots = provider.fetch_forecast(0.0, 0.0).await.unwrap().slots;
+    let now = chrono::Utc::now().timestamp();
+    assert_eq!(slots.len(), 9);
+    assert!((slots[0].timestamp - now).abs() <= 60 * 60);
+    assert_eq!(slots[1].timestamp - slots[0].timestamp, 3 * 60 * 60);
+}
+
+#[tokio::test]
+async fn fetch_weather_uses_configured_provider_and_cache() {
+    let server = configured_server();
+    let before = server.request_count("/weather");
+
+    let first = weather::fetch_weather(52.2297, 21.0122).await.unwrap();
+    let second = weather::fetch_weather(52.2297, 21.0122).await.unwrap();
+
+    assert_eq!(first.data.location, "Kraków");
+    assert!(!first.stale);
+    assert!(!second.stale);
+    assert_eq!(second.data.temperature, first.data.temperature);
+    assert_eq!(server.request_count("/weather") - before, 1);
+}
+
+#[tokio::test]
+async fn fetch_forecast_for_date_rejects_dates_beyond_forecast() {
+    configured_server();
+
+    let date = (chrono::Utc::now() + chrono::Duration::days(30)).to_rfc3339();
+    let err = weather::fetch_forecast_for_date(52.2297, 21.0122, &date)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        AppError::invalid_input("Date must be within the next 5 days")
+    );
+}
+
+#[tokio::test]
+async fn fetch_forecast_for_date_accepts_the_whole_last_local_day() {
+    configured_server();
+
+    // The forecast reports UTC+2 for the location, so days end at midnight there
+    weather::fetch_forecast(52.2297, 21.0122).await.unwrap();
+    let zone = LocationZone::fixed(7200);
+    let last_day = zone.date(chrono::Utc::now().timestamp()) + chrono::Days::new(5);
+    let end = zone.start_of_day(last_day + chrono::Days::new(1));
+    let late = chrono::DateTime::from_timestamp(end - 60 * 60, 0).unwrap();
+    let next_day = chrono::DateTime::from_timestamp(end, 0).unwrap();
+
+    weather::fetch_forecast_for_date(52.2297, 21.0122, &late.to_rfc3339())
+        .await
+        .unwrap();
+    let err = weather::fetch_forecast_for_date(52.2297, 21.0122, &next_day.to_rfc3339())
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        AppError::invalid_input("Date must be within the next 5 days")
+    );
+}
+
+#[tokio::test]
+async fn fetch_forecast_for_date_serves_past_dates_from_history() {
+    let server = configured_server();
+    let before = server.request_count("/archive");
+
+    let first = weather::fetch_forecast_for_date(41.9028, 12.4964, "2024-01-15T12:00:00Z")
+        .await
+        .unwrap()
+        .data;
+    // Same instant written differently, so only the recorded observation can answer
+    let second = weather::fetch_forecast_for_date(41.9028, 12.4964, "2024-01-15T13:00:00+01:00")
+        .await
+        .unwrap()
+        .data;
+
+    // The archive reports Europe/Warsaw, an hour ahead of UTC in January
+    assert_eq!(first.date, "2024-01-15T13:00:00+01:00");
+    assert_eq!(first.timezone.as_deref(), Some("Europe/Warsaw"));
+    assert_eq!(first.temperature, -1.2);
+    assert_eq!(second.temperature, first.temperature);
+    assert_eq!(server.request_count("/archive") - before, 1);
+}
+
+#[tokio::test]
+async fn fetch_forecast_for_date_finds_the_day_without_a_forecast_download() {
+    let server = configured_server();
+
+    weather::fetch_forecast_for_date(37.9838, 23.7275, "2024-01-15T12:00:00Z")
+        .await
+        .unwrap();
+
+    let downloads = server
+        .requests()
+        .iter()
+        .filter(|target| target.starts_with("/forecast?") && target.contains("lat=37.9838"))
+        .count();
+    assert_eq!(downloads, 0);
+}
+
+#[tokio::test]
+async fn fetch_forecast_returns_series_and_shares_download() {
+    let server = configured_server();
+    let before = server.request_count("/forecast");
+
+    let points = weather::fetch_forecast(48.8566, 2.3522).await.unwrap().data;
+    assert_eq!(points.len(), 8);
+    assert_eq!(points[1].rain, 0.38 / 3.0);
+
+    let now = chrono::Utc::now().to_rfc3339();
+    weather::fetch_forecast_for_date(48.8566, 2.3522, &now)
+        .await
+        .unwrap();
+
+    assert_eq!(server.request_count("/forecast") - before, 1);
+}
+
+#[tokio::test]
+async fn fetch_daily_forecast_buckets_by_location_timezone() {
+    configured_server();
+
+    // The fixture starts at 15:00 UTC in Kraków (UTC+2), so the first three
+    // slots fall on 17 October local time and the rest on the 18th
+    let days = weather::fetch_daily_forecast(45.4642, 9.19)
+        .await
+        .unwrap()
+        .data;
+
+    assert_eq!(days.len(), 2);
+
+    let first = &days[0];
+    assert_eq!(first.date, "2025-10-17");
+    assert_eq!(first.temp_min, 9.4);
+    assert_eq!(first.temp_max, 14.1);
+    assert!((first.humidity - 238.0 / 3.0).abs() < 1e-9);
+    assert!((first.precipitation - 1.5).abs() < 1e-9);
+    assert_eq!(first.pop, 0.64);
+    assert_eq!(first.description, "light rain");
+    assert_eq!(first.icon, "10d");
+
+    let second = &days[1];
+    assert_eq!(second.date, "2025-10-18");
+    assert_eq!(second.temp_min, 8.1);
+    assert_eq!(second.temp_max, 15.2);
+    assert_eq!(second.precipitation, 0.0);
+    assert_eq!(second.description, "overcast clouds");
+    assert_eq!(second.icon, "04d");
+}
+
+#[tokio::test]
+async fn open_meteo_reads_old_dates_from_archive() {
+    let server = FixtureServer::start(vec![Route::fixture("/archive", "open_meteo_archive.json")]);
+
+    let data = open_meteo(&server)
+        .fetch_historical(50.0614, 19.9366, ARCHIVE_TIMESTAMP)
+        .await
+        .unwrap();
+
+    assert_eq!(data.date, "2024-01-15T12:00:00+00:00");
+    assert_eq!(data.temperature, -1.2);
+    assert_eq!(data.description, "snow");
+    assert!((data.snow - 0.6).abs() < 1e-9);
+    assert_eq!(data.timezone_offset, 3600);
+    assert_eq!(data.sunrise.as_deref(), Some("2024-01-15T06:57:30+00:00"));
+
+    let request = &server.requests()[0];
+    assert!(request.contains("start_date=2024-01-14&end_date=2024-01-16"));
+}
+
+#[tokio::test]
+async fn open_meteo_reads_recent_past_from_forecast_api() {
+    let server = FixtureServer::start(vec![Route::fixture("/forecast", "open_meteo_archive.json")]);
+    let three_days_ago = chrono::Utc::now().timestamp() - 3 * 24 * 60 * 60;
+
+    open_meteo(&server)
+        .fetch_historical(50.0614, 19.9366, three_days_ago)
+        .await
+        .unwrap();
+
+    assert_eq!(server.request_count("/forecast"), 1);
+    assert!(server.requests()[0].contains("start_date="));
+}
+
+#[tokio::test]
+async fn open_weather_map_has_no_history() {
+    let server = FixtureServer::start(openweather_routes());
+    let provider = OpenWeatherMapProvider::new("test-key".to_string(), server.base_url());
+
+    assert!(provider
+        .fetch_historical(50.0614, 19.9366, ARCHIVE_TIMESTAMP)
+        .await
+        .is_err());
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn mock_provider_serves_history() {
+    let path = format!(
+        "{}/tests/fixtures/mock_weather.json",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let provider = MockProvider::new(path);
+    let two_days_ago = chrono::Utc::now().timestamp() - 48 * 60 * 60;
+
+    let data = provider
+        .fetch_historical(0.0, 0.0, two_days_ago)
+        .await
+        .unwrap();
+
+    assert_eq!(data.description, "light rain");
+    assert_eq!(data.rain, 0.6);
+}
+
+#[test]
+fn weather_history_persists_and_matches_nearby_observations() {
+    let path = std::env::temp_dir().join(format!(
+        "weather_history_{}_{}.jsonl",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+
+    let history = WeatherHistory::open(Some(path.clone())).unwrap();
+    let observed = {
+        let mut data = common::sample_weather();
+        data.temperature = 21.5;
+        data
+    };
+    history.record(50.0614, 19.9366, ARCHIVE_TIMESTAMP, observed);
+
+    let reopened = WeatherHistory::open(Some(path.clone())).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // Within ~5 km and 90 minutes
+    let found = reopened.find(50.07, 19.95, ARCHIVE_TIMESTAMP + 60 * 60);
+    assert_eq!(found.map(|d| d.temperature), Some(21.5));
+
+    assert!(reopened.find(50.2, 19.9366, ARCHIVE_TIMESTAMP).is_none());
+    assert!(reopened
+        .find(50.0614, 19.9366, ARCHIVE_TIMESTAMP + 3 * 60 * 60)
+        .is_none());
+}
+
+#[test]
+fn weather_history_survives_a_torn_write() {
+    let path = std::env::temp_dir().join(format!(
+        "weather_history_torn_{}_{}.jsonl",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    let legacy_path = path.with_extension("json");
+    let observation = |timestamp: i64, temperature: f64| {
+        serde_json::json!({
+            "lat": 50.0614,
+            "lon": 19.9366,
+            "timestamp": timestamp,
+            "data": observation_data(temperature),
+        })
+    };
+
+    // A file from before observations were appended, as one JSON array
+    // under the old name, is taken over and rewritten as lines
+    let legacy = serde_json::json!([observation(ARCHIVE_TIMESTAMP, 3.0)]);
+    std::fs::write(&legacy_path, legacy.to_string()).unwrap();
+    let history = WeatherHistory::open(Some(path.clone())).unwrap();
+    let found = history.find(50.0614, 19.9366, ARCHIVE_TIMESTAMP);
+    assert_eq!(found.map(|d| d.temperature), Some(3.0));
+    assert!(!legacy_path.exists());
+    assert!(!std::fs::read_to_string(&path).unwrap().starts_with('['));
+
+    // A crash cut the last line short
+    let later = ARCHIVE_TIMESTAMP + 6 * 60 * 60;
+    let torn = observation(later, 5.0).to_string();
+    let mut body = std::fs::read_to_string(&path).unwrap();
+    body.push_str(&torn[..torn.len() / 2]);
+    std::fs::write(&path, body).unwrap();
+
+    let history = WeatherHistory::open(Some(path.clone())).unwrap();
+    assert!(history.find(50.0614, 19.9366, later).is_none());
+    history.record(50.0614, 19.9366, later, observation_data(7.0));
+
+    let reopened = WeatherHistory::open(Some(path.clone())).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let found = reopened.find(50.0614, 19.9366, ARCHIVE_TIMESTAMP);
+    assert_eq!(found.map(|d| d.temperature), Some(3.0));
+    let found = reopened.find(50.0614, 19.9366, later);
+    assert_eq!(found.map(|d| d.temperature), Some(7.0));
+}
+
+fn observation_data(temperature: f64) -> WeatherData {
+    WeatherData {
+        temperature,
+        ..common::sample_weather()
+    }
+}
+
+#[tokio::test]
+async fn returned_dates_carry_the_location_offset() {
+    configured_server();
+
+    // OpenWeatherMap reports Kraków at UTC+2 without naming the zone
+    let current = weather::fetch_weather(50.0614, 19.9366).await.unwrap().data;
+    assert!(current.date.ends_with("+02:00"), "{}", current.date);
+    assert_eq!(
+        current.sunrise.as_deref(),
+        Some("2025-10-17T07:10:45+02:00")
+    );
+    assert_eq!(current.timezone_offset, 7200);
+
+    let points = weather::fetch_forecast(50.0614, 19.9366)
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(points[0].date, "2025-10-17T17:00:00+02:00");
+}
+
+#[tokio::test]
+async fn today_starts_at_local_midnight() {
+    let server = configured_server();
+    let before = server.request_count("/archive");
+
+    // Just after midnight at the location is today there, so it is forecast
+    // rather than history, whatever the date is in UTC. Current weather
+    // reports the location's offset.
+    weather::fetch_weather(50.0647, 19.945).await.unwrap();
+    let zone = LocationZone::fixed(7200);
+    let now = chrono::Utc::now().timestamp();
+    let start_of_today = zone.start_of_day(zone.date(now));
+    weather::fetch_forecast_for_date(50.0647, 19.945, &zone.format(start_of_today + 60))
+        .await
+        .unwrap();
+
+    assert_eq!(server.request_count("/archive"), before);
+}
+
+#[test]
+fn named_zones_follow_daylight_saving() {
+    let zone = LocationZone::resolve(0.0, 0.0, Some("Europe/Warsaw"), 7200, 0);
+    assert_eq!(zone.name(), Some("Europe/Warsaw"));
+
+    // Clocks go back at 01:00 UTC on 26 October 2025
+    assert_eq!(zone.format(1761438600), "2025-10-26T02:30:00+02:00");
+    assert_eq!(zone.format(1761442200), "2025-10-26T02:30:00+01:00");
+    assert_eq!(zone.offset_secs(1761442200), 3600);
+
+    // Santiago skips from midnight to 01:00 on 7 September 2025
+    let santiago = LocationZone::resolve(0.0, 0.0, Some("America/Santiago"), 0, 0);
+    let date = chrono::NaiveDate::from_ymd_opt(2025, 9, 7).unwrap();
+    assert_eq!(santiago.start_of_day(date), 1757217600);
+}
+
+#[test]
+fn unnamed_zones_use_the_provider_offset() {
+    let zone = LocationZone::resolve(1.87, -157.4, None, 14 * 3600, 0);
+    assert_eq!(zone.name(), None);
+
+    // 11:00 UTC is already the next day at UTC+14
+    assert_eq!(
+        zone.date(1760698800),
+        chrono::NaiveDate::from_ymd_opt(2025, 10, 18).unwrap()
+    );
+    assert_eq!(zone.format(1760698800), "2025-10-18T01:00:00+14:00");
+}
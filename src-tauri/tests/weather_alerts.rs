@@ -0,0 +1,310 @@
+use command_center_4_lib::env::EnvConfig;
+use command_center_4_lib::locations::{LocationInput, Locations, SavedLocation};
+use command_center_4_lib::weather::ForecastPoint;
+use command_center_4_lib::weather_alerts::{
+    evaluate, AlertConfig, AlertRules, WeatherAlertKind, WeatherAlerts,
+};
+use command_center_4_lib::weather_preferences::UnitSystem;
+use command_center_4_lib::weather_provider::{Forecast, ForecastSlot, ProviderAlert};
+
+const HOUR: i64 = 60 * 60;
+const NOW: i64 = 1_700_000_000 / HOUR * HOUR;
+
+fn location() -> SavedLocation {
+    SavedLocation {
+        id: "home".to_string(),
+        name: "Home".to_string(),
+        lat: 50.0614,
+        lon: 19.9366,
+    }
+}
+
+/// Calm, dry slot `offset` hours from now
+fn slot(offset: i64) -> ForecastSlot {
+    let timestamp = NOW + offset * HOUR;
+    ForecastSlot {
+        timestamp,
+        point: ForecastPoint {
+            date: chrono::DateTime::from_timestamp(timestamp, 0)
+                .unwrap()
+                .to_rfc3339(),
+            temperature: 12.0,
+            feels_like: 11.0,
+            humidity: 60,
+            wind_speed: 3.0,
+            wind_deg: 180,
+            wind_gust: None,
+            pressure: 1013,
+            clouds: 20,
+            visibility: Some(10000),
+            pop: 0.0,
+            rain: 0.0,
+            snow: 0.0,
+            description: "few clouds".to_string(),
+            icon: "02d".to_string(),
+            units: UnitSystem::Metric,
+        },
+    }
+}
+
+/// Three-hourly forecast covering the next two days
+fn forecast(edit: impl Fn(i64, &mut ForecastPoint)) -> Forecast {
+    Forecast {
+        location: "Kraków".to_string(),
+        timezone_offset: 0,
+        timezone: None,
+        slots: (0..16)
+            .map(|i| {
+                let mut slot = slot(i * 3);
+                edit(i * 3, &mut slot.point);
+                slot
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn calm_forecast_raises_nothing() {
+    let alerts = evaluate(
+        &AlertRules::default(),
+        &location(),
+        &forecast(|_, _| {}),
+        &[],
+        NOW,
+    );
+
+    assert!(alerts.is_empty(), "{:?}", alerts);
+}
+
+#[test]
+fn heavy_rain_compares_the_hourly_rate() {
+    // 5 mm/h is below the default threshold
+    let light = forecast(|offset, point| {
+        if offset == 6 {
+            point.rain = 5.0;
+        }
+    });
+    assert!(evaluate(&AlertRules::default(), &location(), &light, &[], NOW).is_empty());
+
+    let heavy = forecast(|offset, point| {
+        if offset == 6 || offset == 9 {
+            point.rain = 10.0;
+        }
+    });
+    let alerts = evaluate(&AlertRules::default(), &location(), &heavy, &[], NOW);
+
+    assert_eq!(alerts.len(), 1);
+    let alert = &alerts[0];
+    assert_eq!(alert.kind, WeatherAlertKind::HeavyRain);
+    assert_eq!(alert.location, "Home");
+    assert_eq!(alert.description, "Up to 10.0 mm of rain per hour");
+    assert_eq!(
+        chrono::DateTime::parse_from_rfc3339(&alert.starts_at)
+            .unwrap()
+            .timestamp(),
+        NOW + 6 * HOUR
+    );
+    assert_eq!(
+        chrono::DateTime::parse_from_rfc3339(alert.ends_at.as_ref().unwrap())
+            .unwrap()
+            .timestamp(),
+        NOW + 12 * HOUR
+    );
+}
+
+#[test]
+fn separate_events_are_raised_separately() {
+    // Two downpours with a dry slot between them
+    let showers = forecast(|offset, point| match offset {
+        3 => point.rain = 10.0,
+        9 => point.rain = 15.0,
+        _ => {}
+    });
+    let alerts = evaluate(&AlertRules::default(), &location(), &showers, &[], NOW);
+
+    assert_eq!(alerts.len(), 2);
+    assert_ne!(alerts[0].id, alerts[1].id);
+    assert_eq!(alerts[0].description, "Up to 10.0 mm of rain per hour");
+    assert_eq!(alerts[1].description, "Up to 15.0 mm of rain per hour");
+
+    // The same events on the next check keep their IDs
+    let again = evaluate(&AlertRules::default(), &location(), &showers, &[], NOW);
+    let ids: Vec<_> = again.iter().map(|alert| &alert.id).collect();
+    assert_eq!(ids, vec![&alerts[0].id, &alerts[1].id]);
+}
+
+#[test]
+fn freezing_and_gusts_use_configured_thresholds() {
+    let wintry = forecast(|offset, point| {
+        if offset == 12 {
+            point.temperature = 1.5;
+            point.wind_gust = Some(15.0);
+        }
+    });
+
+    assert!(evaluate(&AlertRules::default(), &location(), &wintry, &[], NOW).is_empty());
+
+    let rules = AlertRules {
+        freezing_temperature: 2.0,
+        high_wind_speed: 14.0,
+        ..AlertRules::default()
+    };
+    let alerts = evaluate(&rules, &location(), &wintry, &[], NOW);
+    let kinds: Vec<_> = alerts.iter().map(|alert| alert.kind).collect();
+
+    assert_eq!(
+        kinds,
+        vec![WeatherAlertKind::Freezing, WeatherAlertKind::HighWind]
+    );
+    assert_eq!(alerts[0].description, "Down to 1.5 °C");
+    assert_eq!(alerts[1].description, "Wind up to 15.0 m/s");
+}
+
+#[test]
+fn conditions_beyond_the_lookahead_are_ignored() {
+    let later = forecast(|offset, point| {
+        if offset >= 30 {
+            point.temperature = -5.0;
+        }
+    });
+
+    assert!(evaluate(&AlertRules::default(), &location(), &later, &[], NOW).is_empty());
+
+    let rules = AlertRules {
+        lookahead_hours: 36,
+        ..AlertRules::default()
+    };
+    assert_eq!(evaluate(&rules, &location(), &later, &[], NOW).len(), 1);
+}
+
+#[test]
+fn provider_warnings_are_passed_on_until_they_end() {
+    let warnings = vec![
+        ProviderAlert {
+            event: "Storm warning".to_string(),
+            description: "Thunderstorms with hail".to_string(),
+            sender: Some("Met office".to_string()),
+            start: NOW + 2 * HOUR,
+            end: Some(NOW + 8 * HOUR),
+        },
+        ProviderAlert {
+            event: "Fog".to_string(),
+            description: "Dense fog".to_string(),
+            sender: None,
+            start: NOW - 6 * HOUR,
+            end: Some(NOW - HOUR),
+        },
+    ];
+
+    let alerts = evaluate(
+        &AlertRules::default(),
+        &location(),
+        &forecast(|_, _| {}),
+        &warnings,
+        NOW,
+    );
+
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].kind, WeatherAlertKind::Provider);
+    assert_eq!(alerts[0].title, "Storm warning");
+    assert_eq!(
+        alerts[0].description,
+        "Thunderstorms with hail (Met office)"
+    );
+}
+
+#[test]
+fn invalid_config_is_rejected() {
+    let alerts = WeatherAlerts::global();
+
+    let err = alerts
+        .set_config(AlertConfig {
+            poll_interval_secs: 5,
+            ..AlertConfig::default()
+        })
+        .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Alert poll interval must be at least 60 seconds"
+    );
+}
+
+#[test]
+fn config_survives_reopening() {
+    let path = std::env::temp_dir().join(format!("alert-settings-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let alerts = WeatherAlerts::open(Some(path.clone())).unwrap();
+    assert!(alerts.config().enabled);
+    alerts
+        .set_config(AlertConfig {
+            enabled: false,
+            rules: AlertRules {
+                high_wind_speed: 12.0,
+                ..AlertRules::default()
+            },
+            ..AlertConfig::default()
+        })
+        .unwrap();
+
+    let reopened = WeatherAlerts::open(Some(path.clone())).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(!reopened.config().enabled);
+    assert_eq!(reopened.config().rules.high_wind_speed, 12.0);
+}
+
+#[tokio::test]
+async fn check_raises_each_alert_once() {
+    let path = std::env::temp_dir().join(format!("alerts-mock-{}.json", std::process::id()));
+    let fixture = serde_json::json!({
+        "location": "Stormville",
+        "current": { "temperature": 4.0, "feels_like": 1.0, "humidity": 90, "description": "rain", "icon": "10d" },
+        "forecast": [
+            { "offset_hours": 0, "temperature": 4.0, "feels_like": 1.0, "humidity": 90, "description": "rain", "icon": "10d" },
+            { "offset_hours": 1, "temperature": -2.0, "feels_like": -6.0, "humidity": 90, "description": "snow", "icon": "13d", "wind_speed": 20.0 },
+            { "offset_hours": 2, "temperature": 3.0, "feels_like": 1.0, "humidity": 90, "description": "rain", "icon": "10d" }
+        ],
+        "alerts": [
+            { "event": "Gale warning", "description": "Severe gales", "start_offset_hours": 0, "end_offset_hours": 6 }
+        ]
+    });
+    std::fs::write(&path, fixture.to_string()).unwrap();
+    std::env::set_var("WEATHER_PROVIDER", "mock");
+    std::env::set_var("WEATHER_MOCK_FILE", &path);
+    EnvConfig::init().expect("Failed to initialize EnvConfig");
+
+    let home = Locations::global()
+        .add(LocationInput {
+            name: "Home".to_string(),
+            lat: 50.0614,
+            lon: 19.9366,
+        })
+        .unwrap();
+    let alerts = WeatherAlerts::global();
+
+    let raised = alerts.check().await.unwrap();
+    let mut kinds: Vec<_> = raised.iter().map(|alert| alert.kind).collect();
+    kinds.sort_by_key(|kind| format!("{:?}", kind));
+
+    assert_eq!(
+        kinds,
+        vec![
+            WeatherAlertKind::Freezing,
+            WeatherAlertKind::HighWind,
+            WeatherAlertKind::Provider
+        ]
+    );
+    assert!(raised
+        .iter()
+        .all(|alert| alert.notify && alert.location_id == home.id));
+    assert_eq!(alerts.active(None).len(), 3);
+
+    assert_eq!(alerts.active(Some(&home.id)).len(), 3);
+    assert!(alerts.active(Some("elsewhere")).is_empty());
+
+    // Conditions still in effect are not raised again
+    assert!(alerts.check().await.unwrap().is_empty());
+    assert_eq!(alerts.active(None).len(), 3);
+}
@@ -0,0 +1,432 @@
+mod common;
+
+use command_center_4_lib::env::EnvConfig;
+use command_center_4_lib::geohash;
+use command_center_4_lib::weather::{self, WeatherData};
+use command_center_4_lib::weather_cache::{
+    WeatherCache, WeatherCacheConfig, WeatherCacheKind, DEFAULT_GRID_PRECISION,
+};
+use command_center_4_lib::weather_provider::{MockProvider, WeatherProvider};
+use common::{sample_weather, FixtureServer, Route};
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// Location with an expired entry in the cache file loaded at startup
+const STALE_LAT: f64 = 48.8566;
+const STALE_LON: f64 = 2.3522;
+
+/// Location with an expired entry that is fetched with stale-while-revalidate off
+const BLOCKING_LAT: f64 = 41.9028;
+const BLOCKING_LON: f64 = 12.4964;
+
+/// Location with an entry fetched a minute before startup
+const FRESH_LAT: f64 = 40.4168;
+const FRESH_LON: f64 = -3.7038;
+
+fn cache_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("weather-cache-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{}.json", name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn current_key(lat: f64, lon: f64) -> String {
+    format!(
+        "current_{}",
+        geohash::encode(lat, lon, DEFAULT_GRID_PRECISION)
+    )
+}
+
+/// Cache file holding current weather for each `(lat, lon, age in seconds)`
+fn write_cache_file(path: &PathBuf, entries: &[(f64, f64, i64)]) {
+    let now = chrono::Utc::now().timestamp();
+    let current: serde_json::Map<_, _> = entries
+        .iter()
+        .map(|(lat, lon, age)| {
+            (
+                current_key(*lat, *lon),
+                json!({ "value": sample_weather(), "fetched_at": now - age }),
+            )
+        })
+        .collect();
+
+    let body = json!({ "current": current, "forecast": {}, "series": {} });
+    std::fs::write(path, body.to_string()).unwrap();
+}
+
+/// Global cache restored from disk, backed by a local OpenWeatherMap stand-in
+async fn configured_server() -> &'static FixtureServer {
+    static SERVER: OnceCell<FixtureServer> = OnceCell::const_new();
+
+    SERVER
+        .get_or_init(|| async {
+            let server =
+                FixtureServer::start(vec![Route::fixture("/weather", "openweather_current.json")]);
+            std::env::set_var("WEATHER_PROVIDER", "open_weather_map");
+            std::env::set_var("OPEN_WEATHER_API_KEY", "test-key");
+            std::env::set_var("OPEN_WEATHER_BASE_URL", server.base_url());
+            EnvConfig::init().expect("Failed to initialize EnvConfig");
+
+            let path = cache_path("global");
+            write_cache_file(
+                &path,
+                &[
+                    (STALE_LAT, STALE_LON, 30 * 60),
+                    (BLOCKING_LAT, BLOCKING_LON, 30 * 60),
+                    (FRESH_LAT, FRESH_LON, 60),
+                ],
+            );
+            WeatherCache::init(path).await.unwrap();
+
+            server
+        })
+        .await
+}
+
+#[tokio::test]
+async fn entries_survive_reopening_the_cache_file() {
+    let path = cache_path("reopen");
+
+    let cache = WeatherCache::open(Some(path.clone())).await.unwrap();
+    cache.set_current(10.0, 20.0, sample_weather()).await;
+    cache.flush().await.unwrap();
+    drop(cache);
+
+    let reopened = WeatherCache::open(Some(path)).await.unwrap();
+    let cached = reopened.get_current(10.0, 20.0).await.unwrap();
+
+    assert!(!cached.stale);
+    assert_eq!(cached.value.location, "Testville");
+    assert!(reopened.get_current(11.0, 20.0).await.is_none());
+}
+
+#[tokio::test]
+async fn ttl_counts_from_the_original_fetch_across_restarts() {
+    let path = cache_path("ttl");
+    write_cache_file(
+        &path,
+        &[
+            (1.0, 1.0, 60),
+            (2.0, 2.0, 11 * 60),
+            (3.0, 3.0, 30 * 24 * 60 * 60),
+        ],
+    );
+
+    let cache = WeatherCache::open(Some(path)).await.unwrap();
+
+    assert!(!cache.get_current(1.0, 1.0).await.unwrap().stale);
+    assert!(cache.get_current(2.0, 2.0).await.unwrap().stale);
+    // Too old to serve at all
+    assert!(cache.get_current(3.0, 3.0).await.is_none());
+}
+
+#[tokio::test]
+async fn clear_empties_the_cache_file() {
+    let path = cache_path("clear");
+
+    let cache = WeatherCache::open(Some(path.clone())).await.unwrap();
+    cache.set_current(10.0, 20.0, sample_weather()).await;
+    cache.clear();
+    cache.flush().await.unwrap();
+
+    let reopened = WeatherCache::open(Some(path)).await.unwrap();
+    assert!(reopened.get_current(10.0, 20.0).await.is_none());
+}
+
+#[tokio::test]
+async fn reported_timezones_outlive_cleared_responses() {
+    let path = cache_path("zones");
+    let weather = WeatherData {
+        timezone: Some("Asia/Tokyo".to_string()),
+        timezone_offset: 9 * 60 * 60,
+        ..sample_weather()
+    };
+
+    let cache = WeatherCache::open(Some(path.clone())).await.unwrap();
+    cache.set_current(10.0, 20.0, weather).await;
+    cache.clear();
+    cache.flush().await.unwrap();
+    drop(cache);
+
+    let reopened = WeatherCache::open(Some(path)).await.unwrap();
+    let zone = reopened.zone(10.0, 20.0).await.unwrap();
+
+    assert_eq!(zone.timezone.as_deref(), Some("Asia/Tokyo"));
+    assert_eq!(zone.timezone_offset, 9 * 60 * 60);
+    assert!(reopened.get_current(10.0, 20.0).await.is_none());
+    assert!(reopened.zone(11.0, 20.0).await.is_none());
+}
+
+#[tokio::test]
+async fn changes_are_saved_shortly_after_they_are_made() {
+    let path = cache_path("debounced");
+
+    let cache = WeatherCache::open(Some(path.clone())).await.unwrap();
+    cache.set_current(10.0, 20.0, sample_weather()).await;
+    cache.set_current(11.0, 20.0, sample_weather()).await;
+    tokio::time::sleep(Duration::from_secs(3)).await;
+
+    let reopened = WeatherCache::open(Some(path)).await.unwrap();
+    assert!(reopened.get_current(10.0, 20.0).await.is_some());
+    assert!(reopened.get_current(11.0, 20.0).await.is_some());
+}
+
+#[tokio::test]
+async fn corrupt_cache_file_starts_empty_and_is_replaced() {
+    let path = cache_path("corrupt");
+    std::fs::write(&path, r#"{"current": {"current_s0"#).unwrap();
+
+    let cache = WeatherCache::open(Some(path.clone())).await.unwrap();
+    assert!(cache.get_current(10.0, 20.0).await.is_none());
+    cache.set_current(10.0, 20.0, sample_weather()).await;
+    cache.flush().await.unwrap();
+
+    let reopened = WeatherCache::open(Some(path)).await.unwrap();
+    assert!(reopened.get_current(10.0, 20.0).await.is_some());
+}
+
+#[test]
+fn geohash_matches_reference_cells() {
+    assert_eq!(geohash::encode(57.64911, 10.40744, 11), "u4pruydqqvj");
+    assert_eq!(geohash::encode(42.6, -5.6, 5), "ezs42");
+    assert_eq!(geohash::encode(-25.382708, -49.265506, 6), "6gkzwg");
+}
+
+#[tokio::test]
+async fn nearby_locations_share_a_grid_cell() {
+    let cache = WeatherCache::open(None).await.unwrap();
+    cache.set_current(52.2297, 21.0122, sample_weather()).await;
+
+    // About 30 m away, in the same ~1 km cell
+    assert!(cache.get_current(52.2299, 21.0125).await.is_some());
+    // About 8 km away
+    assert!(cache.get_current(52.3, 21.0122).await.is_none());
+
+    // Coarser cells (~40 km) cover both
+    let config = WeatherCacheConfig {
+        grid_precision: 4,
+        ..cache.config()
+    };
+    cache.set_config(config).await.unwrap();
+    cache.set_current(52.2297, 21.0122, sample_weather()).await;
+    assert!(cache.get_current(52.3, 21.0122).await.is_some());
+}
+
+#[tokio::test]
+async fn series_download_fills_an_entry_per_slot() {
+    let fixture = format!(
+        "{}/tests/fixtures/mock_weather.json",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let forecast = MockProvider::new(fixture)
+        .fetch_forecast(0.0, 0.0)
+        .await
+        .unwrap();
+    let cache = WeatherCache::open(None).await.unwrap();
+
+    cache.set_series(10.0, 20.0, forecast.clone()).await;
+
+    for slot in &forecast.slots {
+        // Any time within the slot's hour, at any nearby location, hits the entry
+        let cached = cache
+            .get_forecast(10.0001, 20.0001, slot.timestamp + 25 * 60)
+            .await
+            .expect("Slot was not cached");
+        assert_eq!(cached.value.temperature, slot.point.temperature);
+    }
+
+    // Hours between slots are left to the caller
+    let first = forecast.slots[0].timestamp;
+    assert!(cache
+        .get_forecast(10.0, 20.0, first + 60 * 60)
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn entries_list_kind_cell_and_age() {
+    let path = cache_path("entries");
+    write_cache_file(&path, &[(1.0, 1.0, 60), (2.0, 2.0, 11 * 60)]);
+    let cache = WeatherCache::open(Some(path)).await.unwrap();
+
+    let entries = cache.entries();
+
+    assert_eq!(entries.len(), 2);
+    let newest = &entries[0];
+    assert_eq!(newest.kind, WeatherCacheKind::Current);
+    assert_eq!(
+        newest.cell,
+        geohash::encode(1.0, 1.0, DEFAULT_GRID_PRECISION)
+    );
+    assert_eq!(newest.location, "Testville");
+    assert!((60..120).contains(&newest.age_secs));
+    assert!(!newest.stale);
+    assert!(entries[1].stale);
+}
+
+#[tokio::test]
+async fn invalidate_by_location_and_kind() {
+    let fixture = format!(
+        "{}/tests/fixtures/mock_weather.json",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let forecast = MockProvider::new(fixture)
+        .fetch_forecast(0.0, 0.0)
+        .await
+        .unwrap();
+    let cache = WeatherCache::open(None).await.unwrap();
+    cache.set_current(10.0, 20.0, sample_weather()).await;
+    cache.set_current(30.0, 40.0, sample_weather()).await;
+    cache.set_series(10.0, 20.0, forecast.clone()).await;
+
+    // Only the current entry near (30, 40)
+    let removed = cache
+        .invalidate(Some(WeatherCacheKind::Current), Some((30.0001, 40.0001)))
+        .await;
+    assert_eq!(removed, 1);
+    assert!(cache.get_current(30.0, 40.0).await.is_none());
+    assert!(cache.get_current(10.0, 20.0).await.is_some());
+
+    // Every forecast entry, across locations
+    let removed = cache
+        .invalidate(Some(WeatherCacheKind::Forecast), None)
+        .await;
+    assert_eq!(removed, forecast.slots.len());
+    assert!(cache.get_series(10.0, 20.0).await.is_some());
+
+    // Everything left at (10, 20)
+    let removed = cache.invalidate(None, Some((10.0, 20.0))).await;
+    assert_eq!(removed, 2);
+    assert!(cache.entries().is_empty());
+}
+
+#[tokio::test]
+async fn stats_count_hits_misses_and_evictions() {
+    let cache = WeatherCache::open(None).await.unwrap();
+    let config = WeatherCacheConfig {
+        current_capacity: 2,
+        ..cache.config()
+    };
+    cache.set_config(config).await.unwrap();
+
+    assert!(cache.get_current(1.0, 1.0).await.is_none());
+    for i in 0..5 {
+        cache.set_current(i as f64, 0.0, sample_weather()).await;
+    }
+    let _ = cache.get_current(4.0, 0.0).await;
+
+    let stats = cache.stats().await;
+    assert_eq!(stats.misses, 1);
+    assert!(stats.hits + stats.misses >= 2);
+    assert!(stats.current_entries <= 2);
+    assert!(stats.evictions >= 3);
+}
+
+#[tokio::test]
+async fn config_changes_keep_entries_and_apply_new_ttls() {
+    let cache = WeatherCache::open(None).await.unwrap();
+    cache.set_current(10.0, 20.0, sample_weather()).await;
+
+    let config = WeatherCacheConfig {
+        current_ttl_secs: 1,
+        forecast_capacity: 50,
+        ..cache.config()
+    };
+    cache.set_config(config).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+
+    let cached = cache.get_current(10.0, 20.0).await.unwrap();
+    assert!(cached.stale);
+
+    let invalid = WeatherCacheConfig {
+        grid_precision: 13,
+        ..cache.config()
+    };
+    assert!(cache.set_config(invalid).await.is_err());
+}
+
+#[tokio::test]
+async fn config_changes_survive_a_restart() {
+    let path = cache_path("settings");
+
+    let cache = WeatherCache::open(None).await.unwrap();
+    assert!(!cache.load_config(path.clone()).await.unwrap());
+    let stored = cache
+        .set_config(WeatherCacheConfig {
+            current_ttl_secs: 120,
+            stale_while_revalidate: false,
+            ..cache.config()
+        })
+        .await
+        .unwrap();
+    assert_eq!(stored.current_ttl_secs, 120);
+
+    let restarted = WeatherCache::open(None).await.unwrap();
+    assert!(restarted.load_config(path).await.unwrap());
+    assert_eq!(restarted.config(), stored);
+}
+
+#[tokio::test]
+async fn fresh_restored_entry_is_served_without_a_request() {
+    let server = configured_server().await;
+
+    let response = weather::fetch_weather(FRESH_LAT, FRESH_LON).await.unwrap();
+
+    assert!(!response.stale);
+    assert_eq!(response.data.location, "Testville");
+    assert!(!server
+        .requests()
+        .iter()
+        .any(|target| target.contains("lat=40.4168")));
+}
+
+#[tokio::test]
+async fn stale_while_revalidate_serves_expired_entries_and_refreshes_them() {
+    let server = configured_server().await;
+
+    let response = weather::fetch_weather(STALE_LAT, STALE_LON).await.unwrap();
+    assert!(response.stale);
+    assert_eq!(response.data.location, "Testville");
+
+    // The background refresh replaces the entry with the provider's response
+    let mut refreshed = None;
+    for _ in 0..50 {
+        let cached = WeatherCache::global()
+            .get_current(STALE_LAT, STALE_LON)
+            .await
+            .unwrap();
+        if !cached.stale {
+            refreshed = Some(cached.value);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    assert_eq!(
+        refreshed.expect("Entry was not refreshed").location,
+        "Kraków"
+    );
+    assert!(server
+        .requests()
+        .iter()
+        .any(|target| target.contains("lat=48.8566")));
+
+    // With the mode off, an expired entry waits for the provider instead.
+    // Checked here so no other test sees the global flag switched off.
+    let cache = WeatherCache::global();
+    let config = cache.config();
+    cache
+        .set_config(WeatherCacheConfig {
+            stale_while_revalidate: false,
+            ..config.clone()
+        })
+        .await
+        .unwrap();
+    let data = weather::fetch_weather(BLOCKING_LAT, BLOCKING_LON).await;
+    cache.set_config(config).await.unwrap();
+
+    assert_eq!(data.unwrap().data.location, "Kraków");
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::weather::WeatherData;
+use command_center_4_lib::weather_classifier::{
+    classify, ClassifierThresholds, WeatherClassifier, WeatherType,
+};
+use command_center_4_lib::weather_preferences::UnitSystem;
+use common::sample_weather;
+
+fn feeling(feels_like: f64) -> WeatherData {
+    WeatherData {
+        feels_like,
+        ..sample_weather()
+    }
+}
+
+#[test]
+fn feels_like_temperature_picks_one_band() {
+    let thresholds = ClassifierThresholds::default();
+
+    assert_eq!(
+        classify(&feeling(30.0), &thresholds),
+        vec![WeatherType::Hot]
+    );
+    assert_eq!(
+        classify(&feeling(25.0), &thresholds),
+        vec![WeatherType::Hot]
+    );
+    assert_eq!(
+        classify(&feeling(20.0), &thresholds),
+        vec![WeatherType::Warm]
+    );
+    assert_eq!(
+        classify(&feeling(12.0), &thresholds),
+        vec![WeatherType::Cool]
+    );
+    assert_eq!(
+        classify(&feeling(-5.0), &thresholds),
+        vec![WeatherType::Cold]
+    );
+
+    // Readings in other units are compared in °C
+    let imperial = feeling(20.0).in_units(UnitSystem::Imperial);
+    assert_eq!(classify(&imperial, &thresholds), vec![WeatherType::Warm]);
+}
+
+#[test]
+fn precipitation_and_conditions_add_rainy_and_snowy() {
+    let thresholds = ClassifierThresholds::default();
+
+    let wet = WeatherData {
+        rain: 1.5,
+        ..feeling(12.0)
+    };
+    assert_eq!(
+        classify(&wet, &thresholds),
+        vec![WeatherType::Cool, WeatherType::Rainy]
+    );
+
+    // A drizzle icon counts even when too little rain was measured
+    let drizzle = WeatherData {
+        icon: "09n".to_string(),
+        ..feeling(12.0)
+    };
+    assert_eq!(
+        classify(&drizzle, &thresholds),
+        vec![WeatherType::Cool, WeatherType::Rainy]
+    );
+
+    let sleet = WeatherData {
+        rain: 0.5,
+        snow: 0.8,
+        icon: "13d".to_string(),
+        ..feeling(-1.0)
+    };
+    assert_eq!(
+        classify(&sleet, &thresholds),
+        vec![WeatherType::Cold, WeatherType::Rainy, WeatherType::Snowy]
+    );
+
+    let trace = WeatherData {
+        rain: 0.1,
+        ..feeling(12.0)
+    };
+    assert_eq!(classify(&trace, &thresholds), vec![WeatherType::Cool]);
+}
+
+#[test]
+fn thresholds_are_configurable_and_validated() {
+    let classifier = WeatherClassifier::global();
+    assert_eq!(classifier.classify(&feeling(22.0)), vec![WeatherType::Warm]);
+
+    classifier
+        .set_thresholds(ClassifierThresholds {
+            hot_feels_like: 21.0,
+            ..ClassifierThresholds::default()
+        })
+        .unwrap();
+    assert_eq!(classifier.classify(&feeling(22.0)), vec![WeatherType::Hot]);
+
+    let err = classifier
+        .set_thresholds(ClassifierThresholds {
+            warm_feels_like: 30.0,
+            ..ClassifierThresholds::default()
+        })
+        .unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+
+    let err = classifier
+        .set_thresholds(ClassifierThresholds {
+            rainy_mm: 0.0,
+            ..ClassifierThresholds::default()
+        })
+        .unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+    assert_eq!(classifier.thresholds().hot_feels_like, 21.0);
+}
+
+#[test]
+fn thresholds_survive_reopening() {
+    let path =
+        std::env::temp_dir().join(format!("classifier-thresholds-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let classifier = WeatherClassifier::open(Some(path.clone())).unwrap();
+    let stored = classifier
+        .set_thresholds(ClassifierThresholds {
+            cool_feels_like: 8.0,
+            ..ClassifierThresholds::default()
+        })
+        .unwrap();
+    assert_eq!(stored.cool_feels_like, 8.0);
+
+    let reopened = WeatherClassifier::open(Some(path.clone())).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(reopened.thresholds().cool_feels_like, 8.0);
+}
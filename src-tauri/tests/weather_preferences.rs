@@ -0,0 +1,150 @@
+mod common;
+
+use command_center_4_lib::env::EnvConfig;
+use command_center_4_lib::error::AppError;
+use command_center_4_lib::weather::{self, WeatherData};
+use command_center_4_lib::weather_preferences::{Preferences, UnitSystem, WeatherPreferences};
+use command_center_4_lib::weather_provider::{
+    OpenMeteoProvider, OpenWeatherMapProvider, WeatherProvider,
+};
+use common::{sample_weather, FixtureServer, Route};
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn readings_convert_between_unit_systems() {
+    let metric = WeatherData {
+        wind_gust: Some(10.0),
+        ..sample_weather()
+    };
+
+    let imperial = metric.clone().in_units(UnitSystem::Imperial);
+    assert_eq!(imperial.units, UnitSystem::Imperial);
+    assert_close(imperial.temperature, 59.0);
+    assert_close(imperial.feels_like, 57.2);
+    assert_close(imperial.wind_speed, 4.473_872_584_108_98);
+    assert_close(imperial.wind_gust.unwrap(), 22.369_362_920_544_9);
+    // Only temperature and wind speed depend on the unit system
+    assert_eq!(imperial.pressure, metric.pressure);
+    assert_eq!(imperial.visibility, metric.visibility);
+
+    let standard = imperial.in_units(UnitSystem::Standard);
+    assert_close(standard.temperature, 288.15);
+    assert_close(standard.wind_speed, 2.0);
+
+    let back = standard.in_units(UnitSystem::Metric);
+    assert_close(back.temperature, 15.0);
+    assert_close(back.feels_like, 14.0);
+}
+
+#[test]
+fn preferences_normalize_language_and_persist() {
+    let path = temp_path("weather-preferences");
+    let preferences = Preferences::open(Some(path.clone())).unwrap();
+    assert_eq!(preferences.get(), WeatherPreferences::default());
+
+    let stored = preferences
+        .set(WeatherPreferences {
+            units: UnitSystem::Imperial,
+            language: " PT-br ".to_string(),
+        })
+        .unwrap();
+    assert_eq!(stored.language, "pt_br");
+
+    for language in ["", "english", "e1", "pt_bra"] {
+        let err = preferences
+            .set(WeatherPreferences {
+                units: UnitSystem::Metric,
+                language: language.to_string(),
+            })
+            .unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput { .. }), "{:?}", err);
+    }
+
+    let reopened = Preferences::open(Some(path.clone())).unwrap();
+    assert_eq!(reopened.get(), stored);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn open_weather_map_requests_condition_text_in_language() {
+    let server = FixtureServer::start(vec![Route::fixture("/weather", "openweather_current.json")]);
+    let provider =
+        OpenWeatherMapProvider::new("test-key".to_string(), server.base_url()).with_language("de");
+
+    let data = provider.fetch_current(50.0614, 19.9366).await.unwrap();
+
+    assert_eq!(data.units, UnitSystem::Metric);
+    let requests = server.requests();
+    assert!(requests[0].contains("units=metric"), "{}", requests[0]);
+    assert!(requests[0].contains("lang=de"), "{}", requests[0]);
+}
+
+#[tokio::test]
+async fn open_meteo_translates_weather_codes() {
+    let server = FixtureServer::start(vec![Route::fixture("/forecast", "open_meteo_current.json")]);
+    let provider = |language: &str| {
+        OpenMeteoProvider::new(server.base_url(), server.base_url()).with_language(language)
+    };
+
+    let data = provider("pl")
+        .fetch_current(50.0614, 19.9366)
+        .await
+        .unwrap();
+    assert_eq!(data.description, "pochmurno");
+
+    // Languages without a translation keep the English text
+    let data = provider("pt_br")
+        .fetch_current(50.0614, 19.9366)
+        .await
+        .unwrap();
+    assert_eq!(data.description, "overcast");
+}
+
+#[tokio::test]
+async fn switching_units_converts_cached_weather_without_refetching() {
+    let server = FixtureServer::start(vec![Route::fixture("/weather", "openweather_current.json")]);
+    std::env::set_var("WEATHER_PROVIDER", "open_weather_map");
+    std::env::set_var("OPEN_WEATHER_API_KEY", "test-key");
+    std::env::set_var("OPEN_WEATHER_BASE_URL", server.base_url());
+    EnvConfig::init().expect("Failed to initialize EnvConfig");
+
+    let metric = weather::fetch_weather(50.0614, 19.9366).await.unwrap().data;
+    assert_eq!(metric.units, UnitSystem::Metric);
+    assert_eq!(metric.temperature, 14.62);
+
+    weather::set_preferences(WeatherPreferences {
+        units: UnitSystem::Imperial,
+        language: "en".to_string(),
+    })
+    .unwrap();
+    let imperial = weather::fetch_weather(50.0614, 19.9366).await.unwrap().data;
+    assert_eq!(imperial.units, UnitSystem::Imperial);
+    assert_close(imperial.temperature, 14.62 * 9.0 / 5.0 + 32.0);
+    assert_eq!(server.request_count("/weather"), 1);
+
+    // Condition text has to come from the provider again
+    weather::set_preferences(WeatherPreferences {
+        units: UnitSystem::Imperial,
+        language: "fr".to_string(),
+    })
+    .unwrap();
+    weather::fetch_weather(50.0614, 19.9366).await.unwrap();
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].contains("lang=fr"), "{}", requests[1]);
+}
@@ -0,0 +1,34 @@
+use command_center_4_lib::env::EnvConfig;
+use command_center_4_lib::weather_provider::{self, WeatherProviderKind};
+
+#[test]
+fn chosen_provider_survives_a_restart() {
+    let path = std::env::temp_dir().join(format!("weather-provider-{}.json", std::process::id()));
+    let mock = format!(
+        "{}/tests/fixtures/mock_weather.json",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    std::env::remove_var("WEATHER_PROVIDER");
+    std::env::remove_var("OPEN_WEATHER_API_KEY");
+    std::env::set_var("WEATHER_MOCK_FILE", mock);
+    EnvConfig::init().expect("Failed to initialize EnvConfig");
+
+    // The choice saved by the previous run replaces the default
+    std::fs::write(&path, r#"{"provider":"mock"}"#).unwrap();
+    weather_provider::init(path.clone()).unwrap();
+    assert_eq!(weather_provider::active().kind(), WeatherProviderKind::Mock);
+
+    weather_provider::set_active(WeatherProviderKind::OpenMeteo).unwrap();
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("open_meteo"), "{}", saved);
+
+    // A provider that cannot be created is neither used nor saved
+    assert!(weather_provider::set_active(WeatherProviderKind::OpenWeatherMap).is_err());
+    assert_eq!(
+        weather_provider::active().kind(),
+        WeatherProviderKind::OpenMeteo
+    );
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), saved);
+
+    let _ = std::fs::remove_file(&path);
+}